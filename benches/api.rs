@@ -1,6 +1,7 @@
 #![cfg(feature = "benchmarking")] // Lint
 use criterion::{criterion_group, criterion_main, Criterion};
 use pqc_kyber::*;
+use std::convert::TryFrom;
 
 // Benchmarking key generation
 fn keypair(c: &mut Criterion) {
@@ -31,8 +32,8 @@ fn encap(c: &mut Criterion) {
 
 // Decapsulating a single correct ciphertext
 fn decap(c: &mut Criterion) {
-    let sk = decode_hex(SK);
-    let ct = decode_hex(CT);
+    let sk = SecretKey::try_from(&decode_hex(SK)[..]).unwrap();
+    let ct = Ciphertext::try_from(&decode_hex(CT)[..]).unwrap();
     c.bench_function("Decapsulate", |b| {
         b.iter(|| {
             let _dec = decapsulate(&ct, &sk);
@@ -42,8 +43,8 @@ fn decap(c: &mut Criterion) {
 
 // Decapsulating a single incorrect ciphertext
 fn decap_fail(c: &mut Criterion) {
-    let sk = decode_hex(BAD_SK);
-    let ct = decode_hex(CT);
+    let sk = SecretKey::try_from(&decode_hex(BAD_SK)[..]).unwrap();
+    let ct = Ciphertext::try_from(&decode_hex(CT)[..]).unwrap();
     c.bench_function("Decapsulate Failure", |b| {
         b.iter(|| {
             let _dec = decapsulate(&ct, &sk);
@@ -1,10 +1,12 @@
 use crate::{
     error::KyberError,
-    kem::*,
-    kex::{Decapsulated, Encapsulated, PublicKey, SecretKey},
+    kex::{Decapsulated, Encapsulated},
     params::*,
-    CryptoRng, RngCore,
+    rng::randombytes,
+    types::ct_eq,
+    Choice, Ciphertext, CryptoRng, PublicKey, RngCore, SecretKey, SharedSecret,
 };
+use core::{convert::TryFrom, fmt, marker::PhantomData, ops::Deref};
 #[cfg(feature = "zeroize")]
 use zeroize::{Zeroize, ZeroizeOnDrop};
 /// Keypair generation with a provided RNG.
@@ -21,10 +23,7 @@ pub fn keypair<R>(rng: &mut R) -> Result<Keypair, KyberError>
 where
     R: RngCore + CryptoRng,
 {
-    let mut public = [0u8; KYBER_PUBLICKEYBYTES];
-    let mut secret = [0u8; KYBER_SECRETKEYBYTES];
-    crypto_kem_keypair(&mut public, &mut secret, rng, None)?;
-    Ok(Keypair { public, secret })
+    DefaultParams::keypair(rng)
 }
 /// Verify that given secret and public key matches and put them in
 /// the KeyPair structure after zeroize them if asked.
@@ -35,13 +34,13 @@ where
 /// let mut rng = rand::thread_rng();
 /// let keys = keypair(&mut rng)?;
 /// let mut public = keys.public;
-/// let mut secret = keys.secret;
+/// let mut secret = keys.secret.clone();
 /// let _ = keypairfrom(&mut public, &mut secret, &mut rng)?;
 /// # Ok(())}
 /// ```
 pub fn keypairfrom<R>(
-    public: &mut [u8; KYBER_PUBLICKEYBYTES],
-    secret: &mut [u8; KYBER_SECRETKEYBYTES],
+    public: &mut PublicKey,
+    secret: &mut SecretKey,
     rng: &mut R,
 ) -> Result<Keypair, KyberError>
 where
@@ -52,18 +51,14 @@ where
     let expected_shared_secret = decapsulate(&ciphertext, secret)?;
     //If it does match, return a KeyPair
     if expected_shared_secret == shared_secret {
-        let mut public2 = *public;
-        let mut secret2 = *secret;
         let key = Keypair {
-            public: public2,
-            secret: secret2,
+            public: *public,
+            secret: secret.clone(),
         };
         #[cfg(feature = "zeroize")]
         {
             public.zeroize();
             secret.zeroize();
-            public2.zeroize();
-            secret2.zeroize();
         }
         Ok(key)
     } else {
@@ -74,6 +69,9 @@ where
 /// Encapsulates a public key returning the ciphertext to send
 /// and the shared secret
 ///
+/// Public keys encoding coefficients that are not reduced modulo q fail with
+/// [`KyberError::InvalidPublicKey`].
+///
 /// ### Example
 /// ```
 /// # use pqc_kyber::*;
@@ -83,22 +81,20 @@ where
 /// let (ciphertext, shared_secret) = encapsulate(&keys.public, &mut rng)?;
 /// # Ok(())}
 /// ```
-pub fn encapsulate<R>(pk: &[u8], rng: &mut R) -> Encapsulated
+pub fn encapsulate<R>(pk: &PublicKey, rng: &mut R) -> Encapsulated
 where
     R: CryptoRng + RngCore,
 {
-    if pk.len() != KYBER_PUBLICKEYBYTES {
-        return Err(KyberError::InvalidInput);
-    }
-    let mut ct = [0u8; KYBER_CIPHERTEXTBYTES];
-    let mut ss = [0u8; KYBER_SSBYTES];
-    crypto_kem_enc(&mut ct, &mut ss, pk, rng, None)?;
-    Ok((ct, ss))
+    DefaultParams::encapsulate(pk, rng)
 }
 
 /// Decapsulates ciphertext with a secret key, the result will contain
 /// a KyberError if decapsulation fails
 ///
+/// Takes either a [`SecretKey`] or a [`SeedSecretKey`], see
+/// [`DecapsulationKey`]. Secret keys whose stored public key hash does not
+/// match fail with [`KyberError::InvalidSecretKey`].
+///
 /// ### Example
 /// ```
 /// # use pqc_kyber::*;
@@ -110,25 +106,182 @@ where
 /// assert_eq!(ss1, ss2);
 /// #  Ok(())}
 /// ```
-pub fn decapsulate(ct: &[u8], sk: &[u8]) -> Decapsulated {
-    if ct.len() != KYBER_CIPHERTEXTBYTES || sk.len() != KYBER_SECRETKEYBYTES {
-        return Err(KyberError::InvalidInput);
+pub fn decapsulate<K: DecapsulationKey + ?Sized>(ct: &Ciphertext, sk: &K) -> Decapsulated {
+    sk.decapsulate_ciphertext(ct)
+}
+
+/// A secret key that can be passed to [`decapsulate`]
+///
+/// Implemented by the full [`SecretKey`] and by [`SeedSecretKey`], which is
+/// expanded on each call.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = Keypair::generate_with_seed(&mut rng)?;
+/// let (ct, ss) = encapsulate(&keys.public, &mut rng)?;
+/// assert_eq!(decapsulate(&ct, &keys.secret)?, ss);
+/// assert_eq!(decapsulate(&ct, &keys.to_seed())?, ss);
+/// #  Ok(())}
+/// ```
+pub trait DecapsulationKey<P: KyberParams = DefaultParams> {
+    /// Decapsulates ciphertext with this key, the same as
+    /// [`KyberParams::decapsulate`]
+    fn decapsulate_ciphertext(&self, ct: &Ciphertext<P>) -> Decapsulated;
+}
+
+impl<P: KyberParams> DecapsulationKey<P> for SecretKey<P> {
+    fn decapsulate_ciphertext(&self, ct: &Ciphertext<P>) -> Decapsulated {
+        P::decapsulate(ct, self)
     }
-    let mut ss = [0u8; KYBER_SSBYTES];
-    crypto_kem_dec(&mut ss, ct, sk);
-    Ok(ss)
+}
+
+impl<P: KyberParams> DecapsulationKey<P> for SeedSecretKey<P> {
+    fn decapsulate_ciphertext(&self, ct: &Ciphertext<P>) -> Decapsulated {
+        self.decapsulate(ct)
+    }
+}
+
+/// Encapsulates a public key, filling `out` with key material bound to an
+/// application `context` instead of returning the 32 byte shared secret
+///
+/// The key material is SHAKE256 over the pre-key, `H(c)`, a fixed label and
+/// the length prefixed context, or HKDF-SHA512 with the pre-key and `H(c)`
+/// as input keying material and the same label and context as info in 90s
+/// mode. It never equals the shared secret of [`encapsulate`], even for an
+/// empty context. `out` can be of any length, up to
+/// 16320 bytes in 90s mode, longer fails with [`KyberError::InvalidInput`].
+/// Shorter outputs for the same context are prefixes of longer ones, split
+/// one output into keys rather than asking for each with a different length.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let (mut alice, mut bob) = ([0u8; 64], [0u8; 64]);
+/// let ct = encapsulate_with_context(&keys.public, b"my protocol v1", &mut alice, &mut rng)?;
+/// decapsulate_with_context(&ct, &keys.secret, b"my protocol v1", &mut bob)?;
+/// assert_eq!(alice, bob);
+/// let (encryption_key, mac_key) = alice.split_at(32);
+/// # Ok(())}
+/// ```
+pub fn encapsulate_with_context<R>(
+    pk: &PublicKey,
+    context: &[u8],
+    out: &mut [u8],
+    rng: &mut R,
+) -> Result<Ciphertext, KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    DefaultParams::encapsulate_with_context(pk, context, out, rng)
+}
+
+/// Decapsulates ciphertext with a secret key, filling `out` with key
+/// material bound to an application `context`
+///
+/// The counterpart of [`encapsulate_with_context`], both sides have to use
+/// the same context and output length. Invalid ciphertexts are implicitly
+/// rejected, `out` is then pseudo-random.
+pub fn decapsulate_with_context(
+    ct: &Ciphertext,
+    sk: &SecretKey,
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), KyberError> {
+    DefaultParams::decapsulate_with_context(ct, sk, context, out)
+}
+
+/// Decapsulates ciphertext with a secret key, returning the shared secret
+/// and whether the ciphertext was valid
+///
+/// The [`Choice`] is 1 when the ciphertext passed the re-encryption check.
+/// Invalid ciphertexts still yield the implicit rejection secret and the
+/// call runs in constant time either way, keep the `Choice` out of any
+/// branch an attacker can observe. Secret keys whose stored public key hash
+/// does not match fail with [`KyberError::InvalidSecretKey`].
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let (ct, ss1) = encapsulate(&keys.public, &mut rng)?;
+/// let (ss2, valid) = decapsulate_checked(&ct, &keys.secret)?;
+/// assert!(bool::from(valid));
+/// assert_eq!(ss1, ss2);
+/// #  Ok(())}
+/// ```
+pub fn decapsulate_checked(
+    ct: &Ciphertext,
+    sk: &SecretKey,
+) -> Result<(SharedSecret, Choice), KyberError> {
+    DefaultParams::decapsulate_checked(ct, sk)
+}
+
+/// Decapsulates ciphertext with a secret key, returning
+/// [`KyberError::Decapsulation`] for invalid ciphertexts instead of the
+/// implicit rejection secret
+///
+/// An opt-in for protocols that call for explicit rejection. The error
+/// tells the caller, and anyone observing its handling, which ciphertexts
+/// were invalid, prefer [`decapsulate`] unless the protocol needs it.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let (mut ct, _) = encapsulate(&keys.public, &mut rng)?;
+/// ct.as_mut()[0] ^= 1;
+/// assert_eq!(
+///     decapsulate_explicit(&ct, &keys.secret),
+///     Err(KyberError::Decapsulation)
+/// );
+/// #  Ok(())}
+/// ```
+pub fn decapsulate_explicit(ct: &Ciphertext, sk: &SecretKey) -> Decapsulated {
+    DefaultParams::decapsulate_explicit(ct, sk)
 }
 
 /// A public/secret keypair for use with Kyber.
 ///
-/// Byte lengths of the keys are determined by the security level chosen.
+/// Byte lengths of the keys are determined by the security level `P`, which
+/// defaults to the one chosen with feature flags.
+///
+/// To keep the 64 byte seed of a keypair, generate it with
+/// [`Keypair::generate_with_seed`] or expand it with [`Keypair::from_seed`],
+/// both return a [`KeypairWithSeed`].
 #[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "zeroize", derive(Zeroize, ZeroizeOnDrop))]
-pub struct Keypair {
-    pub public: PublicKey,
-    pub secret: SecretKey,
+pub struct Keypair<P: KyberParams = DefaultParams> {
+    pub public: PublicKey<P>,
+    pub secret: SecretKey<P>,
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Zeroize for Keypair<P> {
+    fn zeroize(&mut self) {
+        self.public.zeroize();
+        self.secret.zeroize();
+    }
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Drop for Keypair<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> ZeroizeOnDrop for Keypair<P> {}
+
 impl Keypair {
     /// Securely generates a new keypair`
     /// ```
@@ -136,8 +289,10 @@ impl Keypair {
     /// # fn main() -> Result<(), KyberError> {
     /// let mut rng = rand::thread_rng();
     /// let keys = Keypair::generate(&mut rng)?;
+    /// # use std::convert::TryFrom;
     /// # let empty_keys = Keypair{
-    ///   public: [0u8; KYBER_PUBLICKEYBYTES], secret: [0u8; KYBER_SECRETKEYBYTES]
+    ///   public: PublicKey::try_from(&[0u8; KYBER_PUBLICKEYBYTES][..])?,
+    ///   secret: SecretKey::try_from(&[0u8; KYBER_SECRETKEYBYTES][..])?,
     /// };
     /// # assert!(empty_keys != keys);
     /// # Ok(()) }
@@ -145,6 +300,24 @@ impl Keypair {
     pub fn generate<R: CryptoRng + RngCore>(rng: &mut R) -> Result<Keypair, KyberError> {
         keypair(rng)
     }
+    /// Securely generates a new keypair retaining the 64 byte seed it was
+    /// expanded from, the seed can be stored instead of the secret key.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # fn main() -> Result<(), KyberError> {
+    /// let mut rng = rand::thread_rng();
+    /// let keys = Keypair::generate_with_seed(&mut rng)?;
+    /// let seed = keys.to_seed();
+    /// assert_eq!(Keypair::from_seed(&seed), keys);
+    /// # Ok(()) }
+    /// ```
+    pub fn generate_with_seed<R: CryptoRng + RngCore>(
+        rng: &mut R,
+    ) -> Result<KeypairWithSeed, KyberError> {
+        let mut seed = SeedSecretKey::zeroed();
+        randombytes(&mut seed.0, SeedSecretKey::<DefaultParams>::LEN, rng)?;
+        Ok(Keypair::from_seed(&seed))
+    }
     /// Verify that given secret and public key matches and put them in
     /// the KeyPair structure after zeroize them if asked.
     /// ### Example
@@ -154,20 +327,209 @@ impl Keypair {
     /// let mut rng = rand::thread_rng();
     /// let keys = keypair(&mut rng)?;
     /// let mut public = keys.public;
-    /// let mut secret = keys.secret;
+    /// let mut secret = keys.secret.clone();
     /// let _ = Keypair::import(&mut public, &mut secret, &mut rng)?;
     /// # Ok(())}
     /// ```
     pub fn import<R: CryptoRng + RngCore>(
-        public: &mut [u8; KYBER_PUBLICKEYBYTES],
-        secret: &mut [u8; KYBER_SECRETKEYBYTES],
-        rng: &mut R
+        public: &mut PublicKey,
+        secret: &mut SecretKey,
+        rng: &mut R,
     ) -> Result<Keypair, KyberError> {
         keypairfrom(public, secret, rng)
     }
 }
 
-struct DummyRng {}
+impl<P: KyberParams> Keypair<P> {
+    /// Expands a seed into its keypair and retains the seed, the same keys
+    /// as [`derive`] and [`SeedSecretKey::expand`].
+    pub fn from_seed(seed: &SeedSecretKey<P>) -> KeypairWithSeed<P> {
+        KeypairWithSeed {
+            keys: seed.expand(),
+            seed: seed.clone(),
+        }
+    }
+}
+
+/// A [`Keypair`] that retains the 64 byte seed it was expanded from, made by
+/// [`Keypair::generate_with_seed`] and [`Keypair::from_seed`].
+///
+/// Dereferences to the [`Keypair`], so the keys are used as usual.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = Keypair::generate_with_seed(&mut rng)?;
+/// let (ct, ss1) = encapsulate(&keys.public, &mut rng)?;
+/// let ss2 = decapsulate(&ct, &keys.secret)?;
+/// assert_eq!(ss1, ss2);
+/// let stored = keys.to_seed();
+/// assert_eq!(stored.expand(), *keys);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeypairWithSeed<P: KyberParams = DefaultParams> {
+    keys: Keypair<P>,
+    seed: SeedSecretKey<P>,
+}
+
+impl<P: KyberParams> KeypairWithSeed<P> {
+    /// Returns the seed the keypair was expanded from
+    pub fn to_seed(&self) -> SeedSecretKey<P> {
+        self.seed.clone()
+    }
+
+    /// Drops the seed, keeping only the keys
+    pub fn into_keypair(self) -> Keypair<P> {
+        self.keys
+    }
+}
+
+impl<P: KyberParams> Deref for KeypairWithSeed<P> {
+    type Target = Keypair<P>;
+
+    fn deref(&self) -> &Keypair<P> {
+        &self.keys
+    }
+}
+
+impl<P: KyberParams> From<KeypairWithSeed<P>> for Keypair<P> {
+    fn from(keys: KeypairWithSeed<P>) -> Keypair<P> {
+        keys.into_keypair()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Zeroize for KeypairWithSeed<P> {
+    fn zeroize(&mut self) {
+        self.keys.zeroize();
+        self.seed.zeroize();
+    }
+}
+
+// Both fields are wiped on drop
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> ZeroizeOnDrop for KeypairWithSeed<P> {}
+
+/// A secret key in its compact form, the 64 byte seed `d || z` the keypair
+/// is deterministically expanded from as in [`derive`].
+///
+/// The seed is expanded again on every use, trading a key generation per
+/// decapsulation for storing 64 bytes instead of the full secret key.
+/// Redacted when printed and zeroized on drop with the `zeroize` feature.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let seed = Keypair::generate_with_seed(&mut rng)?.to_seed();
+/// let (ct, ss1) = encapsulate(&seed.public_key(), &mut rng)?;
+/// let ss2 = decapsulate(&ct, &seed)?;
+/// assert_eq!(ss1, ss2);
+/// # Ok(()) }
+/// ```
+pub struct SeedSecretKey<P: KyberParams = DefaultParams>(
+    pub(crate) [u8; 2 * KYBER_SYMBYTES],
+    PhantomData<P>,
+);
+
+impl<P: KyberParams> SeedSecretKey<P> {
+    /// Size in bytes
+    pub const LEN: usize = 2 * KYBER_SYMBYTES;
+
+    pub(crate) fn zeroed() -> Self {
+        SeedSecretKey([0u8; 2 * KYBER_SYMBYTES], PhantomData)
+    }
+
+    /// Expands the seed into its keypair
+    pub fn expand(&self) -> Keypair<P> {
+        let mut public = PublicKey::zeroed();
+        let mut secret = SecretKey::zeroed();
+        self.expand_into(public.as_mut(), secret.as_mut());
+        Keypair { public, secret }
+    }
+
+    /// Expands the seed and returns the public key
+    pub fn public_key(&self) -> PublicKey<P> {
+        self.expand().public
+    }
+
+    /// Expands the seed and decapsulates a ciphertext with the secret key,
+    /// the same as [`KyberParams::decapsulate`]
+    pub fn decapsulate(&self, ct: &Ciphertext<P>) -> Decapsulated {
+        let mut public = PublicKey::<P>::zeroed();
+        let mut secret = SecretKey::<P>::zeroed();
+        self.expand_into(public.as_mut(), secret.as_mut());
+        P::decapsulate(ct, &secret)
+    }
+
+    fn expand_into(&self, pk: &mut [u8], sk: &mut [u8]) {
+        let (d, z) = self.0.split_at(KYBER_SYMBYTES);
+        // The RNG is never used when seeded, this can't fail
+        let _ = P::crypto_kem_keypair(pk, sk, &mut DummyRng {}, Some((d, z)));
+    }
+}
+
+impl<P: KyberParams> AsRef<[u8]> for SeedSecretKey<P> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<P: KyberParams> TryFrom<&[u8]> for SeedSecretKey<P> {
+    type Error = KyberError;
+
+    /// Fails with [`KyberError::InvalidInput`] if the slice is not
+    /// exactly the right size.
+    fn try_from(bytes: &[u8]) -> Result<Self, KyberError> {
+        if bytes.len() != Self::LEN {
+            return Err(KyberError::InvalidInput);
+        }
+        let mut out = Self::zeroed();
+        out.0.copy_from_slice(bytes);
+        Ok(out)
+    }
+}
+
+impl<P: KyberParams> Clone for SeedSecretKey<P> {
+    fn clone(&self) -> Self {
+        SeedSecretKey(self.0, PhantomData)
+    }
+}
+
+impl<P: KyberParams> PartialEq for SeedSecretKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl<P: KyberParams> Eq for SeedSecretKey<P> {}
+
+impl<P: KyberParams> fmt::Debug for SeedSecretKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SeedSecretKey(<redacted>)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Zeroize for SeedSecretKey<P> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Drop for SeedSecretKey<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> ZeroizeOnDrop for SeedSecretKey<P> {}
+
+pub(crate) struct DummyRng {}
 impl CryptoRng for DummyRng {}
 impl RngCore for DummyRng {
     fn next_u32(&mut self) -> u32 {
@@ -187,26 +549,15 @@ impl RngCore for DummyRng {
 /// Deterministically derive a keypair from a seed as specified
 /// in draft-schwabe-cfrg-kyber.
 pub fn derive(seed: &[u8]) -> Result<Keypair, KyberError> {
-    let mut public = [0u8; KYBER_PUBLICKEYBYTES];
-    let mut secret = [0u8; KYBER_SECRETKEYBYTES];
-    let mut _rng = DummyRng {};
-    if seed.len() != 64 {
-        return Err(KyberError::InvalidInput);
-    }
-    crypto_kem_keypair(
-        &mut public,
-        &mut secret,
-        &mut _rng,
-        Some((&seed[..32], &seed[32..])),
-    )?;
-    Ok(Keypair { public, secret })
+    Ok(SeedSecretKey::try_from(seed)?.expand())
 }
 
 /// Extracts public key from private key.
-pub fn public(sk: &[u8]) -> PublicKey {
-    let mut pk = [0u8; KYBER_INDCPA_PUBLICKEYBYTES];
-    pk.copy_from_slice(
-        &sk[KYBER_INDCPA_SECRETKEYBYTES..KYBER_INDCPA_SECRETKEYBYTES + KYBER_INDCPA_PUBLICKEYBYTES],
+pub fn public(sk: &SecretKey) -> PublicKey {
+    let mut pk = PublicKey::zeroed();
+    pk.as_mut().copy_from_slice(
+        &sk.as_ref()[KYBER_INDCPA_SECRETKEYBYTES
+            ..KYBER_INDCPA_SECRETKEYBYTES + KYBER_INDCPA_PUBLICKEYBYTES],
     );
     pk
 }
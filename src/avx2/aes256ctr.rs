@@ -5,6 +5,8 @@
 #![cfg(feature = "90s")]
 
 use core::arch::x86_64::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[derive(Clone, Copy)]
 #[repr(C)]
@@ -24,6 +26,15 @@ impl Aes256CtrCtx {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for Aes256CtrCtx {
+    fn zeroize(&mut self) {
+        self.rkeys.zeroize();
+        self.n.zeroize();
+    }
+}
+
+#[target_feature(enable = "aes,avx2")]
 unsafe fn aesni_encrypt4(out: &mut [u8], n: &mut __m128i, rkeys: &[__m128i; 16]) {
     let idx: __m128i = _mm_set_epi8(8, 9, 10, 11, 12, 13, 14, 15, 7, 6, 5, 4, 3, 2, 1, 0);
 
@@ -68,15 +79,18 @@ unsafe fn aesni_encrypt4(out: &mut [u8], n: &mut __m128i, rkeys: &[__m128i; 16])
 }
 
 // Casting aliases
+#[target_feature(enable = "aes,avx2")]
 unsafe fn cast_128i(x: __m128) -> __m128i {
     _mm_castps_si128(x)
 }
 
+#[target_feature(enable = "aes,avx2")]
 unsafe fn cast_128(x: __m128i) -> __m128 {
     _mm_castsi128_ps(x)
 }
 
-pub fn aes256ctr_init(state: &mut Aes256CtrCtx, key: &[u8], nonce: [u8; 12]) {
+#[target_feature(enable = "aes,avx2")]
+pub unsafe fn aes256ctr_init(state: &mut Aes256CtrCtx, key: &[u8], nonce: [u8; 12]) {
     unsafe {
         let mut idx = 0;
         let key0 = _mm_loadu_si128(key.as_ptr() as *const __m128i);
@@ -138,7 +152,8 @@ pub fn aes256ctr_init(state: &mut Aes256CtrCtx, key: &[u8], nonce: [u8; 12]) {
     }
 }
 
-pub fn aes256ctr_squeezeblocks(out: &mut [u8], nblocks: usize, state: &mut Aes256CtrCtx) {
+#[target_feature(enable = "aes,avx2")]
+pub unsafe fn aes256ctr_squeezeblocks(out: &mut [u8], nblocks: usize, state: &mut Aes256CtrCtx) {
     let mut idx = 0;
     for _ in 0..nblocks {
         unsafe {
@@ -149,16 +164,12 @@ pub fn aes256ctr_squeezeblocks(out: &mut [u8], nblocks: usize, state: &mut Aes25
 }
 
 #[cfg(feature = "90s")]
-pub fn aes256ctr_prf(out: &mut [u8], mut outlen: usize, seed: &[u8], nonce: u8) {
+#[target_feature(enable = "aes,avx2")]
+pub unsafe fn aes256ctr_prf(out: &mut [u8], mut outlen: usize, seed: &[u8], nonce: u8) {
     let mut buf = [0u8; 64];
     let mut idx = 0;
     let mut pad_nonce = [0u8; 12];
-    let mut state = unsafe {
-        Aes256CtrCtx {
-            rkeys: [_mm_setzero_si128(); 16],
-            n: _mm_setzero_si128(),
-        }
-    };
+    let mut state = Aes256CtrCtx::new();
 
     pad_nonce[0] = nonce;
     aes256ctr_init(&mut state, seed, pad_nonce);
@@ -1,11 +1,13 @@
 #![allow(dead_code)]
 
-use crate::avx2::rejsample::REJ_UNIFORM_AVX_NBLOCKS;
+use super::poly::NOISE_NBLOCKS;
+use super::rejsample::REJ_UNIFORM_AVX_NBLOCKS;
 use crate::fips202::{SHAKE128_RATE, SHAKE256_RATE};
 use crate::params::*;
-use crate::poly::NOISE_NBLOCKS;
 use crate::symmetric::*;
 use core::arch::x86_64::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[derive(Copy, Clone)]
 #[repr(C, align(32))]
@@ -64,6 +66,13 @@ impl IndcpaBuf {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for IndcpaBuf {
+    fn zeroize(&mut self) {
+        unsafe { self.coeffs.zeroize() }
+    }
+}
+
 #[repr(C, align(8))]
 pub union Eta2Buf {
     pub coeffs: [u8; KYBER_ETA2 * KYBER_N / 4],
@@ -78,6 +87,13 @@ impl Eta2Buf {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for Eta2Buf {
+    fn zeroize(&mut self) {
+        unsafe { self.coeffs.zeroize() }
+    }
+}
+
 #[derive(Copy, Clone)]
 #[repr(C, align(8))]
 pub union Eta4xBuf {
@@ -92,3 +108,10 @@ impl Eta4xBuf {
         }
     }
 }
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Eta4xBuf {
+    fn zeroize(&mut self) {
+        unsafe { self.coeffs.zeroize() }
+    }
+}
@@ -0,0 +1,48 @@
+// Multiplication of polynomials in the NTT domain, ported from the
+// basemul.S kernel of the C reference implementation.
+use super::{consts::*, fq::*, poly::Poly};
+use core::arch::x86_64::*;
+
+// Multiplies 64 coefficients as 32 degree one polynomials modulo
+// X^2 - zeta, the zetas are read from QDATA at zetas
+#[target_feature(enable = "avx2")]
+unsafe fn schoolbook(r: &mut [__m256i], a: &[__m256i], b: &[__m256i], zetas: usize) {
+    let q = qdata(_16XQ);
+    let qinv = qdata(_16XQINV);
+    let (a0, b0, a1, b1) = (a[0], a[1], a[2], a[3]);
+    let (c0, d0, c1, d1) = (b[0], b[1], b[2], b[3]);
+
+    let a0lo = _mm256_mullo_epi16(qinv, a0);
+    let b0lo = _mm256_mullo_epi16(qinv, b0);
+    let a1lo = _mm256_mullo_epi16(qinv, a1);
+    let b1lo = _mm256_mullo_epi16(qinv, b1);
+
+    let a0c0 = fqmulprecomp(a0lo, a0, c0, q);
+    let a0d0 = fqmulprecomp(a0lo, a0, d0, q);
+    let b0c0 = fqmulprecomp(b0lo, b0, c0, q);
+    let b0d0 = fqmulprecomp(b0lo, b0, d0, q);
+
+    let a1c1 = fqmulprecomp(a1lo, a1, c1, q);
+    let a1d1 = fqmulprecomp(a1lo, a1, d1, q);
+    let b1c1 = fqmulprecomp(b1lo, b1, c1, q);
+    let b1d1 = fqmulprecomp(b1lo, b1, d1, q);
+
+    let zl = qdata(zetas);
+    let zh = qdata(zetas + 16);
+    let rb0d0 = fqmulprecomp(zl, zh, b0d0, q);
+    let rb1d1 = fqmulprecomp(zl, zh, b1d1, q);
+
+    r[0] = _mm256_add_epi16(rb0d0, a0c0);
+    r[1] = _mm256_add_epi16(a0d0, b0c0);
+    r[2] = _mm256_sub_epi16(a1c1, rb1d1);
+    r[3] = _mm256_add_epi16(a1d1, b1c1);
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn basemul_avx(r: &mut Poly, a: &Poly, b: &Poly) {
+    let zetas = _ZETAS_EXP + 176;
+    schoolbook(&mut r.vec[..4], &a.vec[..4], &b.vec[..4], zetas);
+    schoolbook(&mut r.vec[4..8], &a.vec[4..8], &b.vec[4..8], zetas + 32);
+    schoolbook(&mut r.vec[8..12], &a.vec[8..12], &b.vec[8..12], zetas + 224);
+    schoolbook(&mut r.vec[12..], &a.vec[12..], &b.vec[12..], zetas + 256);
+}
@@ -1,12 +1,13 @@
 #![allow(non_snake_case, dead_code)]
-use crate::align::Eta4xBuf;
+use super::align::Eta4xBuf;
 #[cfg(feature = "90s")]
-use crate::align::IndcpaBuf;
+use super::align::IndcpaBuf;
+use super::poly::*;
 use crate::params::KYBER_N;
-use crate::poly::*;
 use core::arch::x86_64::*;
 
-fn cbd2(r: &mut Poly, buf: &[__m256i]) {
+#[target_feature(enable = "avx2")]
+unsafe fn cbd2(r: &mut Poly, buf: &[__m256i]) {
     unsafe {
         let mask55: __m256i = _mm256_set1_epi32(0x55555555);
         let mask33: __m256i = _mm256_set1_epi32(0x33333333);
@@ -49,7 +50,8 @@ fn cbd2(r: &mut Poly, buf: &[__m256i]) {
     }
 }
 
-fn cbd3(r: &mut Poly, buf: &[u8]) {
+#[target_feature(enable = "avx2")]
+unsafe fn cbd3(r: &mut Poly, buf: &[u8]) {
     unsafe {
         let (mut f0, mut f1, mut f2, mut f3);
         let mask249: __m256i = _mm256_set1_epi32(0x249249);
@@ -103,7 +105,8 @@ fn cbd3(r: &mut Poly, buf: &[u8]) {
     }
 }
 
-pub fn poly_cbd_eta1(r: &mut Poly, buf: &Eta4xBuf) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_cbd_eta1(r: &mut Poly, buf: &Eta4xBuf) {
     unsafe {
         if cfg!(feature = "kyber512") {
             cbd3(r, &buf.coeffs)
@@ -114,7 +117,8 @@ pub fn poly_cbd_eta1(r: &mut Poly, buf: &Eta4xBuf) {
 }
 
 #[cfg(feature = "90s")]
-pub fn poly_cbd_eta1_90s(r: &mut Poly, buf: &IndcpaBuf) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_cbd_eta1_90s(r: &mut Poly, buf: &IndcpaBuf) {
     unsafe {
         if cfg!(feature = "kyber512") {
             cbd3(r, &buf.coeffs)
@@ -124,6 +128,7 @@ pub fn poly_cbd_eta1_90s(r: &mut Poly, buf: &IndcpaBuf) {
     }
 }
 
-pub fn poly_cbd_eta2(r: &mut Poly, buf: &[__m256i]) {
-    cbd2(r, &buf)
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_cbd_eta2(r: &mut Poly, buf: &[__m256i]) {
+    cbd2(r, buf)
 }
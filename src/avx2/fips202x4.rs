@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
-use crate::align::{Eta4xBuf, GenMatrixBuf};
+use super::align::{Eta4xBuf, GenMatrixBuf};
+use super::keccak4x::f1600_x4;
 use crate::fips202::*;
-use crate::keccak4x::f1600_x4;
 use core::arch::x86_64::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[repr(C)]
 pub struct Keccakx4State {
@@ -20,6 +22,16 @@ impl Keccakx4State {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for Keccakx4State {
+    fn zeroize(&mut self) {
+        self.s.zeroize();
+    }
+}
+
+// One input per lane, as in the C keccakx4 interface
+#[allow(clippy::too_many_arguments)]
+#[target_feature(enable = "avx2")]
 pub unsafe fn keccakx4_absorb_once(
     s: &mut [__m256i; 25],
     r: usize,
@@ -71,6 +83,7 @@ pub unsafe fn keccakx4_absorb_once(
     s[r / 8 - 1] = _mm256_xor_si256(s[r / 8 - 1], t);
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn keccakx4_squeezeblocks128(
     out: &mut [GenMatrixBuf; 4],
     mut nblocks: usize,
@@ -99,6 +112,7 @@ pub unsafe fn keccakx4_squeezeblocks128(
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn keccakx4_squeezeblocks256(
     out: &mut [Eta4xBuf; 4],
     mut nblocks: usize,
@@ -122,6 +136,7 @@ pub unsafe fn keccakx4_squeezeblocks256(
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn shake128x4_absorb_once(
     state: &mut Keccakx4State,
     in0: &[u8],
@@ -133,6 +148,7 @@ pub unsafe fn shake128x4_absorb_once(
     keccakx4_absorb_once(&mut state.s, SHAKE128_RATE, in0, in1, in2, in3, inlen, 0x1F)
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn shake128x4_squeezeblocks(
     out: &mut [GenMatrixBuf; 4],
     nblocks: usize,
@@ -141,6 +157,7 @@ pub unsafe fn shake128x4_squeezeblocks(
     keccakx4_squeezeblocks128(out, nblocks, SHAKE128_RATE, &mut state.s);
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn shake256x4_absorb_once(
     state: &mut Keccakx4State,
     in0: &[u8],
@@ -152,6 +169,7 @@ pub unsafe fn shake256x4_absorb_once(
     keccakx4_absorb_once(&mut state.s, SHAKE256_RATE, in0, in1, in2, in3, inlen, 0x1F)
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn shake256x4_squeezeblocks(
     out: &mut [Eta4xBuf; 4],
     nblocks: usize,
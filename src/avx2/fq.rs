@@ -0,0 +1,68 @@
+// Modular arithmetic on 16 coefficients at a time, ported from the
+// fq.S and fq.inc kernels of the C reference implementation.
+use super::{consts::*, poly::Poly};
+use core::arch::x86_64::*;
+
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn qdata(offset: usize) -> __m256i {
+    _mm256_load_si256(&QDATA.vec[offset / 16])
+}
+
+// Four constants from QDATA repeated across the vector
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn qdata_broadcastq(offset: usize) -> __m256i {
+    let p = QDATA.coeffs[offset..].as_ptr() as *const __m128i;
+    _mm256_broadcastq_epi64(_mm_loadl_epi64(p))
+}
+
+// Barrett reduction, v holds 16 copies of _16XV
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn red16(r: __m256i, q: __m256i, v: __m256i) -> __m256i {
+    let mut x = _mm256_mulhi_epi16(r, v);
+    x = _mm256_srai_epi16(x, 10);
+    x = _mm256_mullo_epi16(x, q);
+    _mm256_sub_epi16(r, x)
+}
+
+// Conditionally subtract q
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn csubq(r: __m256i, q: __m256i) -> __m256i {
+    let r = _mm256_sub_epi16(r, q);
+    let mut x = _mm256_srai_epi16(r, 15);
+    x = _mm256_and_si256(x, q);
+    _mm256_add_epi16(x, r)
+}
+
+// Montgomery multiplication of b by a constant split into its
+// qinv product al and the constant itself ah
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn fqmulprecomp(al: __m256i, ah: __m256i, b: __m256i, q: __m256i) -> __m256i {
+    let mut x = _mm256_mullo_epi16(al, b);
+    let b = _mm256_mulhi_epi16(ah, b);
+    x = _mm256_mulhi_epi16(q, x);
+    _mm256_sub_epi16(b, x)
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn reduce_avx(r: &mut Poly) {
+    let q = qdata(_16XQ);
+    let v = qdata(_16XV);
+    for f in r.vec.iter_mut() {
+        *f = red16(*f, q, v);
+    }
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn tomont_avx(r: &mut Poly) {
+    let q = qdata(_16XQ);
+    let montsqlo = qdata(_16XMONTSQLO);
+    let montsqhi = qdata(_16XMONTSQHI);
+    for f in r.vec.iter_mut() {
+        *f = fqmulprecomp(montsqlo, montsqhi, *f, q);
+    }
+}
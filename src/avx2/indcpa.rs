@@ -1,13 +1,15 @@
-use crate::rng::randombytes;
+#[cfg(not(feature = "90s"))]
+use super::fips202x4::*;
 #[cfg(feature = "90s")]
-use crate::{aes256ctr::*, cbd::*};
-use crate::{
-    align::*, params::*, poly::*, polyvec::*, rejsample::*, symmetric::*, CryptoRng, KyberError,
-    RngCore,
-};
+use super::{aes256ctr::*, cbd::*};
+use super::{align::*, poly::*, polyvec::*, rejsample::*};
 #[cfg(not(feature = "90s"))]
-use crate::{fips202::*, fips202x4::*};
+use crate::fips202::*;
+use crate::rng::randombytes;
+use crate::{params::*, symmetric::*, CryptoRng, KyberError, RngCore};
 use core::arch::x86_64::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 /// Name:  pack_pk
 ///
@@ -18,7 +20,8 @@ use core::arch::x86_64::*;
 /// Arguments:   [u8] r:  the output serialized public key
 ///  const poly *pk:  the input public-key polynomial
 ///  const [u8] seed: the input public seed
-fn pack_pk(r: &mut [u8], pk: &Polyvec, seed: &[u8]) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn pack_pk(r: &mut [u8], pk: &Polyvec, seed: &[u8]) {
     polyvec_tobytes(r, pk);
     r[KYBER_POLYVECBYTES..][..KYBER_SYMBYTES].copy_from_slice(&seed[..KYBER_SYMBYTES]);
 }
@@ -31,7 +34,8 @@ fn pack_pk(r: &mut [u8], pk: &Polyvec, seed: &[u8]) {
 /// Arguments:   - Polyvec pk:     output public-key vector of polynomials
 ///  - [u8] seed:   output seed to generate matrix A
 ///  - const [u8] packedpk: input serialized public key
-fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
     unsafe {
         polyvec_frombytes(pk, packedpk);
     }
@@ -44,7 +48,8 @@ fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
 ///
 /// Arguments:   - [u8] r:  output serialized secret key
 ///  - const Polyvec sk: input vector of polynomials (secret key)
-fn pack_sk(r: &mut [u8], sk: &Polyvec) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn pack_sk(r: &mut [u8], sk: &Polyvec) {
     polyvec_tobytes(r, sk);
 }
 
@@ -55,7 +60,8 @@ fn pack_sk(r: &mut [u8], sk: &Polyvec) {
 ///
 /// Arguments:   - Polyvec sk:     output vector of polynomials (secret key)
 ///  - const [u8] packedsk: input serialized secret key
-fn unpack_sk(sk: &mut Polyvec, packedsk: &[u8]) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn unpack_sk(sk: &mut Polyvec, packedsk: &[u8]) {
     unsafe {
         polyvec_frombytes(sk, packedsk);
     }
@@ -70,7 +76,8 @@ fn unpack_sk(sk: &mut Polyvec, packedsk: &[u8]) {
 /// Arguments:   [u8] r:  the output serialized ciphertext
 ///  const poly *pk:  the input vector of polynomials b
 ///  const [u8] seed: the input polynomial v
-fn pack_ciphertext(r: &mut [u8], b: &Polyvec, v: Poly) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn pack_ciphertext(r: &mut [u8], b: &Polyvec, v: &Poly) {
     unsafe {
         polyvec_compress(r, b);
         poly_compress(&mut r[KYBER_POLYVECCOMPRESSEDBYTES..], v);
@@ -85,7 +92,8 @@ fn pack_ciphertext(r: &mut [u8], b: &Polyvec, v: Poly) {
 /// Arguments:   - Polyvec b:   output vector of polynomials b
 ///  - Poly *v:  output polynomial v
 ///  - const [u8] c:   input serialized ciphertext
-fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
     unsafe {
         polyvec_decompress(b, c);
         poly_decompress(v, &c[KYBER_POLYVECCOMPRESSEDBYTES..]);
@@ -103,7 +111,8 @@ fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
 ///  - usize buflen:  length of input buffer in bytes
 ///
 /// Returns number of sampled 16-bit integers (at most len)
-fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+unsafe fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
     let (mut ctr, mut pos) = (0usize, 0usize);
     let (mut val0, mut val1);
 
@@ -124,19 +133,22 @@ fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
     ctr
 }
 
-pub fn gen_a(a: &mut [Polyvec], b: &[u8]) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn gen_a(a: &mut [Polyvec], b: &[u8]) {
     unsafe {
         gen_matrix(a, b, false);
     }
 }
 
-pub fn gen_at(a: &mut [Polyvec], b: &[u8]) {
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn gen_at(a: &mut [Polyvec], b: &[u8]) {
     unsafe {
         gen_matrix(a, b, true);
     }
 }
 
 #[cfg(feature = "90s")]
+#[target_feature(enable = "avx2,bmi2,popcnt")]
 unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     let (mut ctr, mut off, mut buflen);
     let mut nonce: u64;
@@ -174,6 +186,7 @@ unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
 }
 
 #[cfg(all(feature = "kyber512", not(feature = "90s")))]
+#[target_feature(enable = "avx2,bmi2,popcnt")]
 unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     let mut state = Keccakx4State::new();
     let mut buf = [GenMatrixBuf::new(); 4];
@@ -259,6 +272,7 @@ unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     not(feature = "kyber1024"),
     not(feature = "90s")
 ))]
+#[target_feature(enable = "avx2,bmi2,popcnt")]
 unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     let mut state = Keccakx4State::new();
     let mut state1x = KeccakState::new();
@@ -435,6 +449,7 @@ unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
 }
 
 #[cfg(all(feature = "kyber1024", not(feature = "90s")))]
+#[target_feature(enable = "avx2,bmi2,popcnt")]
 unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     let mut f;
     let mut state = Keccakx4State::new();
@@ -510,7 +525,8 @@ unsafe fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     }
 }
 
-pub fn indcpa_keypair<R>(
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_keypair<R>(
     pk: &mut [u8],
     sk: &mut [u8],
     _seed: Option<(&[u8], &[u8])>,
@@ -519,13 +535,11 @@ pub fn indcpa_keypair<R>(
 where
     R: CryptoRng + RngCore,
 {
-    let mut a = [Polyvec::new(); KYBER_K];
-    let (mut e, mut pkpv, mut skpv) = (Polyvec::new(), Polyvec::new(), Polyvec::new());
     let mut buf = [0u8; 2 * KYBER_SYMBYTES];
     let mut randbuf = [0u8; 2 * KYBER_SYMBYTES];
 
     if let Some(s) = _seed {
-        randbuf[..KYBER_SYMBYTES].copy_from_slice(&s.0);
+        randbuf[..KYBER_SYMBYTES].copy_from_slice(s.0);
     } else {
         randombytes(&mut randbuf, KYBER_SYMBYTES, _rng)?;
     }
@@ -533,6 +547,26 @@ where
     hash_g(&mut buf, &randbuf, KYBER_SYMBYTES);
 
     let (publicseed, noiseseed) = buf.split_at(KYBER_SYMBYTES);
+    indcpa_keypair_derand(pk, sk, publicseed, noiseseed);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        randbuf.zeroize();
+    }
+    Ok(())
+}
+
+// Deterministic key generation from the expanded public and noise seeds
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_keypair_derand(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    publicseed: &[u8],
+    noiseseed: &[u8],
+) {
+    let mut a = [Polyvec::new(); KYBER_K];
+    let (mut e, mut pkpv, mut skpv) = (Polyvec::new(), Polyvec::new(), Polyvec::new());
+
     gen_a(&mut a, publicseed);
 
     #[cfg(feature = "90s")]
@@ -558,6 +592,11 @@ where
                 poly_cbd_eta1_90s(&mut e.vec[i], &coins);
             }
         }
+        #[cfg(feature = "zeroize")]
+        {
+            state.zeroize();
+            coins.zeroize();
+        }
     }
 
     #[cfg(all(feature = "kyber512", not(feature = "90s")))]
@@ -645,24 +684,45 @@ where
 
     pack_sk(sk, &skpv);
     pack_pk(pk, &pkpv, publicseed);
-    Ok(())
+    #[cfg(feature = "zeroize")]
+    {
+        skpv.zeroize();
+        e.zeroize();
+    }
+}
+
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
+    let mut at = [Polyvec::new(); KYBER_K];
+    let mut pkpv = Polyvec::new();
+
+    indcpa_expand_pk(&mut at, &mut pkpv, pk);
+    indcpa_enc_expanded(c, m, &at, &pkpv, coins);
 }
 
-pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
+// Unpacks the public key and generates the transposed matrix A
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_expand_pk(at: &mut [Polyvec], pkpv: &mut Polyvec, pk: &[u8]) {
+    let mut seed = [0u8; KYBER_SYMBYTES];
+
+    unpack_pk(pkpv, &mut seed, pk);
+    gen_at(at, &seed);
+}
+
+// Encryption with a public key expanded by indcpa_expand_pk
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_enc_expanded(
+    c: &mut [u8],
+    m: &[u8],
+    at: &[Polyvec],
+    pkpv: &Polyvec,
+    coins: &[u8],
+) {
     unsafe {
-        let mut at = [Polyvec::new(); KYBER_K];
-        let (mut sp, mut pkpv, mut ep, mut b) = (
-            Polyvec::new(),
-            Polyvec::new(),
-            Polyvec::new(),
-            Polyvec::new(),
-        );
+        let (mut sp, mut ep, mut b) = (Polyvec::new(), Polyvec::new(), Polyvec::new());
         let (mut v, mut k, mut epp) = (Poly::new(), Poly::new(), Poly::new());
-        let mut seed = [0u8; KYBER_SYMBYTES];
 
-        unpack_pk(&mut pkpv, &mut seed, pk);
         poly_frommsg(&mut k, m);
-        gen_at(&mut at, &seed);
 
         #[cfg(feature = "90s")]
         {
@@ -687,6 +747,11 @@ pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
             }
             aes256ctr_squeezeblocks(&mut buf.coeffs, CIPHERTEXTNOISE_NBLOCKS, &mut state);
             poly_cbd_eta2(&mut epp, &buf.vec);
+            #[cfg(feature = "zeroize")]
+            {
+                state.zeroize();
+                buf.zeroize();
+            }
         }
 
         #[cfg(all(feature = "kyber512", not(feature = "90s")))]
@@ -775,7 +840,7 @@ pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
         for i in 0..KYBER_K {
             polyvec_basemul_acc_montgomery(&mut b.vec[i], &at[i], &sp);
         }
-        polyvec_basemul_acc_montgomery(&mut v, &pkpv, &sp);
+        polyvec_basemul_acc_montgomery(&mut v, pkpv, &sp);
 
         polyvec_invntt_tomont(&mut b);
         poly_invntt_tomont(&mut v);
@@ -787,23 +852,51 @@ pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
         polyvec_reduce(&mut b);
         poly_reduce(&mut v);
 
-        pack_ciphertext(c, &b, v);
+        pack_ciphertext(c, &b, &v);
+        #[cfg(feature = "zeroize")]
+        {
+            sp.zeroize();
+            ep.zeroize();
+            b.zeroize();
+            v.zeroize();
+            k.zeroize();
+            epp.zeroize();
+        }
     }
 }
 
-pub fn indcpa_dec(m: &mut [u8], c: &[u8], sk: &[u8]) {
-    let (mut b, mut skpv) = (Polyvec::new(), Polyvec::new());
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_dec(m: &mut [u8], c: &[u8], sk: &[u8]) {
+    let mut skpv = Polyvec::new();
+
+    indcpa_expand_sk(&mut skpv, sk);
+    indcpa_dec_expanded(m, c, &skpv);
+    #[cfg(feature = "zeroize")]
+    skpv.zeroize();
+}
+
+// Unpacks the NTT domain secret vector
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_expand_sk(skpv: &mut Polyvec, sk: &[u8]) {
+    unpack_sk(skpv, sk);
+}
+
+// Decryption with a secret key unpacked by indcpa_expand_sk
+#[target_feature(enable = "avx2,bmi2,popcnt")]
+pub unsafe fn indcpa_dec_expanded(m: &mut [u8], c: &[u8], skpv: &Polyvec) {
+    let mut b = Polyvec::new();
     let (mut v, mut mp) = (Poly::new(), Poly::new());
 
     unpack_ciphertext(&mut b, &mut v, c);
-    unpack_sk(&mut skpv, sk);
 
     polyvec_ntt(&mut b);
-    polyvec_basemul_acc_montgomery(&mut mp, &skpv, &b);
+    polyvec_basemul_acc_montgomery(&mut mp, skpv, &b);
 
     poly_invntt_tomont(&mut mp);
     poly_sub(&mut mp, &v);
     poly_reduce(&mut mp);
 
-    poly_tomsg(m, mp);
+    poly_tomsg(m, &mp);
+    #[cfg(feature = "zeroize")]
+    mp.zeroize();
 }
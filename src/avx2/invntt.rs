@@ -0,0 +1,180 @@
+// Inverse NTT ported from the invntt.S kernel of the C reference
+// implementation. Variables are named after the registers they replace
+// so the code can be followed alongside the original.
+use super::{consts::*, fq::*, poly::Poly, shuffle::*};
+use core::arch::x86_64::*;
+
+// Gentleman-Sande butterflies, the differences are multiplied by the
+// zetas in Montgomery form
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn butterfly(
+    rl: [__m256i; 4],
+    rh: [__m256i; 4],
+    zl: [__m256i; 2],
+    zh: [__m256i; 2],
+    q: __m256i,
+) -> ([__m256i; 4], [__m256i; 4]) {
+    let mut l = [_mm256_setzero_si256(); 4];
+    let mut h = [_mm256_setzero_si256(); 4];
+    for i in 0..4 {
+        let t = _mm256_sub_epi16(rh[i], rl[i]);
+        l[i] = _mm256_add_epi16(rl[i], rh[i]);
+        let x = _mm256_mullo_epi16(zl[i / 2], t);
+        let t = _mm256_mulhi_epi16(zh[i / 2], t);
+        let x = _mm256_mulhi_epi16(q, x);
+        h[i] = _mm256_sub_epi16(t, x);
+    }
+    (l, h)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn intt_levels0t5(r: &mut [__m256i], off: usize, y0: __m256i) {
+    let r = &mut r[8 * off..];
+    let zetas = _ZETAS_EXP + (1 - off) * 224;
+
+    // level 0
+    let y2 = qdata(_16XFLO);
+    let y3 = qdata(_16XFHI);
+
+    let y4 = fqmulprecomp(y2, y3, r[0], y0);
+    let y6 = fqmulprecomp(y2, y3, r[2], y0);
+    let y5 = fqmulprecomp(y2, y3, r[1], y0);
+    let y7 = fqmulprecomp(y2, y3, r[3], y0);
+
+    let y8 = fqmulprecomp(y2, y3, r[4], y0);
+    let y10 = fqmulprecomp(y2, y3, r[6], y0);
+    let y9 = fqmulprecomp(y2, y3, r[5], y0);
+    let y11 = fqmulprecomp(y2, y3, r[7], y0);
+
+    let y12 = qdata(_REVIDXB);
+    let y15 = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(qdata(zetas + 208), 0x4E), y12);
+    let y1 = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(qdata(zetas + 176), 0x4E), y12);
+    let y2 = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(qdata(zetas + 224), 0x4E), y12);
+    let y3 = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(qdata(zetas + 192), 0x4E), y12);
+
+    let ([y4, y5, y8, y9], [y6, y7, y10, y11]) = butterfly(
+        [y4, y5, y8, y9],
+        [y6, y7, y10, y11],
+        [y15, y1],
+        [y2, y3],
+        y0,
+    );
+
+    // level 1
+    let y1 = qdata(_REVIDXB);
+    let y2 = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(qdata(zetas + 144), 0x4E), y1);
+    let y3 = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(qdata(zetas + 160), 0x4E), y1);
+
+    let ([y4, y5, y6, y7], [y8, y9, y10, y11]) =
+        butterfly([y4, y5, y6, y7], [y8, y9, y10, y11], [y2, y2], [y3, y3], y0);
+
+    let (y3, y5) = shuffle1(y4, y5);
+    let (y4, y7) = shuffle1(y6, y7);
+    let (y6, y9) = shuffle1(y8, y9);
+    let (y8, y11) = shuffle1(y10, y11);
+
+    // level 2
+    let y12 = qdata(_REVIDXD);
+    let y2 = _mm256_permutevar8x32_epi32(qdata(zetas + 112), y12);
+    let y10 = _mm256_permutevar8x32_epi32(qdata(zetas + 128), y12);
+
+    let ([y3, y4, y6, y8], [y5, y7, y9, y11]) = butterfly(
+        [y3, y4, y6, y8],
+        [y5, y7, y9, y11],
+        [y2, y2],
+        [y10, y10],
+        y0,
+    );
+
+    let y1 = qdata(_16XV);
+    let y3 = red16(y3, y0, y1);
+
+    let (y10, y4) = shuffle2(y3, y4);
+    let (y3, y8) = shuffle2(y6, y8);
+    let (y6, y7) = shuffle2(y5, y7);
+    let (y5, y11) = shuffle2(y9, y11);
+
+    // level 3
+    let y2 = _mm256_permute4x64_epi64(qdata(zetas + 80), 0x1B);
+    let y9 = _mm256_permute4x64_epi64(qdata(zetas + 96), 0x1B);
+
+    let ([y10, y3, y6, y5], [y4, y8, y7, y11]) =
+        butterfly([y10, y3, y6, y5], [y4, y8, y7, y11], [y2, y2], [y9, y9], y0);
+
+    let (y9, y3) = shuffle4(y10, y3);
+    let (y10, y5) = shuffle4(y6, y5);
+    let (y6, y8) = shuffle4(y4, y8);
+    let (y4, y11) = shuffle4(y7, y11);
+
+    // level 4
+    let y2 = _mm256_permute4x64_epi64(qdata(zetas + 48), 0x4E);
+    let y7 = _mm256_permute4x64_epi64(qdata(zetas + 64), 0x4E);
+
+    let ([y9, y10, y6, y4], [y3, y5, y8, y11]) =
+        butterfly([y9, y10, y6, y4], [y3, y5, y8, y11], [y2, y2], [y7, y7], y0);
+
+    let y9 = red16(y9, y0, y1);
+
+    let (y7, y10) = shuffle8(y9, y10);
+    let (y9, y4) = shuffle8(y6, y4);
+    let (y6, y5) = shuffle8(y3, y5);
+    let (y3, y11) = shuffle8(y8, y11);
+
+    // level 5
+    let y2 = qdata(zetas + 16);
+    let y8 = qdata(zetas + 32);
+
+    let ([y7, y9, y6, y3], [y10, y4, y5, y11]) =
+        butterfly([y7, y9, y6, y3], [y10, y4, y5, y11], [y2, y2], [y8, y8], y0);
+
+    r[0] = y7;
+    r[1] = y9;
+    r[2] = y6;
+    r[3] = y3;
+    r[4] = y10;
+    r[5] = y4;
+    r[6] = y5;
+    r[7] = y11;
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn intt_level6(r: &mut [__m256i], off: usize, y0: __m256i) {
+    let r = &mut r[4 * off..];
+
+    // level 6
+    let y2 = qdata_broadcastq(_ZETAS_EXP);
+    let y3 = qdata_broadcastq(_ZETAS_EXP + 4);
+
+    let ([mut y4, y5, y6, y7], [y8, y9, y10, y11]) = butterfly(
+        [r[0], r[1], r[2], r[3]],
+        [r[8], r[9], r[10], r[11]],
+        [y2, y2],
+        [y3, y3],
+        y0,
+    );
+
+    if off == 0 {
+        y4 = red16(y4, y0, qdata(_16XV));
+    }
+
+    r[0] = y4;
+    r[1] = y5;
+    r[2] = y6;
+    r[3] = y7;
+    r[8] = y8;
+    r[9] = y9;
+    r[10] = y10;
+    r[11] = y11;
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn invntt_avx(r: &mut Poly) {
+    let y0 = qdata(_16XQ);
+
+    intt_levels0t5(&mut r.vec, 0, y0);
+    intt_levels0t5(&mut r.vec, 1, y0);
+
+    intt_level6(&mut r.vec, 0, y0);
+    intt_level6(&mut r.vec, 1, y0);
+}
@@ -259,7 +259,8 @@ macro_rules! unroll24 {
 }
 
 #[allow(unused_assignments, non_upper_case_globals)]
-pub fn f1600_x4(a: &mut [__m256i]) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn f1600_x4(a: &mut [__m256i]) {
     unsafe {
         for i in 0..24 {
             let mut array = [_mm256_setzero_si256(); 5];
@@ -1,12 +1,15 @@
 pub mod aes256ctr;
 pub mod align;
+pub mod basemul;
 pub mod cbd;
 pub mod consts;
-pub mod fips202;
 pub mod fips202x4;
+pub mod fq;
 pub mod indcpa;
+pub mod invntt;
 pub mod keccak4x;
+pub mod ntt;
 pub mod poly;
 pub mod polyvec;
 pub mod rejsample;
-pub mod verify;
+pub mod shuffle;
@@ -0,0 +1,191 @@
+// Forward NTT ported from the ntt.S kernel of the C reference
+// implementation. Variables are named after the registers they replace
+// so the code can be followed alongside the original.
+use super::{consts::*, fq::*, poly::Poly, shuffle::*};
+use core::arch::x86_64::*;
+
+// Low and high halves of the Montgomery products of rh by the zetas
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn mul(
+    rh: [__m256i; 4],
+    zl0: __m256i,
+    zl1: __m256i,
+    zh0: __m256i,
+    zh1: __m256i,
+) -> ([__m256i; 4], [__m256i; 4]) {
+    let t = [
+        _mm256_mullo_epi16(zl0, rh[0]),
+        _mm256_mullo_epi16(zl0, rh[1]),
+        _mm256_mullo_epi16(zl1, rh[2]),
+        _mm256_mullo_epi16(zl1, rh[3]),
+    ];
+    let rh = [
+        _mm256_mulhi_epi16(zh0, rh[0]),
+        _mm256_mulhi_epi16(zh0, rh[1]),
+        _mm256_mulhi_epi16(zh1, rh[2]),
+        _mm256_mulhi_epi16(zh1, rh[3]),
+    ];
+    (rh, t)
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn reduce(t: [__m256i; 4], q: __m256i) -> [__m256i; 4] {
+    [
+        _mm256_mulhi_epi16(q, t[0]),
+        _mm256_mulhi_epi16(q, t[1]),
+        _mm256_mulhi_epi16(q, t[2]),
+        _mm256_mulhi_epi16(q, t[3]),
+    ]
+}
+
+// Cooley-Tukey butterflies finishing the Montgomery reduction of rh
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn update(
+    rl: [__m256i; 4],
+    rh: [__m256i; 4],
+    t: [__m256i; 4],
+) -> ([__m256i; 4], [__m256i; 4]) {
+    let mut l = [_mm256_setzero_si256(); 4];
+    let mut h = [_mm256_setzero_si256(); 4];
+    for i in 0..4 {
+        l[i] = _mm256_sub_epi16(_mm256_add_epi16(rl[i], rh[i]), t[i]);
+        h[i] = _mm256_add_epi16(_mm256_sub_epi16(rl[i], rh[i]), t[i]);
+    }
+    (l, h)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn level0(r: &mut [__m256i], off: usize, y0: __m256i) {
+    let r = &mut r[4 * off..];
+    let y15 = qdata_broadcastq(_ZETAS_EXP);
+    let (y8, y9, y10, y11) = (r[8], r[9], r[10], r[11]);
+    let y2 = qdata_broadcastq(_ZETAS_EXP + 4);
+
+    let ([y8, y9, y10, y11], t) = mul([y8, y9, y10, y11], y15, y15, y2, y2);
+
+    let (y4, y5, y6, y7) = (r[0], r[1], r[2], r[3]);
+
+    let t = reduce(t, y0);
+    let ([y3, y4, y5, y6], [y8, y9, y10, y11]) = update([y4, y5, y6, y7], [y8, y9, y10, y11], t);
+
+    r[0] = y3;
+    r[1] = y4;
+    r[2] = y5;
+    r[3] = y6;
+    r[8] = y8;
+    r[9] = y9;
+    r[10] = y10;
+    r[11] = y11;
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn levels1t6(r: &mut [__m256i], off: usize, y0: __m256i) {
+    let r = &mut r[8 * off..];
+    let zetas = _ZETAS_EXP + 224 * off;
+
+    // level 1
+    let y15 = qdata(zetas + 16);
+    let (y8, y9, y10, y11) = (r[4], r[5], r[6], r[7]);
+    let y2 = qdata(zetas + 32);
+
+    let ([y8, y9, y10, y11], t) = mul([y8, y9, y10, y11], y15, y15, y2, y2);
+
+    let (y4, y5, y6, y7) = (r[0], r[1], r[2], r[3]);
+
+    let t = reduce(t, y0);
+    let ([y3, y4, y5, y6], [y8, y9, y10, y11]) = update([y4, y5, y6, y7], [y8, y9, y10, y11], t);
+
+    // level 2
+    let (y7, y10) = shuffle8(y5, y10);
+    let (y5, y11) = shuffle8(y6, y11);
+
+    let y15 = qdata(zetas + 48);
+    let y2 = qdata(zetas + 64);
+
+    let ([y7, y10, y5, y11], t) = mul([y7, y10, y5, y11], y15, y15, y2, y2);
+
+    let (y6, y8) = shuffle8(y3, y8);
+    let (y3, y9) = shuffle8(y4, y9);
+
+    let t = reduce(t, y0);
+    let ([y4, y6, y8, y3], [y7, y10, y5, y11]) = update([y6, y8, y3, y9], [y7, y10, y5, y11], t);
+
+    // level 3
+    let (y9, y5) = shuffle4(y8, y5);
+    let (y8, y11) = shuffle4(y3, y11);
+
+    let y15 = qdata(zetas + 80);
+    let y2 = qdata(zetas + 96);
+
+    let ([y9, y5, y8, y11], t) = mul([y9, y5, y8, y11], y15, y15, y2, y2);
+
+    let (y3, y7) = shuffle4(y4, y7);
+    let (y4, y10) = shuffle4(y6, y10);
+
+    let t = reduce(t, y0);
+    let ([y6, y3, y7, y4], [y9, y5, y8, y11]) = update([y3, y7, y4, y10], [y9, y5, y8, y11], t);
+
+    // level 4
+    let (y10, y8) = shuffle2(y7, y8);
+    let (y7, y11) = shuffle2(y4, y11);
+
+    let y15 = qdata(zetas + 112);
+    let y2 = qdata(zetas + 128);
+
+    let ([y10, y8, y7, y11], t) = mul([y10, y8, y7, y11], y15, y15, y2, y2);
+
+    let (y4, y9) = shuffle2(y6, y9);
+    let (y6, y5) = shuffle2(y3, y5);
+
+    let t = reduce(t, y0);
+    let ([y3, y4, y9, y6], [y10, y8, y7, y11]) = update([y4, y9, y6, y5], [y10, y8, y7, y11], t);
+
+    // level 5
+    let (y5, y7) = shuffle1(y9, y7);
+    let (y9, y11) = shuffle1(y6, y11);
+
+    let y15 = qdata(zetas + 144);
+    let y2 = qdata(zetas + 160);
+
+    let ([y5, y7, y9, y11], t) = mul([y5, y7, y9, y11], y15, y15, y2, y2);
+
+    let (y6, y10) = shuffle1(y3, y10);
+    let (y3, y8) = shuffle1(y4, y8);
+
+    let t = reduce(t, y0);
+    let ([y4, y6, y10, y3], [y5, y7, y9, y11]) = update([y6, y10, y3, y8], [y5, y7, y9, y11], t);
+
+    // level 6
+    let y14 = qdata(zetas + 176);
+    let y15 = qdata(zetas + 208);
+    let y8 = qdata(zetas + 192);
+    let y2 = qdata(zetas + 224);
+
+    let ([y10, y3, y9, y11], t) = mul([y10, y3, y9, y11], y14, y15, y8, y2);
+
+    let t = reduce(t, y0);
+    let ([y8, y4, y6, y5], [y10, y3, y9, y11]) = update([y4, y6, y5, y7], [y10, y3, y9, y11], t);
+
+    r[0] = y8;
+    r[1] = y4;
+    r[2] = y10;
+    r[3] = y3;
+    r[4] = y6;
+    r[5] = y5;
+    r[6] = y9;
+    r[7] = y11;
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn ntt_avx(r: &mut Poly) {
+    let y0 = qdata(_16XQ);
+
+    level0(&mut r.vec, 0, y0);
+    level0(&mut r.vec, 1, y0);
+
+    levels1t6(&mut r.vec, 0, y0);
+    levels1t6(&mut r.vec, 1, y0);
+}
@@ -1,6 +1,11 @@
 #![allow(unused_imports)]
-use crate::{align::*, cbd::*, consts::*, fips202::*, fips202x4::*, params::*, symmetric::*};
+use super::{
+    align::*, basemul::*, cbd::*, consts::*, fips202x4::*, fq::*, invntt::*, ntt::*, shuffle::*,
+};
+use crate::{fips202::*, params::*, symmetric::*};
 use core::arch::x86_64::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 pub const NOISE_NBLOCKS: usize = (KYBER_ETA1 * KYBER_N / 4 + SHAKE256_RATE - 1) / SHAKE256_RATE;
 
@@ -21,24 +26,16 @@ impl Poly {
     }
 }
 
-extern "C" {
-    fn ntt_avx(r: &mut [i16; KYBER_N], q_data: &[i16; 640]);
-    fn invntt_avx(r: &mut [i16; KYBER_N], q_data: &[i16; 640]);
-    fn nttunpack_avx(r: &mut [i16; KYBER_N], q_data: &[i16; 640]);
-    fn basemul_avx(
-        r: &mut [i16; KYBER_N],
-        a: &[i16; KYBER_N],
-        b: &[i16; KYBER_N],
-        q_data: &[i16; 640],
-    );
-    fn tomont_avx(r: &mut [i16; KYBER_N], q_data: &[i16; 640]);
-    fn reduce_avx(r: &mut [i16; KYBER_N], q_data: &[i16; 640]);
-    fn ntttobytes_avx(r: *mut u8, a: &[i16; KYBER_N], q_data: &[i16; 640]);
-    fn nttfrombytes_avx(r: *mut i16, a: *const u8, q_data: &[i16; 640]);
+#[cfg(feature = "zeroize")]
+impl Zeroize for Poly {
+    fn zeroize(&mut self) {
+        unsafe { self.coeffs.zeroize() }
+    }
 }
 
 #[cfg(any(feature = "kyber512", not(feature = "kyber1024")))]
-pub unsafe fn poly_compress(r: &mut [u8], a: Poly) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_compress(r: &mut [u8], a: &Poly) {
     let (mut f0, mut f1, mut f2, mut f3);
     let v: __m256i = _mm256_load_si256(QDATA.vec[_16XV / 16..].as_ptr());
     let shift1: __m256i = _mm256_set1_epi16(1 << 9);
@@ -74,6 +71,7 @@ pub unsafe fn poly_compress(r: &mut [u8], a: Poly) {
 }
 
 #[cfg(any(feature = "kyber512", not(feature = "kyber1024")))]
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_decompress(r: &mut Poly, a: &[u8]) {
     let (mut t, mut f);
     let q: __m256i = _mm256_load_si256(QDATA.vec[_16XQ / 16..].as_ptr());
@@ -96,7 +94,8 @@ pub unsafe fn poly_decompress(r: &mut Poly, a: &[u8]) {
 }
 
 #[cfg(feature = "kyber1024")]
-pub unsafe fn poly_compress(r: &mut [u8], a: Poly) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_compress(r: &mut [u8], a: &Poly) {
     let (mut f0, mut f1);
     let (mut t0, mut t1);
     let mut tmp;
@@ -136,6 +135,7 @@ pub unsafe fn poly_compress(r: &mut [u8], a: Poly) {
 }
 
 #[cfg(feature = "kyber1024")]
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_decompress(r: &mut Poly, a: &[u8]) {
     let (mut t, mut f, mut ti);
 
@@ -164,20 +164,17 @@ pub unsafe fn poly_decompress(r: &mut Poly, a: &[u8]) {
     }
 }
 
-pub fn poly_frombytes(r: &mut Poly, a: &[u8]) {
-    unsafe {
-        nttfrombytes_avx(r.coeffs.as_mut_ptr(), a.as_ptr(), &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_frombytes(r: &mut Poly, a: &[u8]) {
+    nttfrombytes_avx(r, a);
 }
 
-pub fn poly_tobytes(r: &mut [u8], a: Poly) {
-    let mut buf = [0u8; KYBER_POLYBYTES];
-    unsafe {
-        ntttobytes_avx(buf.as_mut_ptr(), &a.coeffs, &QDATA.coeffs);
-    }
-    r[..KYBER_POLYBYTES].copy_from_slice(&buf[..]);
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_tobytes(r: &mut [u8], a: &Poly) {
+    ntttobytes_avx(r, a);
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_frommsg(r: &mut Poly, msg: &[u8]) {
     let shift = _mm256_broadcastsi128_si256(_mm_set_epi32(0, 1, 2, 3));
     let idx = _mm256_broadcastsi128_si256(_mm_set_epi8(
@@ -221,7 +218,8 @@ pub unsafe fn poly_frommsg(r: &mut Poly, msg: &[u8]) {
     frommsg64(3, _mm256_shuffle_epi32(f, 255));
 }
 
-pub fn poly_tomsg(msg: &mut [u8], a: Poly) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_tomsg(msg: &mut [u8], a: &Poly) {
     unsafe {
         let (mut f0, mut f1, mut g0, mut g1);
         let hq: __m256i = _mm256_set1_epi16((KYBER_Q - 1) as i16 / 2);
@@ -247,16 +245,22 @@ pub fn poly_tomsg(msg: &mut [u8], a: Poly) {
 }
 
 #[cfg(all(any(feature = "kyber1024", feature = "kyber512"), not(feature = "90s")))]
-pub fn poly_getnoise_eta2(r: &mut Poly, seed: &[u8], nonce: u8) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_getnoise_eta2(r: &mut Poly, seed: &[u8], nonce: u8) {
     let mut buf = Eta2Buf::new();
     unsafe {
         prf(&mut buf.coeffs, KYBER_ETA2 * KYBER_N / 4, seed, nonce);
         poly_cbd_eta2(r, &buf.vec);
     }
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
 }
 
+// Four polynomials and their nonces, sampled in one pass over the 4x Keccak
 #[cfg(not(feature = "90s"))]
-pub fn poly_getnoise_eta1_4x(
+#[allow(clippy::too_many_arguments)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_getnoise_eta1_4x(
     r0: &mut Poly,
     r1: &mut Poly,
     r2: &mut Poly,
@@ -270,7 +274,9 @@ pub fn poly_getnoise_eta1_4x(
     unsafe {
         let mut buf = [Eta4xBuf::new(); 4];
         let mut state = Keccakx4State::new();
-        let f = _mm256_loadu_si256(seed.as_ptr() as *const __m256i);
+        // Spilled to the stack, wiped with the buffers
+        #[allow(unused_mut)]
+        let mut f = _mm256_loadu_si256(seed.as_ptr() as *const __m256i);
         _mm256_store_si256(buf[0].vec.as_mut_ptr(), f);
         _mm256_store_si256(buf[1].vec.as_mut_ptr(), f);
         _mm256_store_si256(buf[2].vec.as_mut_ptr(), f);
@@ -295,11 +301,19 @@ pub fn poly_getnoise_eta1_4x(
         poly_cbd_eta1(r1, &buf[1]);
         poly_cbd_eta1(r2, &buf[2]);
         poly_cbd_eta1(r3, &buf[3]);
+        #[cfg(feature = "zeroize")]
+        {
+            buf.zeroize();
+            state.zeroize();
+            f.zeroize();
+        }
     }
 }
 
 #[cfg(all(feature = "kyber512", not(feature = "90s")))]
-pub fn poly_getnoise_eta1122_4x(
+#[allow(clippy::too_many_arguments)]
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_getnoise_eta1122_4x(
     r0: &mut Poly,
     r1: &mut Poly,
     r2: &mut Poly,
@@ -313,7 +327,9 @@ pub fn poly_getnoise_eta1122_4x(
     let mut buf = [Eta4xBuf::new(); 4];
     let mut state = Keccakx4State::new();
     unsafe {
-        let f = _mm256_loadu_si256(seed.as_ptr() as *const __m256i);
+        // Spilled to the stack, wiped with the buffers
+        #[allow(unused_mut)]
+        let mut f = _mm256_loadu_si256(seed.as_ptr() as *const __m256i);
         _mm256_store_si256(buf[0].vec.as_mut_ptr(), f);
         _mm256_store_si256(buf[1].vec.as_mut_ptr(), f);
         _mm256_store_si256(buf[2].vec.as_mut_ptr(), f);
@@ -338,46 +354,47 @@ pub fn poly_getnoise_eta1122_4x(
         poly_cbd_eta1(r1, &buf[1]);
         poly_cbd_eta2(r2, &buf[2].vec);
         poly_cbd_eta2(r3, &buf[3].vec);
+        #[cfg(feature = "zeroize")]
+        {
+            buf.zeroize();
+            state.zeroize();
+            f.zeroize();
+        }
     }
 }
 
-pub fn poly_ntt(r: &mut Poly) {
-    unsafe {
-        ntt_avx(&mut r.coeffs, &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_ntt(r: &mut Poly) {
+    ntt_avx(r);
 }
 
-pub fn poly_invntt_tomont(r: &mut Poly) {
-    unsafe {
-        invntt_avx(&mut r.coeffs, &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_invntt_tomont(r: &mut Poly) {
+    invntt_avx(r);
 }
 
-pub fn poly_nttunpack(r: &mut Poly) {
-    unsafe {
-        nttunpack_avx(&mut r.coeffs, &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_nttunpack(r: &mut Poly) {
+    nttunpack_avx(r);
 }
 
-pub fn poly_basemul(r: &mut Poly, a: &Poly, b: &Poly) {
-    unsafe {
-        basemul_avx(&mut r.coeffs, &a.coeffs, &b.coeffs, &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_basemul(r: &mut Poly, a: &Poly, b: &Poly) {
+    basemul_avx(r, a, b);
 }
 
-pub fn poly_tomont(r: &mut Poly) {
-    unsafe {
-        tomont_avx(&mut r.coeffs, &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_tomont(r: &mut Poly) {
+    tomont_avx(r);
 }
 
-pub fn poly_reduce(r: &mut Poly) {
-    unsafe {
-        reduce_avx(&mut r.coeffs, &QDATA.coeffs);
-    }
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_reduce(r: &mut Poly) {
+    reduce_avx(r);
 }
 
-pub fn poly_add(r: &mut Poly, b: &Poly) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_add(r: &mut Poly, b: &Poly) {
     let (mut f0, mut f1);
     for i in 0..(KYBER_N / 16) {
         unsafe {
@@ -389,7 +406,8 @@ pub fn poly_add(r: &mut Poly, b: &Poly) {
     }
 }
 
-pub fn poly_sub(r: &mut Poly, a: &Poly) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn poly_sub(r: &mut Poly, a: &Poly) {
     let (mut f0, mut f1);
     for i in 0..(KYBER_N / 16) {
         unsafe {
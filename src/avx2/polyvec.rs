@@ -1,5 +1,8 @@
-use crate::{consts::*, params::*, poly::*};
+use super::{consts::*, poly::*};
+use crate::params::*;
 use core::arch::x86_64::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[derive(Clone)]
 pub struct Polyvec {
@@ -16,6 +19,14 @@ impl Polyvec {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for Polyvec {
+    fn zeroize(&mut self) {
+        self.vec.zeroize();
+    }
+}
+
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_compress10(r: &mut [u8], a: &Poly) {
     let (mut f0, mut f1, mut f2);
     let (mut t0, mut t1);
@@ -56,6 +67,7 @@ pub unsafe fn poly_compress10(r: &mut [u8], a: &Poly) {
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_decompress10(r: &mut Poly, a: &[u8]) {
     let mut f;
     let q = _mm256_set1_epi32(((KYBER_Q as i32) << 16) + 4 * KYBER_Q as i32);
@@ -77,6 +89,7 @@ pub unsafe fn poly_decompress10(r: &mut Poly, a: &[u8]) {
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_compress11(r: &mut [u8], a: &Poly) {
     let (mut f0, mut f1, mut f2);
     let (mut t0, mut t1);
@@ -120,6 +133,7 @@ pub unsafe fn poly_compress11(r: &mut [u8], a: &Poly) {
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn poly_decompress11(r: &mut Poly, a: &[u8]) {
     let mut f;
 
@@ -147,6 +161,7 @@ pub unsafe fn poly_decompress11(r: &mut Poly, a: &[u8]) {
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn polyvec_compress(r: &mut [u8], a: &Polyvec) {
     if cfg!(feature = "kyber1024") {
         for i in 0..KYBER_K {
@@ -159,6 +174,7 @@ pub unsafe fn polyvec_compress(r: &mut [u8], a: &Polyvec) {
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn polyvec_decompress(r: &mut Polyvec, a: &[u8]) {
     if cfg!(feature = "kyber1024") {
         for i in 0..KYBER_K {
@@ -171,12 +187,14 @@ pub unsafe fn polyvec_decompress(r: &mut Polyvec, a: &[u8]) {
     }
 }
 
-pub fn polyvec_tobytes(r: &mut [u8], a: &Polyvec) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn polyvec_tobytes(r: &mut [u8], a: &Polyvec) {
     for i in 0..KYBER_K {
-        poly_tobytes(&mut r[i * KYBER_POLYBYTES..], a.vec[i]);
+        poly_tobytes(&mut r[i * KYBER_POLYBYTES..], &a.vec[i]);
     }
 }
 
+#[target_feature(enable = "avx2")]
 pub unsafe fn polyvec_frombytes(r: &mut Polyvec, a: &[u8]) {
     for i in 0..KYBER_K {
         poly_frombytes(&mut r.vec[i], &a[i * KYBER_POLYBYTES..]);
@@ -188,7 +206,8 @@ pub unsafe fn polyvec_frombytes(r: &mut Polyvec, a: &[u8]) {
 /// Description: Apply forward NTT to all elements of a vector of polynomials
 ///
 /// Arguments:   - Polyvec r: in/output vector of polynomials
-pub fn polyvec_ntt(r: &mut Polyvec) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn polyvec_ntt(r: &mut Polyvec) {
     for i in 0..KYBER_K {
         poly_ntt(&mut r.vec[i]);
     }
@@ -199,7 +218,8 @@ pub fn polyvec_ntt(r: &mut Polyvec) {
 /// Description: Apply inverse NTT to all elements of a vector of polynomials
 ///
 /// Arguments:   - Polyvec r: in/output vector of polynomials
-pub fn polyvec_invntt_tomont(r: &mut Polyvec) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn polyvec_invntt_tomont(r: &mut Polyvec) {
     for i in 0..KYBER_K {
         poly_invntt_tomont(&mut r.vec[i]);
     }
@@ -212,13 +232,16 @@ pub fn polyvec_invntt_tomont(r: &mut Polyvec) {
 /// Arguments: - poly *r:  output polynomial
 ///  - const Polyvec a: first input vector of polynomials
 ///  - const Polyvec b: second input vector of polynomials
-pub fn polyvec_basemul_acc_montgomery(r: &mut Poly, a: &Polyvec, b: &Polyvec) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn polyvec_basemul_acc_montgomery(r: &mut Poly, a: &Polyvec, b: &Polyvec) {
     let mut t = Poly::new();
     poly_basemul(r, &a.vec[0], &b.vec[0]);
     for i in 1..KYBER_K {
         poly_basemul(&mut t, &a.vec[i], &b.vec[i]);
         poly_add(r, &t);
     }
+    #[cfg(feature = "zeroize")]
+    t.zeroize();
 }
 
 /// Name:  polyvec_reduce
@@ -228,7 +251,8 @@ pub fn polyvec_basemul_acc_montgomery(r: &mut Poly, a: &Polyvec, b: &Polyvec) {
 ///  for details of the Barrett reduction see comments in reduce.c
 ///
 /// Arguments:   - poly *r:   input/output polynomial
-pub fn polyvec_reduce(r: &mut Polyvec) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn polyvec_reduce(r: &mut Polyvec) {
     for i in 0..KYBER_K {
         poly_reduce(&mut r.vec[i]);
     }
@@ -241,7 +265,8 @@ pub fn polyvec_reduce(r: &mut Polyvec) {
 /// Arguments: - Polyvec r:   output vector of polynomials
 ///  - const Polyvec a: first input vector of polynomials
 ///  - const Polyvec b: second input vector of polynomials
-pub fn polyvec_add(r: &mut Polyvec, b: &Polyvec) {
+#[target_feature(enable = "avx2")]
+pub unsafe fn polyvec_add(r: &mut Polyvec, b: &Polyvec) {
     for i in 0..KYBER_K {
         poly_add(&mut r.vec[i], &b.vec[i]);
     }
@@ -0,0 +1,197 @@
+// Coefficient reordering and packing between the NTT domain layout and
+// bytes, ported from the shuffle.S and shuffle.inc kernels of the C
+// reference implementation. Variables are named after the registers
+// they replace.
+use super::{consts::*, fq::*, poly::Poly};
+use crate::params::KYBER_POLYBYTES;
+use core::arch::x86_64::*;
+
+// Interleave 128 bit lanes
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn shuffle8(r0: __m256i, r1: __m256i) -> (__m256i, __m256i) {
+    (
+        _mm256_permute2x128_si256(r0, r1, 0x20),
+        _mm256_permute2x128_si256(r0, r1, 0x31),
+    )
+}
+
+// Interleave 64 bit words
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn shuffle4(r0: __m256i, r1: __m256i) -> (__m256i, __m256i) {
+    (_mm256_unpacklo_epi64(r0, r1), _mm256_unpackhi_epi64(r0, r1))
+}
+
+// Interleave 32 bit words
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn shuffle2(r0: __m256i, r1: __m256i) -> (__m256i, __m256i) {
+    let r2 = _mm256_castps_si256(_mm256_moveldup_ps(_mm256_castsi256_ps(r1)));
+    let r2 = _mm256_blend_epi32(r0, r2, 0xAA);
+    let r0 = _mm256_srli_epi64(r0, 32);
+    (r2, _mm256_blend_epi32(r0, r1, 0xAA))
+}
+
+// Interleave 16 bit words
+#[inline]
+#[target_feature(enable = "avx2")]
+pub unsafe fn shuffle1(r0: __m256i, r1: __m256i) -> (__m256i, __m256i) {
+    let r2 = _mm256_slli_epi32(r1, 16);
+    let r2 = _mm256_blend_epi16(r0, r2, 0xAA);
+    let r0 = _mm256_srli_epi32(r0, 16);
+    (r2, _mm256_blend_epi16(r0, r1, 0xAA))
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn nttunpack128_avx(r: &mut [__m256i]) {
+    let (y4, y5, y6, y7) = (r[0], r[1], r[2], r[3]);
+    let (y8, y9, y10, y11) = (r[4], r[5], r[6], r[7]);
+
+    let (y3, y8) = shuffle8(y4, y8);
+    let (y4, y9) = shuffle8(y5, y9);
+    let (y5, y10) = shuffle8(y6, y10);
+    let (y6, y11) = shuffle8(y7, y11);
+
+    let (y7, y5) = shuffle4(y3, y5);
+    let (y3, y10) = shuffle4(y8, y10);
+    let (y8, y6) = shuffle4(y4, y6);
+    let (y4, y11) = shuffle4(y9, y11);
+
+    let (y9, y8) = shuffle2(y7, y8);
+    let (y7, y6) = shuffle2(y5, y6);
+    let (y5, y4) = shuffle2(y3, y4);
+    let (y3, y11) = shuffle2(y10, y11);
+
+    let (y10, y5) = shuffle1(y9, y5);
+    let (y9, y4) = shuffle1(y8, y4);
+    let (y8, y3) = shuffle1(y7, y3);
+    let (y7, y11) = shuffle1(y6, y11);
+
+    r[0] = y10;
+    r[1] = y5;
+    r[2] = y9;
+    r[3] = y4;
+    r[4] = y8;
+    r[5] = y3;
+    r[6] = y7;
+    r[7] = y11;
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn nttunpack_avx(r: &mut Poly) {
+    nttunpack128_avx(&mut r.vec[..8]);
+    nttunpack128_avx(&mut r.vec[8..]);
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn ntttobytes128_avx(r: &mut [u8], a: &[__m256i], y0: __m256i) {
+    let y5 = csubq(a[0], y0);
+    let y6 = csubq(a[1], y0);
+    let y7 = csubq(a[2], y0);
+    let y8 = csubq(a[3], y0);
+    let y9 = csubq(a[4], y0);
+    let y10 = csubq(a[5], y0);
+    let y11 = csubq(a[6], y0);
+    let y12 = csubq(a[7], y0);
+
+    // bitpack
+    let y4 = _mm256_or_si256(_mm256_slli_epi16(y6, 12), y5);
+    let y5 = _mm256_or_si256(_mm256_srli_epi16(y6, 4), _mm256_slli_epi16(y7, 8));
+    let y6 = _mm256_or_si256(_mm256_srli_epi16(y7, 8), _mm256_slli_epi16(y8, 4));
+    let y7 = _mm256_or_si256(_mm256_slli_epi16(y10, 12), y9);
+    let y8 = _mm256_or_si256(_mm256_srli_epi16(y10, 4), _mm256_slli_epi16(y11, 8));
+    let y9 = _mm256_or_si256(_mm256_srli_epi16(y11, 8), _mm256_slli_epi16(y12, 4));
+
+    let (y3, y5) = shuffle1(y4, y5);
+    let (y4, y7) = shuffle1(y6, y7);
+    let (y6, y9) = shuffle1(y8, y9);
+
+    let (y8, y4) = shuffle2(y3, y4);
+    let (y3, y5) = shuffle2(y6, y5);
+    let (y6, y9) = shuffle2(y7, y9);
+
+    let (y7, y3) = shuffle4(y8, y3);
+    let (y8, y4) = shuffle4(y6, y4);
+    let (y6, y9) = shuffle4(y5, y9);
+
+    let (y5, y8) = shuffle8(y7, y8);
+    let (y7, y3) = shuffle8(y6, y3);
+    let (y6, y9) = shuffle8(y4, y9);
+
+    let out = [y5, y7, y6, y8, y3, y9];
+    for (chunk, f) in r.chunks_exact_mut(32).zip(out.iter()) {
+        _mm256_storeu_si256(chunk.as_mut_ptr() as *mut __m256i, *f);
+    }
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn ntttobytes_avx(r: &mut [u8], a: &Poly) {
+    let q = qdata(_16XQ);
+    ntttobytes128_avx(&mut r[..KYBER_POLYBYTES / 2], &a.vec[..8], q);
+    ntttobytes128_avx(&mut r[KYBER_POLYBYTES / 2..KYBER_POLYBYTES], &a.vec[8..], q);
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn nttfrombytes128_avx(r: &mut [__m256i], a: &[u8], y0: __m256i) {
+    let mut f = [_mm256_setzero_si256(); 6];
+    for (f, chunk) in f.iter_mut().zip(a.chunks_exact(32)) {
+        *f = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+    }
+    let [y4, y5, y6, y7, y8, y9] = f;
+
+    let (y3, y7) = shuffle8(y4, y7);
+    let (y4, y8) = shuffle8(y5, y8);
+    let (y5, y9) = shuffle8(y6, y9);
+
+    let (y6, y8) = shuffle4(y3, y8);
+    let (y3, y5) = shuffle4(y7, y5);
+    let (y7, y9) = shuffle4(y4, y9);
+
+    let (y4, y5) = shuffle2(y6, y5);
+    let (y6, y7) = shuffle2(y8, y7);
+    let (y8, y9) = shuffle2(y3, y9);
+
+    let (y10, y7) = shuffle1(y4, y7);
+    let (y4, y8) = shuffle1(y5, y8);
+    let (y5, y9) = shuffle1(y6, y9);
+
+    // bitunpack
+    let y11 = _mm256_or_si256(_mm256_srli_epi16(y10, 12), _mm256_slli_epi16(y7, 4));
+    let y10 = _mm256_and_si256(y0, y10);
+    let y11 = _mm256_and_si256(y0, y11);
+
+    let y12 = _mm256_or_si256(_mm256_srli_epi16(y7, 8), _mm256_slli_epi16(y4, 8));
+    let y12 = _mm256_and_si256(y0, y12);
+
+    let y13 = _mm256_and_si256(y0, _mm256_srli_epi16(y4, 4));
+
+    let y14 = _mm256_or_si256(_mm256_srli_epi16(y8, 12), _mm256_slli_epi16(y5, 4));
+    let y8 = _mm256_and_si256(y0, y8);
+    let y14 = _mm256_and_si256(y0, y14);
+
+    let y15 = _mm256_or_si256(_mm256_srli_epi16(y5, 8), _mm256_slli_epi16(y9, 8));
+    let y15 = _mm256_and_si256(y0, y15);
+
+    let y1 = _mm256_and_si256(y0, _mm256_srli_epi16(y9, 4));
+
+    r[0] = y10;
+    r[1] = y11;
+    r[2] = y12;
+    r[3] = y13;
+    r[4] = y8;
+    r[5] = y14;
+    r[6] = y15;
+    r[7] = y1;
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn nttfrombytes_avx(r: &mut Poly, a: &[u8]) {
+    let mask = qdata(_16XMASK);
+    nttfrombytes128_avx(&mut r.vec[..8], &a[..KYBER_POLYBYTES / 2], mask);
+    nttfrombytes128_avx(
+        &mut r.vec[8..],
+        &a[KYBER_POLYBYTES / 2..KYBER_POLYBYTES],
+        mask,
+    );
+}
@@ -0,0 +1,285 @@
+//! Selects the IND-CPA implementation for a security level.
+//!
+//! The avx2 code is specialised for the level chosen with feature flags and
+//! only runs on CPUs supporting the instructions it uses, anything else runs
+//! on the portable reference code.
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+use crate::{avx2, params::KYBER_K};
+use crate::{reference, reference::polyvec::Polyvec, CryptoRng, KyberError, RngCore};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Uses the optimised backend for this security level
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+fn optimised<const K: usize>() -> bool {
+    K == KYBER_K && avx2_available()
+}
+
+/// Runtime detection of the instructions used by the avx2 backend
+#[cfg(all(target_arch = "x86_64", feature = "avx2", feature = "std"))]
+pub(crate) fn avx2_available() -> bool {
+    is_x86_feature_detected!("avx2")
+        && is_x86_feature_detected!("bmi2")
+        && is_x86_feature_detected!("popcnt")
+        && (!cfg!(feature = "90s") || is_x86_feature_detected!("aes"))
+}
+
+/// Without `std` the CPU can't be queried, the avx2 backend is used when the
+/// target features were enabled at compile time
+#[cfg(all(target_arch = "x86_64", feature = "avx2", not(feature = "std")))]
+pub(crate) fn avx2_available() -> bool {
+    cfg!(all(
+        target_feature = "avx2",
+        target_feature = "bmi2",
+        target_feature = "popcnt"
+    )) && (!cfg!(feature = "90s") || cfg!(target_feature = "aes"))
+}
+
+pub fn indcpa_keypair<const K: usize, R>(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    seed: Option<(&[u8], &[u8])>,
+    rng: &mut R,
+) -> Result<(), KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        // Safety: the CPU features were checked by optimised()
+        return unsafe { avx2::indcpa::indcpa_keypair(pk, sk, seed, rng) };
+    }
+    reference::indcpa::indcpa_keypair::<K, R>(pk, sk, seed, rng)
+}
+
+#[cfg(not(feature = "90s"))]
+pub fn indcpa_keypair_derand<const K: usize>(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    publicseed: &[u8],
+    noiseseed: &[u8],
+) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        // Safety: the CPU features were checked by optimised()
+        return unsafe { avx2::indcpa::indcpa_keypair_derand(pk, sk, publicseed, noiseseed) };
+    }
+    reference::indcpa::indcpa_keypair_derand::<K>(pk, sk, publicseed, noiseseed)
+}
+
+pub fn indcpa_enc<const K: usize>(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        // Safety: the CPU features were checked by optimised()
+        return unsafe { avx2::indcpa::indcpa_enc(c, m, pk, coins) };
+    }
+    reference::indcpa::indcpa_enc::<K>(c, m, pk, coins)
+}
+
+/// Public key unpacked and transposed matrix A generated by
+/// `indcpa_expand_pk`, in the layout of the backend picked when the key was
+/// expanded so encryption uses it without conversion
+// Both variants hold the same matrix when the avx2 one is used, clippy
+// sizes the generic reference variant as empty
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub enum ExpandedPk<const K: usize> {
+    Reference {
+        at: [Polyvec<K>; K],
+        pkpv: Polyvec<K>,
+    },
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    Avx2 {
+        at: [avx2::polyvec::Polyvec; KYBER_K],
+        pkpv: avx2::polyvec::Polyvec,
+    },
+}
+
+impl<const K: usize> Default for ExpandedPk<K> {
+    fn default() -> Self {
+        ExpandedPk::Reference {
+            at: [Polyvec::new(); K],
+            pkpv: Polyvec::new(),
+        }
+    }
+}
+
+pub fn indcpa_expand_pk<const K: usize>(exp: &mut ExpandedPk<K>, pk: &[u8]) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        *exp = ExpandedPk::Avx2 {
+            at: [avx2::polyvec::Polyvec::new(); KYBER_K],
+            pkpv: avx2::polyvec::Polyvec::new(),
+        };
+    }
+    match exp {
+        ExpandedPk::Reference { at, pkpv } => {
+            reference::indcpa::indcpa_expand_pk::<K>(at, pkpv, pk)
+        }
+        #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+        ExpandedPk::Avx2 { at, pkpv } => {
+            // Safety: only built after optimised() checked the CPU features
+            unsafe { avx2::indcpa::indcpa_expand_pk(at, pkpv, pk) }
+        }
+    }
+}
+
+pub fn indcpa_enc_expanded<const K: usize>(
+    c: &mut [u8],
+    m: &[u8],
+    exp: &ExpandedPk<K>,
+    coins: &[u8],
+) {
+    match exp {
+        ExpandedPk::Reference { at, pkpv } => {
+            reference::indcpa::indcpa_enc_expanded::<K>(c, m, at, pkpv, coins)
+        }
+        #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+        ExpandedPk::Avx2 { at, pkpv } => {
+            // Safety: only built after optimised() checked the CPU features
+            unsafe { avx2::indcpa::indcpa_enc_expanded(c, m, at, pkpv, coins) }
+        }
+    }
+}
+
+/// NTT domain secret vector unpacked by `indcpa_expand_sk`, zeroized on drop
+/// with the `zeroize` feature
+#[derive(Clone)]
+pub struct ExpandedSk<const K: usize> {
+    skpv: Polyvec<K>,
+}
+
+impl<const K: usize> Default for ExpandedSk<K> {
+    fn default() -> Self {
+        ExpandedSk {
+            skpv: Polyvec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const K: usize> Drop for ExpandedSk<K> {
+    fn drop(&mut self) {
+        for poly in self.skpv.vec.iter_mut() {
+            poly.coeffs.zeroize();
+        }
+    }
+}
+
+pub fn indcpa_expand_sk<const K: usize>(exp: &mut ExpandedSk<K>, sk: &[u8]) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        // Safety: the CPU features were checked by optimised()
+        let mut skpv = avx2::polyvec::Polyvec::new();
+        unsafe { avx2::indcpa::indcpa_expand_sk(&mut skpv, sk) };
+        from_avx2(&mut exp.skpv, &skpv);
+        #[cfg(feature = "zeroize")]
+        skpv.zeroize();
+        return;
+    }
+    reference::indcpa::indcpa_expand_sk::<K>(&mut exp.skpv, sk)
+}
+
+pub fn indcpa_dec_expanded<const K: usize>(m: &mut [u8], c: &[u8], exp: &ExpandedSk<K>) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        // Safety: the CPU features were checked by optimised()
+        let mut skpv = avx2::polyvec::Polyvec::new();
+        to_avx2(&mut skpv, &exp.skpv);
+        unsafe { avx2::indcpa::indcpa_dec_expanded(m, c, &skpv) };
+        #[cfg(feature = "zeroize")]
+        skpv.zeroize();
+        return;
+    }
+    reference::indcpa::indcpa_dec_expanded::<K>(m, c, &exp.skpv)
+}
+
+// Moves polynomials between the aligned avx2 vectors and the cached ones,
+// the coefficients keep the avx2 ordering
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+fn from_avx2<const K: usize>(r: &mut Polyvec<K>, a: &avx2::polyvec::Polyvec) {
+    for i in 0..K {
+        r.vec[i].coeffs = unsafe { a.vec[i].coeffs };
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+fn to_avx2<const K: usize>(r: &mut avx2::polyvec::Polyvec, a: &Polyvec<K>) {
+    for i in 0..K {
+        r.vec[i].coeffs = a.vec[i].coeffs;
+    }
+}
+
+pub fn indcpa_dec<const K: usize>(m: &mut [u8], c: &[u8], sk: &[u8]) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if optimised::<K>() {
+        // Safety: the CPU features were checked by optimised()
+        return unsafe { avx2::indcpa::indcpa_dec(m, c, sk) };
+    }
+    reference::indcpa::indcpa_dec::<K>(m, c, sk)
+}
+
+#[cfg(all(test, target_arch = "x86_64", feature = "avx2"))]
+mod tests {
+    use super::*;
+    use crate::{api::DummyRng, params::*};
+
+    // Both backends must produce the same bytes, runs when the CPU supports avx2
+    #[test]
+    fn avx2_matches_reference() {
+        if !avx2_available() {
+            return;
+        }
+        let seed = [7u8; KYBER_SYMBYTES];
+        let m = [9u8; KYBER_SYMBYTES];
+        let coins = [3u8; KYBER_SYMBYTES];
+        let (mut pk1, mut sk1) = (
+            [0u8; KYBER_INDCPA_PUBLICKEYBYTES],
+            [0u8; KYBER_INDCPA_SECRETKEYBYTES],
+        );
+        let (mut pk2, mut sk2) = (
+            [0u8; KYBER_INDCPA_PUBLICKEYBYTES],
+            [0u8; KYBER_INDCPA_SECRETKEYBYTES],
+        );
+        let (mut c1, mut c2) = ([0u8; KYBER_INDCPA_BYTES], [0u8; KYBER_INDCPA_BYTES]);
+        let (mut m1, mut m2) = ([0u8; KYBER_SYMBYTES], [0u8; KYBER_SYMBYTES]);
+
+        reference::indcpa::indcpa_keypair::<KYBER_K, _>(
+            &mut pk1,
+            &mut sk1,
+            Some((&seed, &seed)),
+            &mut DummyRng {},
+        )
+        .unwrap();
+        reference::indcpa::indcpa_enc::<KYBER_K>(&mut c1, &m, &pk1, &coins);
+        reference::indcpa::indcpa_dec::<KYBER_K>(&mut m1, &c1, &sk1);
+        unsafe {
+            avx2::indcpa::indcpa_keypair(
+                &mut pk2,
+                &mut sk2,
+                Some((&seed, &seed)),
+                &mut DummyRng {},
+            )
+            .unwrap();
+            avx2::indcpa::indcpa_enc(&mut c2, &m, &pk2, &coins);
+            avx2::indcpa::indcpa_dec(&mut m2, &c2, &sk2);
+        }
+        assert_eq!(pk1, pk2);
+        assert_eq!(&sk1[..], &sk2[..]);
+        assert_eq!(&c1[..], &c2[..]);
+        assert_eq!((m1, m2), (m, m));
+    }
+
+    #[cfg(all(feature = "90s", not(feature = "90s-fixslice")))]
+    #[test]
+    fn aesni_prf_matches_bitslice() {
+        if !avx2_available() {
+            return;
+        }
+        let key = [5u8; KYBER_SYMBYTES];
+        let (mut out1, mut out2) = ([0u8; 200], [0u8; 200]);
+        crate::symmetric::prf(&mut out1, 200, &key, 3);
+        crate::aes256ctr::aes256ctr_prf(&mut out2, 200, &key, 3);
+        assert_eq!(&out1[..], &out2[..]);
+    }
+}
@@ -0,0 +1,205 @@
+//! PKCS#8 and SubjectPublicKeyInfo encoding of keys
+//!
+//! Keys are identified with the ML-KEM algorithm identifiers of
+//! draft-ietf-lamps-kyber-certificates, `id-alg-ml-kem-512`, `-768` and
+//! `-1024`, without parameters. The public key is the raw key in the
+//! BIT STRING, the private key is one of
+//!
+//! ```text
+//! ML-KEM-PrivateKey ::= CHOICE {
+//!   seed [0] OCTET STRING (SIZE (64)),
+//!   expandedKey OCTET STRING,
+//!   both SEQUENCE {
+//!     seed OCTET STRING (SIZE (64)),
+//!     expandedKey OCTET STRING } }
+//! ```
+//!
+//! The seed is the FIPS 203 `d || z` seed, it is expanded with the ML-KEM
+//! key generation of the [mlkem](crate::mlkem) module. Secret keys are
+//! exported in the expanded form, [`mlkem::seed_to_pkcs8_der`] exports a
+//! seed.
+//!
+//! [`mlkem::seed_to_pkcs8_der`]: crate::mlkem::seed_to_pkcs8_der
+use crate::{
+    api::DummyRng, mlkem::mlkem_keypair, params::*, types::ct_eq, KyberError, PublicKey, SecretKey,
+};
+use core::convert::TryFrom;
+use pkcs8::{
+    der::{
+        asn1::{BitStringRef, ContextSpecificRef, OctetStringRef},
+        AnyRef, Decode, Encode, Tag, TagMode, TagNumber, Tagged,
+    },
+    spki::{AlgorithmIdentifierRef, AssociatedAlgorithmIdentifier, SubjectPublicKeyInfoRef},
+    Document, EncodePrivateKey, EncodePublicKey, ObjectIdentifier, PrivateKeyInfo, SecretDocument,
+};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// id-alg-ml-kem-512
+pub const ID_ALG_ML_KEM_512: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.1");
+/// id-alg-ml-kem-768
+pub const ID_ALG_ML_KEM_768: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.2");
+/// id-alg-ml-kem-1024
+pub const ID_ALG_ML_KEM_1024: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.3");
+
+const SEEDBYTES: usize = 2 * KYBER_SYMBYTES;
+// Tag and length of the largest expanded key
+const PRIVATEKEY_MAX: usize = 4 + Params::<KYBER_K_MAX>::SECRETKEYBYTES;
+
+const fn oid(k: usize) -> ObjectIdentifier {
+    match k {
+        2 => ID_ALG_ML_KEM_512,
+        3 => ID_ALG_ML_KEM_768,
+        _ => ID_ALG_ML_KEM_1024,
+    }
+}
+
+impl<P: KyberParams> AssociatedAlgorithmIdentifier for PublicKey<P> {
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = AlgorithmIdentifierRef {
+        oid: oid(P::K),
+        parameters: None,
+    };
+}
+
+impl<P: KyberParams> AssociatedAlgorithmIdentifier for SecretKey<P> {
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> =
+        PublicKey::<P>::ALGORITHM_IDENTIFIER;
+}
+
+impl<P: KyberParams> EncodePublicKey for PublicKey<P> {
+    fn to_public_key_der(&self) -> pkcs8::spki::Result<Document> {
+        let spki = SubjectPublicKeyInfoRef {
+            algorithm: Self::ALGORITHM_IDENTIFIER,
+            subject_public_key: BitStringRef::from_bytes(self.as_ref())?,
+        };
+        Ok(Document::encode_msg(&spki)?)
+    }
+}
+
+impl<P: KyberParams> TryFrom<SubjectPublicKeyInfoRef<'_>> for PublicKey<P> {
+    type Error = pkcs8::spki::Error;
+
+    /// Public keys failing the FIPS 203 modulus check are rejected
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> pkcs8::spki::Result<Self> {
+        check_algorithm::<P>(&spki.algorithm)?;
+        let bytes = spki
+            .subject_public_key
+            .as_bytes()
+            .ok_or(pkcs8::spki::Error::KeyMalformed)?;
+        let pk = PublicKey::try_from(bytes).map_err(|_| pkcs8::spki::Error::KeyMalformed)?;
+        P::check_public_key(pk.as_ref()).map_err(|_| pkcs8::spki::Error::KeyMalformed)?;
+        Ok(pk)
+    }
+}
+
+impl<P: KyberParams> EncodePrivateKey for SecretKey<P> {
+    /// Encodes the expanded form
+    fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+        let mut buf = [0u8; PRIVATEKEY_MAX];
+        let res = encode_private_key::<P>(&mut buf, &OctetStringRef::new(self.as_ref())?);
+        #[cfg(feature = "zeroize")]
+        buf.zeroize();
+        res
+    }
+}
+
+impl<P: KyberParams> TryFrom<PrivateKeyInfo<'_>> for SecretKey<P> {
+    type Error = pkcs8::Error;
+
+    /// Accepts the seed, expanded and both forms. Seeds are expanded with the
+    /// FIPS 203 key generation, when both are given they have to match.
+    /// Expanded keys failing the FIPS 203 hash check are rejected.
+    fn try_from(info: PrivateKeyInfo<'_>) -> pkcs8::Result<Self> {
+        check_algorithm::<P>(&info.algorithm)?;
+        let key = AnyRef::from_der(info.private_key)?;
+        let (seed, expanded) = match key.tag() {
+            Tag::ContextSpecific {
+                constructed: false,
+                number: TagNumber::N0,
+            } => (Some(key.value()), None),
+            Tag::OctetString => (None, Some(key.value())),
+            Tag::Sequence => key.sequence(|reader| {
+                let seed = OctetStringRef::decode(reader)?;
+                let expanded = OctetStringRef::decode(reader)?;
+                Ok((Some(seed.as_bytes()), Some(expanded.as_bytes())))
+            })?,
+            _ => return Err(pkcs8::Error::KeyMalformed),
+        };
+
+        let sk = match seed {
+            Some(seed) => {
+                let sk = expand_seed::<P>(seed)?;
+                if matches!(expanded, Some(expanded) if !ct_eq(sk.as_ref(), expanded)) {
+                    return Err(pkcs8::Error::KeyMalformed);
+                }
+                sk
+            }
+            None => SecretKey::try_from(expanded.unwrap_or_default())
+                .map_err(|_| pkcs8::Error::KeyMalformed)?,
+        };
+        P::check_secret_key(sk.as_ref()).map_err(|_| pkcs8::Error::KeyMalformed)?;
+        Ok(sk)
+    }
+}
+
+// Wraps the ML-KEM-PrivateKey in a PKCS#8 document
+pub(crate) fn encode_private_key<P: KyberParams>(
+    buf: &mut [u8],
+    key: &impl Encode,
+) -> pkcs8::Result<SecretDocument> {
+    let key = key.encode_to_slice(buf)?;
+    let info = PrivateKeyInfo::new(SecretKey::<P>::ALGORITHM_IDENTIFIER, key);
+    Ok(SecretDocument::encode_msg(&info)?)
+}
+
+// seed [0] IMPLICIT OCTET STRING
+pub(crate) fn encode_seed<P: KyberParams>(seed: &[u8]) -> Result<SecretDocument, KyberError> {
+    if seed.len() != SEEDBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let mut buf = [0u8; 2 + SEEDBYTES];
+    let seed = OctetStringRef::new(seed).map_err(|_| KyberError::InvalidInput)?;
+    let key = ContextSpecificRef {
+        tag_number: TagNumber::N0,
+        tag_mode: TagMode::Implicit,
+        value: &seed,
+    };
+    let res = encode_private_key::<P>(&mut buf, &key).map_err(|_| KyberError::InvalidInput);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
+    res
+}
+
+fn check_algorithm<P: KyberParams>(
+    algorithm: &AlgorithmIdentifierRef<'_>,
+) -> pkcs8::spki::Result<()> {
+    algorithm.assert_algorithm_oid(oid(P::K))?;
+    if algorithm.parameters.is_some() {
+        return Err(pkcs8::spki::Error::KeyMalformed);
+    }
+    Ok(())
+}
+
+fn expand_seed<P: KyberParams>(seed: &[u8]) -> pkcs8::Result<SecretKey<P>> {
+    if seed.len() != SEEDBYTES {
+        return Err(pkcs8::Error::KeyMalformed);
+    }
+    let mut pk = PublicKey::<P>::zeroed();
+    let mut sk = SecretKey::<P>::zeroed();
+    let (pk, sk_bytes, mut rng) = (pk.as_mut(), sk.as_mut(), DummyRng {});
+    let seed = Some(seed.split_at(KYBER_SYMBYTES));
+    // Can't fail when seeded
+    let _ = match P::K {
+        2 => mlkem_keypair::<2, _>(pk, sk_bytes, &mut rng, seed),
+        3 => mlkem_keypair::<3, _>(pk, sk_bytes, &mut rng, seed),
+        _ => mlkem_keypair::<4, _>(pk, sk_bytes, &mut rng, seed),
+    };
+    Ok(sk)
+}
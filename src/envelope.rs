@@ -0,0 +1,166 @@
+//! Envelope encryption of a message to many recipients
+//!
+//! The message is encrypted once under a random data encryption key, which
+//! is then wrapped for every recipient in a [sealed box](crate::sealedbox).
+//! Each wrapped key is tagged with the recipient's [`KeyId`], the hash of
+//! their public key that is also stored in their secret key, so a recipient
+//! finds their entry directly instead of trying to open all of them.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(), KyberError> {
+//! let mut rng = rand::thread_rng();
+//! let alice = keypair(&mut rng)?;
+//! let bob = keypair(&mut rng)?;
+//! let recipients = [alice.public, bob.public];
+//!
+//! let sealed = envelope::seal(&recipients, b"file key", b"", &mut rng)?;
+//! assert_eq!(envelope::open(&alice.secret, &sealed, b"")?, b"file key");
+//! assert_eq!(envelope::open(&bob.secret, &sealed, b"")?, b"file key");
+//! # Ok(()) }
+//! ```
+//!
+//! The envelope is a two byte big endian recipient count, followed by one
+//! entry per recipient of their key id and wrapped key, then the encrypted
+//! message. Anyone can read the key ids, envelopes do not hide who they are
+//! addressed to. Requires the `envelope` feature.
+extern crate alloc;
+
+use crate::{
+    params::*,
+    rng::randombytes,
+    sealedbox::{self, SEALEDBOX_OVERHEAD, SEALEDBOX_TAGBYTES},
+    symmetric::hash_h,
+    CryptoRng, KyberError, PublicKey, RngCore, SecretKey,
+};
+use alloc::vec::Vec;
+use chacha20poly1305::{aead::AeadInPlace, ChaCha20Poly1305, KeyInit};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Size of a recipient key id
+pub const ENVELOPE_KEYIDBYTES: usize = KYBER_SYMBYTES;
+/// Size of the entry added to the envelope for every recipient
+pub const ENVELOPE_RECIPIENTBYTES: usize = ENVELOPE_KEYIDBYTES + SEALEDBOX_OVERHEAD + DEKBYTES;
+
+/// Identifies the recipient of a wrapped key, the hash of their public key
+pub type KeyId = [u8; ENVELOPE_KEYIDBYTES];
+
+const DEKBYTES: usize = 32;
+const COUNTBYTES: usize = 2;
+// Every data encryption key is used for one message only
+const NONCE: [u8; 12] = [0u8; 12];
+
+/// Key id of a public key.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// # let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// assert_eq!(envelope::key_id(&keys.public), envelope::secret_key_id(&keys.secret));
+/// # Ok(()) }
+/// ```
+pub fn key_id(pk: &PublicKey) -> KeyId {
+    let mut id = [0u8; ENVELOPE_KEYIDBYTES];
+    hash_h(&mut id, pk.as_ref(), KYBER_PUBLICKEYBYTES);
+    id
+}
+
+/// Key id of the public key belonging to a secret key, read from the hash
+/// stored inside it.
+pub fn secret_key_id(sk: &SecretKey) -> KeyId {
+    let start = KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    let mut id = [0u8; ENVELOPE_KEYIDBYTES];
+    id.copy_from_slice(&sk.as_ref()[start..start + ENVELOPE_KEYIDBYTES]);
+    id
+}
+
+/// Encrypts a message to every public key in `recipients`.
+///
+/// `aad` is authenticated but not encrypted, the same value has to be
+/// given to [`open`]. Returns [`KyberError::InvalidInput`] if there are no
+/// recipients or more than 65535.
+pub fn seal<R>(
+    recipients: &[PublicKey],
+    plaintext: &[u8],
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    if recipients.is_empty() || recipients.len() > u16::MAX as usize {
+        return Err(KyberError::InvalidInput);
+    }
+    let mut dek = [0u8; DEKBYTES];
+    randombytes(&mut dek, DEKBYTES, rng)?;
+
+    let header_len = COUNTBYTES + recipients.len() * ENVELOPE_RECIPIENTBYTES;
+    let mut sealed = Vec::with_capacity(header_len + plaintext.len() + SEALEDBOX_TAGBYTES);
+    sealed.extend_from_slice(&(recipients.len() as u16).to_be_bytes());
+    for pk in recipients {
+        let id = key_id(pk);
+        sealed.extend_from_slice(&id);
+        sealed.extend_from_slice(&sealedbox::seal(pk, &dek, &id, rng)?);
+    }
+
+    // The header is authenticated with the message, recipients can't be
+    // added, removed or swapped
+    sealed.extend_from_slice(plaintext);
+    let (header, body) = sealed.split_at_mut(header_len);
+    let tag = ChaCha20Poly1305::new(&dek.into())
+        .encrypt_in_place_detached(&NONCE.into(), &aad_for(header, aad), body)
+        .map_err(|_| KyberError::InvalidInput)?;
+    sealed.extend_from_slice(&tag);
+    #[cfg(feature = "zeroize")]
+    dek.zeroize();
+    Ok(sealed)
+}
+
+/// Opens an envelope with a secret key, returning the message.
+///
+/// Returns [`KyberError::RecipientNotFound`] if the envelope has no entry
+/// for the key, [`KyberError::InvalidSealedBox`] if the envelope was
+/// modified or `aad` differs and [`KyberError::InvalidInput`] if it is
+/// malformed.
+pub fn open(sk: &SecretKey, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, KyberError> {
+    if sealed.len() < COUNTBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let count = u16::from_be_bytes([sealed[0], sealed[1]]) as usize;
+    let header_len = COUNTBYTES + count * ENVELOPE_RECIPIENTBYTES;
+    if count == 0 || sealed.len() < header_len + SEALEDBOX_TAGBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let (header, rest) = sealed.split_at(header_len);
+    let (body, tag) = rest.split_at(rest.len() - SEALEDBOX_TAGBYTES);
+
+    let id = secret_key_id(sk);
+    let entry = header[COUNTBYTES..]
+        .chunks_exact(ENVELOPE_RECIPIENTBYTES)
+        .find(|entry| entry[..ENVELOPE_KEYIDBYTES] == id)
+        .ok_or(KyberError::RecipientNotFound)?;
+    let mut dek = [0u8; DEKBYTES];
+    dek.copy_from_slice(&sealedbox::open(sk, &entry[ENVELOPE_KEYIDBYTES..], &id)?);
+
+    let mut plaintext = body.to_vec();
+    let res = ChaCha20Poly1305::new(&dek.into()).decrypt_in_place_detached(
+        &NONCE.into(),
+        &aad_for(header, aad),
+        &mut plaintext,
+        tag.into(),
+    );
+    #[cfg(feature = "zeroize")]
+    dek.zeroize();
+    res.map_err(|_| KyberError::InvalidSealedBox)?;
+    Ok(plaintext)
+}
+
+// Associated data of the message, the header followed by the caller's aad
+fn aad_for(header: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(header.len() + aad.len());
+    out.extend_from_slice(header);
+    out.extend_from_slice(aad);
+    out
+}
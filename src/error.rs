@@ -11,6 +11,23 @@ pub enum KyberError {
     RandomBytesGeneration,
     /// Error when generating keys
     InvalidKey,
+    /// The public key failed the FIPS 203 modulus check, it encodes
+    /// coefficients that are not reduced modulo q.
+    InvalidPublicKey,
+    /// The secret key failed the FIPS 203 hash check, the stored hash does not
+    /// match the embedded public key.
+    InvalidSecretKey,
+    /// The sealed box failed to authenticate, it was modified or sealed to a
+    /// different key.
+    InvalidSealedBox,
+    /// The HPKE message failed to authenticate, it was modified, sealed to a
+    /// different key or opened with a different context.
+    InvalidHpkeMessage,
+    /// The envelope has no entry for the given secret key.
+    RecipientNotFound,
+    /// The key confirmation tag of a key exchange did not match, the parties
+    /// derived different shared secrets.
+    KeyConfirmation,
 }
 
 impl core::fmt::Display for KyberError {
@@ -27,6 +44,27 @@ impl core::fmt::Display for KyberError {
             KyberError::InvalidKey => {
                 write!(f, "The secret and public key given does not match.")
             }
+            KyberError::InvalidPublicKey => {
+                write!(
+                    f,
+                    "The public key contains coefficients not reduced modulo q"
+                )
+            }
+            KyberError::InvalidSecretKey => {
+                write!(f, "The secret key hash does not match its public key")
+            }
+            KyberError::InvalidSealedBox => {
+                write!(f, "The sealed box could not be authenticated")
+            }
+            KyberError::InvalidHpkeMessage => {
+                write!(f, "The HPKE message could not be authenticated")
+            }
+            KyberError::RecipientNotFound => {
+                write!(f, "The envelope is not addressed to this key")
+            }
+            KyberError::KeyConfirmation => {
+                write!(f, "The key confirmation tag does not match")
+            }
         }
     }
 }
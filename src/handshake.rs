@@ -0,0 +1,402 @@
+//! Key exchanges as a sequence of states
+//!
+//! The [`Uake`](crate::Uake) and [`Ake`](crate::Ake) structs are filled in
+//! step by step, nothing stops a step from being skipped or repeated, or a
+//! struct from being reused for another session. Here each step consumes
+//! the state left by the previous one, so steps run once and in order. The
+//! shared secret is only readable from [`Established`], the ephemeral keys
+//! are dropped by the step that uses them last and zeroized with the
+//! `zeroize` feature.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! use pqc_kyber::handshake::uake;
+//! # fn main() -> Result<(), KyberError> {
+//! let mut rng = rand::thread_rng();
+//! let bob_keys = keypair(&mut rng)?;
+//!
+//! let (alice, client_init) = uake::initiate(KexVersion::V2, &bob_keys.public, &mut rng)?;
+//! let (bob, server_send) = uake::respond(KexVersion::V2, client_init, &bob_keys.secret, &mut rng)?;
+//! let alice = alice.finish(server_send)?;
+//! let bob = bob.finish();
+//!
+//! assert_eq!(alice.shared_secret(), bob.shared_secret());
+//! # Ok(()) }
+//! ```
+//!
+//! With the key confirmation round the server only reaches [`Established`]
+//! once the client's tag checks out:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! use pqc_kyber::handshake::ake;
+//! # fn main() -> Result<(), KyberError> {
+//! # let mut rng = rand::thread_rng();
+//! let alice_keys = keypair(&mut rng)?;
+//! let bob_keys = keypair(&mut rng)?;
+//!
+//! let (alice, client_init) = ake::initiate(KexVersion::V2, &bob_keys.public, &mut rng)?;
+//! let (bob, server_send) = ake::respond(
+//!     KexVersion::V2, client_init, &alice_keys.public, &bob_keys.secret, &mut rng
+//! )?;
+//! let (alice, client_tag) = alice.confirm(server_send, &bob.tag(), &alice_keys.secret)?;
+//! let bob = bob.confirm(&client_tag)?;
+//!
+//! assert_eq!(alice.shared_secret(), bob.shared_secret());
+//! # Ok(()) }
+//! ```
+use crate::{
+    kex::{check_tag, tag_for, KdfParams, KexKeys, CLIENT_CONFIRM_LABEL, SERVER_CONFIRM_LABEL},
+    ConfirmTag, KyberError, SharedSecret,
+};
+
+/// Server side of a key exchange, after responding to the client
+#[derive(Debug)]
+pub struct ServerResponded {
+    shared_secret: SharedSecret,
+    confirm_key: SharedSecret,
+}
+
+impl ServerResponded {
+    /// Tag confirming the shared secret to the client, sent with the
+    /// response when the client checks it
+    pub fn tag(&self) -> ConfirmTag {
+        tag_for(&self.confirm_key, SERVER_CONFIRM_LABEL)
+    }
+
+    /// Completes the exchange without key confirmation
+    pub fn finish(self) -> Established {
+        Established {
+            shared_secret: self.shared_secret,
+        }
+    }
+
+    /// Completes the exchange once the client's tag matches, otherwise
+    /// returns [`KyberError::KeyConfirmation`].
+    pub fn confirm(mut self, tag: &ConfirmTag) -> Result<Established, KyberError> {
+        check_tag(
+            &mut self.shared_secret,
+            &mut self.confirm_key,
+            tag,
+            CLIENT_CONFIRM_LABEL,
+        )?;
+        Ok(self.finish())
+    }
+}
+
+/// A completed key exchange
+#[derive(Debug, Eq, PartialEq)]
+pub struct Established {
+    shared_secret: SharedSecret,
+}
+
+impl Established {
+    /// The shared secret of the exchange
+    pub fn shared_secret(&self) -> &SharedSecret {
+        &self.shared_secret
+    }
+
+    /// Takes the shared secret out of the exchange
+    pub fn into_shared_secret(self) -> SharedSecret {
+        self.shared_secret
+    }
+}
+
+// Client tag of a completed exchange after checking the server's, both
+// keyed with the confirmation key derived next to the shared secret
+fn confirm_client(
+    (mut established, mut confirm_key): (Established, SharedSecret),
+    tag: &ConfirmTag,
+) -> Result<(Established, ConfirmTag), KyberError> {
+    check_tag(
+        &mut established.shared_secret,
+        &mut confirm_key,
+        tag,
+        SERVER_CONFIRM_LABEL,
+    )?;
+    let tag = tag_for(&confirm_key, CLIENT_CONFIRM_LABEL);
+    Ok((established, tag))
+}
+
+/// Unilaterally authenticated key exchange, only the server has a static
+/// key
+pub mod uake {
+    use super::*;
+    use crate::{
+        kex::{uake_init_a, uake_shared_a, uake_shared_b, UAKE_V2_LABEL},
+        params::*,
+        symmetric::hash_h,
+        CryptoRng, KexVersion, PublicKey, RngCore, SecretKey,
+    };
+
+    /// Client side of a key exchange, waiting for the server's response
+    #[derive(Debug)]
+    pub struct ClientInitiated<P: KyberParams = DefaultParams> {
+        send_a: P::UakeSendInit,
+        temp_key: SharedSecret,
+        eska: SecretKey<P>,
+        pk_hash: [u8; KYBER_SYMBYTES],
+        version: KexVersion,
+    }
+
+    /// Initiates a key exchange with the server's public key, returning the
+    /// client state and the message to send.
+    pub fn initiate<P, R>(
+        version: KexVersion,
+        pubkey: &PublicKey<P>,
+        rng: &mut R,
+    ) -> Result<(ClientInitiated<P>, P::UakeSendInit), KyberError>
+    where
+        P: KyberParams,
+        R: CryptoRng + RngCore,
+    {
+        let mut client = ClientInitiated::<P> {
+            send_a: P::UakeSendInit::zeroed(),
+            temp_key: SharedSecret::zeroed(),
+            eska: SecretKey::zeroed(),
+            pk_hash: [0u8; KYBER_SYMBYTES],
+            version,
+        };
+        uake_init_a::<P, R>(
+            client.send_a.as_mut(),
+            client.temp_key.as_mut(),
+            client.eska.as_mut(),
+            pubkey.as_ref(),
+            rng,
+        )?;
+        if version == KexVersion::V2 {
+            hash_h(&mut client.pk_hash, pubkey.as_ref(), P::PUBLICKEYBYTES);
+        }
+        let send_a = client.send_a;
+        Ok((client, send_a))
+    }
+
+    /// Responds to the client's message with the server's secret key,
+    /// returning the server state and the response to send.
+    pub fn respond<P, R>(
+        version: KexVersion,
+        send_a: P::UakeSendInit,
+        secretkey: &SecretKey<P>,
+        rng: &mut R,
+    ) -> Result<(ServerResponded, P::UakeSendResponse), KyberError>
+    where
+        P: KyberParams,
+        R: CryptoRng + RngCore,
+    {
+        let mut send_b = P::UakeSendResponse::zeroed();
+        let mut server = ServerResponded {
+            shared_secret: SharedSecret::zeroed(),
+            confirm_key: SharedSecret::zeroed(),
+        };
+        uake_shared_b::<P, R>(
+            send_b.as_mut(),
+            KexKeys {
+                k: server.shared_secret.as_mut(),
+                ck: server.confirm_key.as_mut(),
+            },
+            send_a.as_ref(),
+            secretkey.as_ref(),
+            KdfParams { version, psk: None },
+            None,
+            rng,
+        )?;
+        Ok((server, send_b))
+    }
+
+    impl<P: KyberParams> ClientInitiated<P> {
+        /// Completes the exchange with the server's response, without key
+        /// confirmation
+        pub fn finish(self, send_b: P::UakeSendResponse) -> Result<Established, KyberError> {
+            Ok(self.complete(send_b)?.0)
+        }
+
+        // The established exchange and its confirmation key
+        fn complete(
+            self,
+            send_b: P::UakeSendResponse,
+        ) -> Result<(Established, SharedSecret), KyberError> {
+            let transcript = [
+                UAKE_V2_LABEL,
+                &self.pk_hash,
+                self.send_a.as_ref(),
+                send_b.as_ref(),
+            ];
+            let mut established = Established {
+                shared_secret: SharedSecret::zeroed(),
+            };
+            let mut confirm_key = SharedSecret::zeroed();
+            uake_shared_a::<P>(
+                KexKeys {
+                    k: established.shared_secret.as_mut(),
+                    ck: confirm_key.as_mut(),
+                },
+                send_b.as_ref(),
+                self.temp_key.as_ref(),
+                self.eska.as_ref(),
+                KdfParams {
+                    version: self.version,
+                    psk: None,
+                },
+                &transcript,
+            )?;
+            Ok((established, confirm_key))
+        }
+
+        /// Completes the exchange once the server's tag matches, returning
+        /// the client's tag for [`ServerResponded::confirm`]. A mismatch
+        /// returns [`KyberError::KeyConfirmation`].
+        pub fn confirm(
+            self,
+            send_b: P::UakeSendResponse,
+            tag: &ConfirmTag,
+        ) -> Result<(Established, ConfirmTag), KyberError> {
+            confirm_client(self.complete(send_b)?, tag)
+        }
+    }
+}
+
+/// Mutually authenticated key exchange, both parties have a static key
+pub mod ake {
+    use super::*;
+    use crate::{
+        kex::{ake_init_a, ake_shared_a, ake_shared_b, pk_hash, AKE_V2_LABEL},
+        params::*,
+        symmetric::hash_h,
+        CryptoRng, KexVersion, PublicKey, RngCore, SecretKey,
+    };
+
+    /// Client side of a key exchange, waiting for the server's response
+    #[derive(Debug)]
+    pub struct ClientInitiated<P: KyberParams = DefaultParams> {
+        send_a: P::AkeSendInit,
+        temp_key: SharedSecret,
+        eska: SecretKey<P>,
+        pk_hash: [u8; KYBER_SYMBYTES],
+        version: KexVersion,
+    }
+
+    /// Initiates a key exchange with the server's public key, returning the
+    /// client state and the message to send.
+    pub fn initiate<P, R>(
+        version: KexVersion,
+        pubkey: &PublicKey<P>,
+        rng: &mut R,
+    ) -> Result<(ClientInitiated<P>, P::AkeSendInit), KyberError>
+    where
+        P: KyberParams,
+        R: CryptoRng + RngCore,
+    {
+        let mut client = ClientInitiated::<P> {
+            send_a: P::AkeSendInit::zeroed(),
+            temp_key: SharedSecret::zeroed(),
+            eska: SecretKey::zeroed(),
+            pk_hash: [0u8; KYBER_SYMBYTES],
+            version,
+        };
+        ake_init_a::<P, R>(
+            client.send_a.as_mut(),
+            client.temp_key.as_mut(),
+            client.eska.as_mut(),
+            pubkey.as_ref(),
+            rng,
+        )?;
+        if version == KexVersion::V2 {
+            hash_h(&mut client.pk_hash, pubkey.as_ref(), P::PUBLICKEYBYTES);
+        }
+        let send_a = client.send_a;
+        Ok((client, send_a))
+    }
+
+    /// Authenticates the client's message with the client's public key and
+    /// responds with the server's secret key, returning the server state and
+    /// the response to send.
+    pub fn respond<P, R>(
+        version: KexVersion,
+        send_a: P::AkeSendInit,
+        pubkey: &PublicKey<P>,
+        secretkey: &SecretKey<P>,
+        rng: &mut R,
+    ) -> Result<(ServerResponded, P::AkeSendResponse), KyberError>
+    where
+        P: KyberParams,
+        R: CryptoRng + RngCore,
+    {
+        let mut send_b = P::AkeSendResponse::zeroed();
+        let mut server = ServerResponded {
+            shared_secret: SharedSecret::zeroed(),
+            confirm_key: SharedSecret::zeroed(),
+        };
+        ake_shared_b::<P, R>(
+            send_b.as_mut(),
+            KexKeys {
+                k: server.shared_secret.as_mut(),
+                ck: server.confirm_key.as_mut(),
+            },
+            send_a.as_ref(),
+            secretkey.as_ref(),
+            pubkey.as_ref(),
+            KdfParams { version, psk: None },
+            rng,
+        )?;
+        Ok((server, send_b))
+    }
+
+    impl<P: KyberParams> ClientInitiated<P> {
+        /// Completes the exchange with the server's response and the client's
+        /// secret key, without key confirmation
+        pub fn finish(
+            self,
+            send_b: P::AkeSendResponse,
+            secretkey: &SecretKey<P>,
+        ) -> Result<Established, KyberError> {
+            Ok(self.complete(send_b, secretkey)?.0)
+        }
+
+        // The established exchange and its confirmation key
+        fn complete(
+            self,
+            send_b: P::AkeSendResponse,
+            secretkey: &SecretKey<P>,
+        ) -> Result<(Established, SharedSecret), KyberError> {
+            let transcript = [
+                AKE_V2_LABEL,
+                &self.pk_hash,
+                pk_hash::<P>(secretkey.as_ref()),
+                self.send_a.as_ref(),
+                send_b.as_ref(),
+            ];
+            let mut established = Established {
+                shared_secret: SharedSecret::zeroed(),
+            };
+            let mut confirm_key = SharedSecret::zeroed();
+            ake_shared_a::<P>(
+                KexKeys {
+                    k: established.shared_secret.as_mut(),
+                    ck: confirm_key.as_mut(),
+                },
+                send_b.as_ref(),
+                self.temp_key.as_ref(),
+                self.eska.as_ref(),
+                secretkey.as_ref(),
+                KdfParams {
+                    version: self.version,
+                    psk: None,
+                },
+                &transcript,
+            )?;
+            Ok((established, confirm_key))
+        }
+
+        /// Completes the exchange once the server's tag matches, returning
+        /// the client's tag for [`ServerResponded::confirm`]. A mismatch
+        /// returns [`KyberError::KeyConfirmation`].
+        pub fn confirm(
+            self,
+            send_b: P::AkeSendResponse,
+            tag: &ConfirmTag,
+            secretkey: &SecretKey<P>,
+        ) -> Result<(Established, ConfirmTag), KyberError> {
+            confirm_client(self.complete(send_b, secretkey)?, tag)
+        }
+    }
+}
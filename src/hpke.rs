@@ -0,0 +1,321 @@
+//! Hybrid Public Key Encryption (RFC 9180) with Kyber as the KEM
+//!
+//! Single-shot encryption of a message to a Kyber public key in the Base and
+//! PSK modes of HPKE, with HKDF-SHA256 as the KDF and either AES-128-GCM or
+//! ChaCha20-Poly1305 as the AEAD. The KEM is [`encapsulate`](crate::encapsulate)
+//! and [`decapsulate`](crate::decapsulate) at the security level selected with
+//! feature flags, its shared secret is used directly.
+//!
+//! Messages are encrypted in place and the tag returned separately so no
+//! allocations are needed.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # use pqc_kyber::hpke::*;
+//! # fn main() -> Result<(), KyberError> {
+//! let mut rng = rand::thread_rng();
+//! let keys = keypair(&mut rng)?;
+//! let hpke = Hpke::base(Aead::ChaCha20Poly1305);
+//!
+//! let mut msg = *b"Hello Bob";
+//! let (enc, tag) = hpke.seal(&keys.public, b"info", b"aad", &mut msg, &mut rng)?;
+//! hpke.open(&enc, &keys.secret, b"info", b"aad", &mut msg, &tag)?;
+//! assert_eq!(&msg, b"Hello Bob");
+//! # Ok(()) }
+//! ```
+//!
+//! Round 3 Kyber has no IANA assigned KEM identifier, see [`KEM_ID`].
+//! Requires the `hpke` feature.
+use crate::{
+    api::derive, decapsulate, encapsulate, params::*, Ciphertext, CryptoRng, Keypair, KyberError,
+    PublicKey, RngCore, SecretKey, SharedSecret,
+};
+use aes_gcm::{aead::AeadInPlace, Aes128Gcm, KeyInit};
+use chacha20poly1305::ChaCha20Poly1305;
+use core::fmt;
+use hkdf::{Hkdf, HkdfExtract};
+use sha2::Sha256;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// HPKE KEM identifier of the selected security level.
+///
+/// Round 3 Kyber has no IANA assigned codepoint, these are taken from the
+/// unassigned range and only interoperate with other users of this crate:
+///
+/// | Level     | KEM_ID | 90s mode |
+/// |-----------|--------|----------|
+/// | kyber512  | 0xff02 | 0xff12   |
+/// | kyber768  | 0xff03 | 0xff13   |
+/// | kyber1024 | 0xff04 | 0xff14   |
+pub const KEM_ID: u16 = 0xff00 | (KYBER_90S as u16) << 4 | KYBER_K as u16;
+/// HPKE KDF identifier of HKDF-SHA256
+pub const KDF_ID: u16 = 0x0001;
+/// Size of the AEAD authentication tag
+pub const HPKE_TAGBYTES: usize = 16;
+/// Smallest accepted pre-shared key
+pub const HPKE_MIN_PSKBYTES: usize = 32;
+
+/// AEAD authentication tag
+pub type Tag = [u8; HPKE_TAGBYTES];
+
+const MODE_BASE: u8 = 0x00;
+const MODE_PSK: u8 = 0x01;
+const NONCEBYTES: usize = 12;
+const MAX_KEYBYTES: usize = 32;
+
+/// The AEAD used to encrypt the message
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Aead {
+    /// AES-128-GCM, AEAD identifier 0x0001
+    Aes128Gcm,
+    /// ChaCha20-Poly1305, AEAD identifier 0x0003
+    ChaCha20Poly1305,
+}
+
+impl Aead {
+    /// The HPKE AEAD identifier
+    pub const fn id(self) -> u16 {
+        match self {
+            Aead::Aes128Gcm => 0x0001,
+            Aead::ChaCha20Poly1305 => 0x0003,
+        }
+    }
+
+    const fn key_bytes(self) -> usize {
+        match self {
+            Aead::Aes128Gcm => 16,
+            Aead::ChaCha20Poly1305 => 32,
+        }
+    }
+}
+
+/// An HPKE ciphersuite and mode, used to seal messages to a public key and
+/// open them with the matching secret key.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # use pqc_kyber::hpke::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let psk = [7u8; 32];
+/// let hpke = Hpke::psk(Aead::Aes128Gcm, &psk, b"psk id")?;
+///
+/// let mut msg = *b"Hello Bob";
+/// let (enc, tag) = hpke.seal(&keys.public, b"", b"", &mut msg, &mut rng)?;
+/// hpke.open(&enc, &keys.secret, b"", b"", &mut msg, &tag)?;
+/// assert_eq!(&msg, b"Hello Bob");
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Copy)]
+pub struct Hpke<'a> {
+    aead: Aead,
+    psk: Option<(&'a [u8], &'a [u8])>,
+}
+
+// The pre-shared key is not printed
+impl fmt::Debug for Hpke<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hpke")
+            .field("aead", &self.aead)
+            .field("psk", &self.psk.is_some())
+            .finish()
+    }
+}
+
+impl<'a> Hpke<'a> {
+    /// Base mode, the sender is not authenticated
+    pub fn base(aead: Aead) -> Self {
+        Hpke { aead, psk: None }
+    }
+
+    /// PSK mode, both parties also hold a pre-shared key identified by
+    /// `psk_id`.
+    ///
+    /// Returns [`KyberError::InvalidInput`] if the key is shorter than
+    /// [`HPKE_MIN_PSKBYTES`] or the identifier is empty.
+    pub fn psk(aead: Aead, psk: &'a [u8], psk_id: &'a [u8]) -> Result<Self, KyberError> {
+        if psk.len() < HPKE_MIN_PSKBYTES || psk_id.is_empty() {
+            return Err(KyberError::InvalidInput);
+        }
+        Ok(Hpke {
+            aead,
+            psk: Some((psk, psk_id)),
+        })
+    }
+
+    /// Encrypts `buffer` in place to a public key, returning the
+    /// encapsulated key and the authentication tag to send along with it.
+    ///
+    /// Public keys encoding coefficients that are not reduced modulo q are
+    /// rejected with [`KyberError::InvalidPublicKey`].
+    pub fn seal<R>(
+        &self,
+        pk: &PublicKey,
+        info: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+        rng: &mut R,
+    ) -> Result<(Ciphertext, Tag), KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let (enc, ss) = encapsulate(pk, rng)?;
+        #[allow(unused_mut)]
+        let (mut key, nonce) = self.key_schedule(&ss, info)?;
+        let mut tag = [0u8; HPKE_TAGBYTES];
+        let res = match self.aead {
+            Aead::Aes128Gcm => Aes128Gcm::new_from_slice(&key[..16])
+                .map(|c| c.encrypt_in_place_detached(&nonce.into(), aad, buffer)),
+            Aead::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(&key)
+                .map(|c| c.encrypt_in_place_detached(&nonce.into(), aad, buffer)),
+        };
+        #[cfg(feature = "zeroize")]
+        key.zeroize();
+        let res = res.map_err(|_| KyberError::InvalidInput)?;
+        tag.copy_from_slice(&res.map_err(|_| KyberError::InvalidInput)?);
+        Ok((enc, tag))
+    }
+
+    /// Decrypts `buffer` in place with a secret key.
+    ///
+    /// Returns [`KyberError::InvalidHpkeMessage`] if the tag does not
+    /// authenticate the message, the buffer is then left unchanged.
+    pub fn open(
+        &self,
+        enc: &Ciphertext,
+        sk: &SecretKey,
+        info: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), KyberError> {
+        let ss = decapsulate(enc, sk)?;
+        #[allow(unused_mut)]
+        let (mut key, nonce) = self.key_schedule(&ss, info)?;
+        let res = match self.aead {
+            Aead::Aes128Gcm => Aes128Gcm::new_from_slice(&key[..16])
+                .map(|c| c.decrypt_in_place_detached(&nonce.into(), aad, buffer, tag.into())),
+            Aead::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(&key)
+                .map(|c| c.decrypt_in_place_detached(&nonce.into(), aad, buffer, tag.into())),
+        };
+        #[cfg(feature = "zeroize")]
+        key.zeroize();
+        res.map_err(|_| KyberError::InvalidInput)?
+            .map_err(|_| KyberError::InvalidHpkeMessage)
+    }
+
+    // KeySchedule of RFC 9180 section 5.1, only the key and base nonce are
+    // needed for a single message. The AES key is the first 16 bytes.
+    fn key_schedule(
+        &self,
+        ss: &SharedSecret,
+        info: &[u8],
+    ) -> Result<([u8; MAX_KEYBYTES], [u8; NONCEBYTES]), KyberError> {
+        let suite_id = self.suite_id();
+        let (mode, psk, psk_id) = match self.psk {
+            Some((psk, psk_id)) => (MODE_PSK, psk, psk_id),
+            None => (MODE_BASE, &[][..], &[][..]),
+        };
+
+        let mut context = [0u8; 1 + 2 * 32];
+        context[0] = mode;
+        let mut psk_id_hash = [0u8; 32];
+        labeled_extract(&suite_id, &[], b"psk_id_hash", psk_id, &mut psk_id_hash);
+        context[1..33].copy_from_slice(&psk_id_hash);
+        let mut info_hash = [0u8; 32];
+        labeled_extract(&suite_id, &[], b"info_hash", info, &mut info_hash);
+        context[33..].copy_from_slice(&info_hash);
+
+        let mut prk = [0u8; 32];
+        let secret = labeled_extract(&suite_id, ss.as_ref(), b"secret", psk, &mut prk);
+        let mut key = [0u8; MAX_KEYBYTES];
+        let mut nonce = [0u8; NONCEBYTES];
+        let nk = self.aead.key_bytes();
+        labeled_expand(&secret, &suite_id, b"key", &context, &mut key[..nk])?;
+        labeled_expand(&secret, &suite_id, b"base_nonce", &context, &mut nonce)?;
+        #[cfg(feature = "zeroize")]
+        prk.zeroize();
+        Ok((key, nonce))
+    }
+
+    // "HPKE" || I2OSP(kem_id, 2) || I2OSP(kdf_id, 2) || I2OSP(aead_id, 2)
+    fn suite_id(&self) -> [u8; 10] {
+        let mut suite_id = [0u8; 10];
+        suite_id[..4].copy_from_slice(b"HPKE");
+        suite_id[4..6].copy_from_slice(&KEM_ID.to_be_bytes());
+        suite_id[6..8].copy_from_slice(&KDF_ID.to_be_bytes());
+        suite_id[8..].copy_from_slice(&self.aead.id().to_be_bytes());
+        suite_id
+    }
+}
+
+/// Deterministically derives a keypair from input keying material, the
+/// `DeriveKeyPair` function of RFC 9180.
+///
+/// A 64 byte seed is expanded from `ikm` with HKDF-SHA256 and passed to
+/// [`derive`](crate::derive). Returns [`KyberError::InvalidInput`] if `ikm`
+/// is shorter than 32 bytes.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let keys = hpke::derive_keypair(&[42u8; 32])?;
+/// assert_eq!(keys, hpke::derive_keypair(&[42u8; 32])?);
+/// # Ok(()) }
+/// ```
+pub fn derive_keypair(ikm: &[u8]) -> Result<Keypair, KyberError> {
+    if ikm.len() < KYBER_SYMBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    // "KEM" || I2OSP(kem_id, 2)
+    let mut suite_id = [0u8; 5];
+    suite_id[..3].copy_from_slice(b"KEM");
+    suite_id[3..].copy_from_slice(&KEM_ID.to_be_bytes());
+
+    let mut prk = [0u8; 32];
+    let dkp_prk = labeled_extract(&suite_id, &[], b"dkp_prk", ikm, &mut prk);
+    let mut seed = [0u8; 2 * KYBER_SYMBYTES];
+    labeled_expand(&dkp_prk, &suite_id, b"sk", &[], &mut seed)?;
+    let keys = derive(&seed);
+    #[cfg(feature = "zeroize")]
+    {
+        prk.zeroize();
+        seed.zeroize();
+    }
+    keys
+}
+
+// LabeledExtract(salt, label, ikm), writes the PRK to `prk` and returns it
+// ready for expansion. The caller owns the bytes and wipes them if secret,
+// they are swapped out so no copy is left behind.
+fn labeled_extract(
+    suite_id: &[u8],
+    salt: &[u8],
+    label: &[u8],
+    ikm: &[u8],
+    prk: &mut [u8; 32],
+) -> Hkdf<Sha256> {
+    let mut extract = HkdfExtract::<Sha256>::new(Some(salt));
+    extract.input_ikm(b"HPKE-v1");
+    extract.input_ikm(suite_id);
+    extract.input_ikm(label);
+    extract.input_ikm(ikm);
+    let (mut out, hkdf) = extract.finalize();
+    prk.swap_with_slice(&mut out);
+    hkdf
+}
+
+// LabeledExpand(prk, label, info, L)
+fn labeled_expand(
+    prk: &Hkdf<Sha256>,
+    suite_id: &[u8],
+    label: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), KyberError> {
+    let len = (out.len() as u16).to_be_bytes();
+    prk.expand_multi_info(&[&len, b"HPKE-v1", suite_id, label, info], out)
+        .map_err(|_| KyberError::InvalidInput)
+}
@@ -1,6 +1,9 @@
+use crate::reference::poly::*;
 use crate::rng::randombytes;
-use crate::{error::KyberError, indcpa::*, params::*, symmetric::*, verify::*};
+use crate::{backend::*, error::KyberError, params::*, symmetric::*, verify::*};
 use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 /// Name:  crypto_kem_keypair
 ///
@@ -9,6 +12,7 @@ use rand_core::{CryptoRng, RngCore};
 ///
 /// Arguments:   - [u8] pk: output public key (an already allocated array of CRYPTO_PUBLICKEYBYTES bytes)
 ///  - [u8] sk: output private key (an already allocated array of CRYPTO_SECRETKEYBYTES bytes)
+#[cfg(any(kyber_kat, fuzzing, feature = "benchmarking"))]
 pub fn crypto_kem_keypair<R>(
     pk: &mut [u8],
     sk: &mut [u8],
@@ -18,19 +22,35 @@ pub fn crypto_kem_keypair<R>(
 where
     R: RngCore + CryptoRng,
 {
-    const PK_START: usize = KYBER_SECRETKEYBYTES - (2 * KYBER_SYMBYTES);
-    const SK_START: usize = KYBER_SECRETKEYBYTES - KYBER_SYMBYTES;
-    const END: usize = KYBER_INDCPA_PUBLICKEYBYTES + KYBER_INDCPA_SECRETKEYBYTES;
+    kem_keypair::<KYBER_K, R>(pk, sk, _rng, _seed)
+}
 
-    indcpa_keypair(pk, sk, _seed, _rng)?;
+/// Name:  kem_keypair
+///
+/// Description: crypto_kem_keypair for the security level K
+pub fn kem_keypair<const K: usize, R>(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    _rng: &mut R,
+    _seed: Option<(&[u8], &[u8])>,
+) -> Result<(), KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    let pk_start = Params::<K>::SECRETKEYBYTES - (2 * KYBER_SYMBYTES);
+    let sk_start = Params::<K>::SECRETKEYBYTES - KYBER_SYMBYTES;
+    let start = Params::<K>::INDCPA_SECRETKEYBYTES;
+    let end = Params::<K>::INDCPA_PUBLICKEYBYTES + Params::<K>::INDCPA_SECRETKEYBYTES;
 
-    sk[KYBER_INDCPA_SECRETKEYBYTES..END].copy_from_slice(&pk[..KYBER_INDCPA_PUBLICKEYBYTES]);
-    hash_h(&mut sk[PK_START..], pk, KYBER_PUBLICKEYBYTES);
+    indcpa_keypair::<K, R>(pk, sk, _seed, _rng)?;
+
+    sk[start..end].copy_from_slice(&pk[..Params::<K>::INDCPA_PUBLICKEYBYTES]);
+    hash_h(&mut sk[pk_start..], pk, Params::<K>::PUBLICKEYBYTES);
 
     if let Some(s) = _seed {
-        sk[SK_START..].copy_from_slice(&s.1)
+        sk[sk_start..].copy_from_slice(s.1)
     } else {
-        randombytes(&mut sk[SK_START..], KYBER_SYMBYTES, _rng)?;
+        randombytes(&mut sk[sk_start..], KYBER_SYMBYTES, _rng)?;
     }
     Ok(())
 }
@@ -43,6 +63,7 @@ where
 /// Arguments:   - [u8] ct:   output cipher text (an already allocated array of CRYPTO_CIPHERTEXTBYTES bytes)
 ///  - [u8] ss:   output shared secret (an already allocated array of CRYPTO_BYTES bytes)
 ///  - const [u8] pk: input public key (an already allocated array of CRYPTO_PUBLICKEYBYTES bytes)
+#[cfg(any(kyber_kat, fuzzing, feature = "benchmarking"))]
 pub fn crypto_kem_enc<R>(
     ct: &mut [u8],
     ss: &mut [u8],
@@ -50,35 +71,139 @@ pub fn crypto_kem_enc<R>(
     _rng: &mut R,
     _seed: Option<&[u8]>,
 ) -> Result<(), KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    kem_enc::<KYBER_K, R>(ct, ss, pk, _rng, _seed)
+}
+
+/// Name:  kem_enc
+///
+/// Description: crypto_kem_enc for the security level K
+pub fn kem_enc<const K: usize, R>(
+    ct: &mut [u8],
+    ss: &mut [u8],
+    pk: &[u8],
+    _rng: &mut R,
+    _seed: Option<&[u8]>,
+) -> Result<(), KyberError>
 where
     R: RngCore + CryptoRng,
 {
     let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+    kem_enc_prekey::<K, R>(ct, &mut kr, pk, _rng, _seed)?;
+
+    // hash concatenation of pre-k and H(c) to k
+    kdf(ss, &kr, 2 * KYBER_SYMBYTES);
+    #[cfg(feature = "zeroize")]
+    kr.zeroize();
+    Ok(())
+}
+
+/// Name:  kem_enc_prekey
+///
+/// Description: kem_enc up to the final KDF, outputs the pre-key and H(c)
+///
+/// Arguments:   - [u8] ct:   output cipher text (of length Params::<K>::CIPHERTEXTBYTES)
+///  - [u8] kr:   output pre-key followed by H(c) (of length 2*KYBER_SYMBYTES)
+///  - const [u8] pk: input public key (of length Params::<K>::PUBLICKEYBYTES)
+pub fn kem_enc_prekey<const K: usize, R>(
+    ct: &mut [u8],
+    kr: &mut [u8],
+    pk: &[u8],
+    _rng: &mut R,
+    _seed: Option<&[u8]>,
+) -> Result<(), KyberError>
+where
+    R: RngCore + CryptoRng,
+{
     let mut buf = [0u8; 2 * KYBER_SYMBYTES];
     let mut randbuf = [0u8; 2 * KYBER_SYMBYTES];
 
     // Deterministic randbuf for KAT's
     if let Some(s) = _seed {
-        randbuf[..KYBER_SYMBYTES].copy_from_slice(&s);
+        randbuf[..KYBER_SYMBYTES].copy_from_slice(s);
     } else {
         randombytes(&mut randbuf, KYBER_SYMBYTES, _rng)?;
     }
 
     // Don't release system RNG output
     hash_h(&mut buf, &randbuf, KYBER_SYMBYTES);
+    #[cfg(feature = "zeroize")]
+    randbuf.zeroize();
+
+    // Multitarget countermeasure for coins + contributory KEM
+    hash_h(&mut buf[KYBER_SYMBYTES..], pk, Params::<K>::PUBLICKEYBYTES);
+    hash_g(kr, &buf, 2 * KYBER_SYMBYTES);
+
+    // coins are in kr[KYBER_SYMBYTES..]
+    indcpa_enc::<K>(ct, &buf, pk, &kr[KYBER_SYMBYTES..]);
+
+    // overwrite coins in kr with H(c)
+    hash_h(&mut kr[KYBER_SYMBYTES..], ct, Params::<K>::CIPHERTEXTBYTES);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
+    Ok(())
+}
+
+/// Name:  kem_expand_pk
+///
+/// Description: Precomputes the public key dependent part of kem_enc
+///
+/// Arguments:   - ExpandedPk exp: output expanded public key
+///  - [u8] hpk: output hash of the public key (of length KYBER_SYMBYTES)
+///  - const [u8] pk: input public key (of length Params::<K>::PUBLICKEYBYTES)
+pub fn kem_expand_pk<const K: usize>(exp: &mut ExpandedPk<K>, hpk: &mut [u8], pk: &[u8]) {
+    indcpa_expand_pk::<K>(exp, pk);
+    hash_h(hpk, pk, Params::<K>::PUBLICKEYBYTES);
+}
+
+/// Name:  kem_enc_expanded
+///
+/// Description: kem_enc with a public key expanded by kem_expand_pk
+///
+/// Arguments:   - [u8] ct:   output cipher text (of length Params::<K>::CIPHERTEXTBYTES)
+///  - [u8] ss:   output shared secret (of length KYBER_SSBYTES)
+///  - const ExpandedPk exp: input expanded public key
+///  - const [u8] hpk: input hash of the public key (of length KYBER_SYMBYTES)
+pub fn kem_enc_expanded<const K: usize, R>(
+    ct: &mut [u8],
+    ss: &mut [u8],
+    exp: &ExpandedPk<K>,
+    hpk: &[u8],
+    _rng: &mut R,
+) -> Result<(), KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+    let mut randbuf = [0u8; 2 * KYBER_SYMBYTES];
+
+    randombytes(&mut randbuf, KYBER_SYMBYTES, _rng)?;
+
+    // Don't release system RNG output
+    hash_h(&mut buf, &randbuf, KYBER_SYMBYTES);
+    #[cfg(feature = "zeroize")]
+    randbuf.zeroize();
 
     // Multitarget countermeasure for coins + contributory KEM
-    hash_h(&mut buf[KYBER_SYMBYTES..], pk, KYBER_PUBLICKEYBYTES);
+    buf[KYBER_SYMBYTES..].copy_from_slice(&hpk[..KYBER_SYMBYTES]);
     hash_g(&mut kr, &buf, 2 * KYBER_SYMBYTES);
 
     // coins are in kr[KYBER_SYMBYTES..]
-    indcpa_enc(ct, &buf, pk, &kr[KYBER_SYMBYTES..]);
+    indcpa_enc_expanded::<K>(ct, &buf, exp, &kr[KYBER_SYMBYTES..]);
 
     // overwrite coins in kr with H(c)
-    hash_h(&mut kr[KYBER_SYMBYTES..], ct, KYBER_CIPHERTEXTBYTES);
+    hash_h(&mut kr[KYBER_SYMBYTES..], ct, Params::<K>::CIPHERTEXTBYTES);
 
     // hash concatenation of pre-k and H(c) to k
     kdf(ss, &kr, 2 * KYBER_SYMBYTES);
+    #[cfg(feature = "zeroize")]
+    {
+        kr.zeroize();
+        buf.zeroize();
+    }
     Ok(())
 }
 
@@ -92,29 +217,169 @@ where
 ///  - const [u8] sk: input private key (an already allocated array of CRYPTO_SECRETKEYBYTES bytes)
 ///
 /// On failure, ss will contain a pseudo-random value.
-pub fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> () {
-    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+#[cfg(any(kyber_kat, fuzzing, feature = "benchmarking"))]
+pub fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) {
+    kem_dec::<KYBER_K>(ss, ct, sk);
+}
+
+/// Name:  kem_dec
+///
+/// Description: crypto_kem_dec for the security level K
+///
+/// Returns 0 if the ciphertext passed the re-encryption check, 1 otherwise
+pub fn kem_dec<const K: usize>(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> u8 {
     let mut kr = [0u8; 2 * KYBER_SYMBYTES];
-    let mut cmp = [0u8; KYBER_CIPHERTEXTBYTES];
-    let mut pk = [0u8; KYBER_INDCPA_PUBLICKEYBYTES];
+    let fail = kem_dec_prekey::<K>(&mut kr, ct, sk);
+    // hash concatenation of pre-k and H(c) to k
+    kdf(ss, &kr, 2 * KYBER_SYMBYTES);
+    #[cfg(feature = "zeroize")]
+    kr.zeroize();
+    fail
+}
 
-    pk.copy_from_slice(&sk[KYBER_INDCPA_SECRETKEYBYTES..][..KYBER_INDCPA_PUBLICKEYBYTES]);
+/// Name:  kem_dec_prekey
+///
+/// Description: kem_dec up to the final KDF, outputs the pre-key, or z on
+///  re-encryption failure, and H(c)
+///
+/// Arguments:   - [u8] kr:   output pre-key followed by H(c) (of length 2*KYBER_SYMBYTES)
+///  - const [u8] ct: input cipher text (of length Params::<K>::CIPHERTEXTBYTES)
+///  - const [u8] sk: input private key (of length Params::<K>::SECRETKEYBYTES)
+///
+/// Returns 0 if the ciphertext passed the re-encryption check, 1 otherwise
+pub fn kem_dec_prekey<const K: usize>(kr: &mut [u8], ct: &[u8], sk: &[u8]) -> u8 {
+    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+    let mut cmp = [0u8; KYBER_CIPHERTEXTBYTES_MAX];
+    let cmp = &mut cmp[..Params::<K>::CIPHERTEXTBYTES];
+    let pk = &sk[Params::<K>::INDCPA_SECRETKEYBYTES..][..Params::<K>::INDCPA_PUBLICKEYBYTES];
 
-    indcpa_dec(&mut buf, ct, sk);
+    indcpa_dec::<K>(&mut buf, ct, sk);
 
     // Multitarget countermeasure for coins + contributory KEM
-    const START: usize = KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
-    const END: usize = KYBER_SECRETKEYBYTES - KYBER_SYMBYTES;
-    buf[KYBER_SYMBYTES..].copy_from_slice(&sk[START..END]);
+    let start = Params::<K>::SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    let end = Params::<K>::SECRETKEYBYTES - KYBER_SYMBYTES;
+    buf[KYBER_SYMBYTES..].copy_from_slice(&sk[start..end]);
+    hash_g(kr, &buf, 2 * KYBER_SYMBYTES);
+
+    // coins are in kr[KYBER_SYMBYTES..]
+    indcpa_enc::<K>(cmp, &buf, pk, &kr[KYBER_SYMBYTES..]);
+    let fail = verify(ct, cmp, Params::<K>::CIPHERTEXTBYTES);
+    // overwrite coins in kr with H(c)
+    hash_h(&mut kr[KYBER_SYMBYTES..], ct, Params::<K>::CIPHERTEXTBYTES);
+    // Overwrite pre-k with z on re-encryption failure
+    cmov(kr, &sk[end..], KYBER_SYMBYTES, fail);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        cmp.zeroize();
+    }
+    fail
+}
+
+/// Name:  kem_expand_sk
+///
+/// Description: Precomputes the secret key dependent part of kem_dec,
+///  including the expansion of the embedded public key
+///
+/// Arguments:   - ExpandedPk pk_exp: output expanded public key
+///  - ExpandedSk sk_exp: output expanded secret key
+///  - const [u8] sk: input private key (of length Params::<K>::SECRETKEYBYTES)
+pub fn kem_expand_sk<const K: usize>(
+    pk_exp: &mut ExpandedPk<K>,
+    sk_exp: &mut ExpandedSk<K>,
+    sk: &[u8],
+) {
+    let pk = &sk[Params::<K>::INDCPA_SECRETKEYBYTES..][..Params::<K>::INDCPA_PUBLICKEYBYTES];
+    indcpa_expand_pk::<K>(pk_exp, pk);
+    indcpa_expand_sk::<K>(sk_exp, sk);
+}
+
+/// Name:  kem_dec_expanded
+///
+/// Description: kem_dec with a secret key expanded by kem_expand_sk
+///
+/// Arguments:   - [u8] ss:   output shared secret (of length KYBER_SSBYTES)
+///  - const [u8] ct: input cipher text (of length Params::<K>::CIPHERTEXTBYTES)
+///  - const ExpandedPk pk_exp: input expanded public key
+///  - const ExpandedSk sk_exp: input expanded secret key
+///  - const [u8] sk: input private key, for H(pk) and z (of length Params::<K>::SECRETKEYBYTES)
+///
+/// On failure, ss will contain a pseudo-random value.
+pub fn kem_dec_expanded<const K: usize>(
+    ss: &mut [u8],
+    ct: &[u8],
+    pk_exp: &ExpandedPk<K>,
+    sk_exp: &ExpandedSk<K>,
+    sk: &[u8],
+) {
+    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+    let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+    let mut cmp = [0u8; KYBER_CIPHERTEXTBYTES_MAX];
+    let cmp = &mut cmp[..Params::<K>::CIPHERTEXTBYTES];
+
+    indcpa_dec_expanded::<K>(&mut buf, ct, sk_exp);
+
+    // Multitarget countermeasure for coins + contributory KEM
+    let start = Params::<K>::SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    let end = Params::<K>::SECRETKEYBYTES - KYBER_SYMBYTES;
+    buf[KYBER_SYMBYTES..].copy_from_slice(&sk[start..end]);
     hash_g(&mut kr, &buf, 2 * KYBER_SYMBYTES);
 
     // coins are in kr[KYBER_SYMBYTES..]
-    indcpa_enc(&mut cmp, &buf, &pk, &kr[KYBER_SYMBYTES..]);
-    let fail = verify(ct, &cmp, KYBER_CIPHERTEXTBYTES);
+    indcpa_enc_expanded::<K>(cmp, &buf, pk_exp, &kr[KYBER_SYMBYTES..]);
+    let fail = verify(ct, cmp, Params::<K>::CIPHERTEXTBYTES);
     // overwrite coins in kr with H(c)
-    hash_h(&mut kr[KYBER_SYMBYTES..], ct, KYBER_CIPHERTEXTBYTES);
+    hash_h(&mut kr[KYBER_SYMBYTES..], ct, Params::<K>::CIPHERTEXTBYTES);
     // Overwrite pre-k with z on re-encryption failure
-    cmov(&mut kr, &sk[END..], KYBER_SYMBYTES, fail);
+    cmov(&mut kr, &sk[end..], KYBER_SYMBYTES, fail);
     // hash concatenation of pre-k and H(c) to k
     kdf(ss, &kr, 2 * KYBER_SYMBYTES);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        kr.zeroize();
+        cmp.zeroize();
+    }
+}
+
+/// Name:  kem_check_pk
+///
+/// Description: FIPS 203 encapsulation key modulus check, the encoded vector
+///  of polynomials must be unchanged by a ByteDecode/ByteEncode round trip,
+///  i.e. every coefficient is smaller than q
+///
+/// Arguments:   - const [u8] pk: input public key (of length Params::<K>::PUBLICKEYBYTES)
+pub fn kem_check_pk<const K: usize>(pk: &[u8]) -> Result<(), KyberError> {
+    let mut poly = Poly::new();
+    let mut buf = [0u8; KYBER_POLYBYTES];
+
+    // One polynomial at a time
+    for packed in pk[..Params::<K>::POLYVECBYTES].chunks_exact(KYBER_POLYBYTES) {
+        poly_frombytes(&mut poly, packed);
+        poly_reduce(&mut poly);
+        poly_tobytes(&mut buf, &poly);
+        if buf[..] != packed[..] {
+            return Err(KyberError::InvalidPublicKey);
+        }
+    }
+    Ok(())
+}
+
+/// Name:  kem_check_sk
+///
+/// Description: FIPS 203 decapsulation key hash check, the hash stored in
+///  the secret key must match the hash of the embedded public key
+///
+/// Arguments:   - const [u8] sk: input secret key (of length Params::<K>::SECRETKEYBYTES)
+pub fn kem_check_sk<const K: usize>(sk: &[u8]) -> Result<(), KyberError> {
+    let start = Params::<K>::INDCPA_SECRETKEYBYTES;
+    let end = Params::<K>::SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    let mut h = [0u8; KYBER_SYMBYTES];
+
+    hash_h(&mut h, &sk[start..end], Params::<K>::PUBLICKEYBYTES);
+
+    if verify(&h, &sk[end..end + KYBER_SYMBYTES], KYBER_SYMBYTES) != 0 {
+        return Err(KyberError::InvalidSecretKey);
+    }
+    Ok(())
 }
@@ -0,0 +1,29 @@
+//! The RustCrypto [`kem`](https://docs.rs/kem) traits.
+//!
+//! [`PublicKey`] implements `Encapsulate` and [`SecretKey`] implements
+//! `Decapsulate`, with the [`Ciphertext`] as the encapsulated key, so Kyber
+//! can be used by code generic over other KEMs. Both use the round 3 Kyber
+//! transform of [`encapsulate`](crate::encapsulate) and
+//! [`decapsulate`](crate::decapsulate).
+use crate::{params::KyberParams, Ciphertext, KyberError, PublicKey, SecretKey, SharedSecret};
+use ::kem::{Decapsulate, Encapsulate};
+use rand_core::CryptoRngCore;
+
+impl<P: KyberParams> Encapsulate<Ciphertext<P>, SharedSecret> for PublicKey<P> {
+    type Error = KyberError;
+
+    fn encapsulate(
+        &self,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Ciphertext<P>, SharedSecret), KyberError> {
+        P::encapsulate(self, rng)
+    }
+}
+
+impl<P: KyberParams> Decapsulate<Ciphertext<P>, SharedSecret> for SecretKey<P> {
+    type Error = KyberError;
+
+    fn decapsulate(&self, ct: &Ciphertext<P>) -> Result<SharedSecret, KyberError> {
+        P::decapsulate(ct, self)
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{kem::*, params::*, symmetric::kdf, KyberError};
+use crate::{
+    params::*,
+    symmetric::{hash_h, hash_transcript, kdf, kex_prf},
+    types::ct_eq,
+    Ciphertext, KyberError, PresharedKey, PublicKey, SecretKey, SharedSecret,
+};
 use rand_core::{CryptoRng, RngCore};
 #[cfg(feature = "zeroize")]
 use zeroize::{Zeroize, ZeroizeOnDrop};
@@ -11,17 +16,13 @@ pub const UAKE_RESPONSE_BYTES: usize = KYBER_CIPHERTEXTBYTES;
 pub const AKE_INIT_BYTES: usize = KYBER_PUBLICKEYBYTES + KYBER_CIPHERTEXTBYTES;
 /// Mutual Key Exchange Response Byte Length
 pub const AKE_RESPONSE_BYTES: usize = 2 * KYBER_CIPHERTEXTBYTES;
+/// Key Confirmation Tag Byte Length
+pub const CONFIRM_TAG_BYTES: usize = KYBER_SYMBYTES;
 
 /// Result of encapsulating a public key which includes the ciphertext and shared secret
-pub type Encapsulated = Result<([u8; KYBER_CIPHERTEXTBYTES], [u8; KYBER_SSBYTES]), KyberError>;
+pub type Encapsulated = Result<(Ciphertext, SharedSecret), KyberError>;
 /// Decapsulated ciphertext
-pub type Decapsulated = Result<[u8; KYBER_SSBYTES], KyberError>;
-/// Kyber public key
-pub type PublicKey = [u8; KYBER_PUBLICKEYBYTES];
-/// Kyber secret key
-pub type SecretKey = [u8; KYBER_SECRETKEYBYTES];
-/// Kyber Shared Secret
-pub type SharedSecret = [u8; KYBER_SSBYTES];
+pub type Decapsulated = Result<SharedSecret, KyberError>;
 /// Bytes to send when initiating a unilateral key exchange
 pub type UakeSendInit = [u8; UAKE_INIT_BYTES];
 /// Bytes to send when responding to a unilateral key exchange
@@ -30,10 +31,41 @@ pub type UakeSendResponse = [u8; UAKE_RESPONSE_BYTES];
 pub type AkeSendInit = [u8; AKE_INIT_BYTES];
 /// Bytes to send when responding to a mutual key exchange
 pub type AkeSendResponse = [u8; AKE_RESPONSE_BYTES];
+/// Tag confirming the shared secret of a key exchange to the other party
+pub type ConfirmTag = [u8; CONFIRM_TAG_BYTES];
 
-// Ephemeral keys
-type TempKey = [u8; KYBER_SSBYTES];
-type Eska = [u8; KYBER_SECRETKEYBYTES];
+// Domain separation of the V2 transcript hashes
+pub(crate) const UAKE_V2_LABEL: &[u8] = b"pqc_kyber uake v2";
+pub(crate) const AKE_V2_LABEL: &[u8] = b"pqc_kyber ake v2";
+// Domain separation of the confirmation tags of each party
+pub(crate) const SERVER_CONFIRM_LABEL: &[u8] = b"pqc_kyber server confirm";
+pub(crate) const CLIENT_CONFIRM_LABEL: &[u8] = b"pqc_kyber client confirm";
+// Domain separation of the confirmation key from the session key
+const CONFIRM_KEY_LABEL: &[u8] = b"pqc_kyber confirm key";
+// Domain separation of the early key from the session key
+const EARLY_KEY_LABEL: &[u8] = b"pqc_kyber uake early key";
+
+/// Session key derivation of the [`Uake`] and [`Ake`] exchanges, both
+/// parties have to use the same version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KexVersion {
+    /// The session key is a KDF over the KEM shared secrets only. Kept for
+    /// compatibility with peers running earlier releases.
+    V1,
+    /// The KEM shared secrets are combined with a hash of the transcript:
+    /// both exchanged messages and the hashes of the static public keys.
+    /// A message substituted in transit or an exchange relayed to another
+    /// party ends in mismatched keys.
+    V2,
+}
+
+// #[default] on a variant needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for KexVersion {
+    fn default() -> Self {
+        KexVersion::V1
+    }
+}
 
 /// Used for unilaterally authenticated key exchange between two parties.
 ///
@@ -52,33 +84,98 @@ type Eska = [u8; KYBER_SECRETKEYBYTES];
 ///
 /// assert_eq!(alice.shared_secret, bob.shared_secret);
 /// # Ok(()) }
-
-#[cfg_attr(feature = "zeroize", derive(Zeroize, ZeroizeOnDrop))]
+/// ```
+///
+/// Other security levels are selected with the type parameter:
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(),KyberError> {
+/// # let mut rng = rand::thread_rng();
+/// let mut alice = Uake::<Kyber1024>::default();
+/// let mut bob = Uake::<Kyber1024>::default();
+/// let bob_keys = Kyber1024::keypair(&mut rng)?;
+/// # let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+/// # let server_send = bob.server_receive(client_init, &bob_keys.secret, &mut rng)?;
+/// # alice.client_confirm(server_send)?;
+/// # assert_eq!(alice.shared_secret, bob.shared_secret);
+/// # Ok(()) }
+/// ```
+///
+/// The session key is derived with [`KexVersion::V1`] unless another
+/// version is selected, [`KexVersion::V2`] also binds it to the transcript
+/// and the server's public key:
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(),KyberError> {
+/// # let mut rng = rand::thread_rng();
+/// let mut alice: Uake = Uake::with_version(KexVersion::V2);
+/// let mut bob: Uake = Uake::with_version(KexVersion::V2);
+/// # let bob_keys = keypair(&mut rng)?;
+/// # let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+/// # let server_send = bob.server_receive(client_init, &bob_keys.secret, &mut rng)?;
+/// # alice.client_confirm(server_send)?;
+/// # assert_eq!(alice.shared_secret, bob.shared_secret);
+/// # Ok(()) }
+/// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Uake {
+pub struct Uake<P: KyberParams = DefaultParams> {
     /// The resulting shared secret from a key exchange
     pub shared_secret: SharedSecret,
     /// Sent when initiating a key exchange
-    send_a: UakeSendInit,
+    send_a: P::UakeSendInit,
     /// Response to a key exchange initiation
-    send_b: UakeSendResponse,
+    send_b: P::UakeSendResponse,
     // Ephemeral keys
-    temp_key: TempKey,
-    eska: Eska,
+    temp_key: SharedSecret,
+    eska: SecretKey<P>,
+    // Hash of the server's static public key, kept by the client for V2
+    pk_hash: [u8; KYBER_SYMBYTES],
+    version: KexVersion,
+    psk: Option<PresharedKey>,
+    psk_id: [u8; KYBER_SYMBYTES],
+    // Derived from the temporary key, before the exchange completes
+    early_key: Option<SharedSecret>,
+    // Keys the confirmation tags, derived next to the shared secret
+    confirm_key: SharedSecret,
+    // Set once this party derived the shared secret
+    completed: bool,
 }
 
-impl Default for Uake {
+impl<P: KyberParams> Default for Uake<P> {
     fn default() -> Self {
-        Uake {
-            shared_secret: [0u8; KYBER_SSBYTES],
-            send_a: [0u8; UAKE_INIT_BYTES],
-            send_b: [0u8; UAKE_RESPONSE_BYTES],
-            temp_key: [0u8; KYBER_SSBYTES],
-            eska: [0u8; KYBER_SECRETKEYBYTES],
-        }
+        Self::with_version(KexVersion::default())
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Zeroize for Uake<P> {
+    fn zeroize(&mut self) {
+        self.shared_secret.zeroize();
+        self.send_a.zeroize();
+        self.send_b.zeroize();
+        self.temp_key.zeroize();
+        self.eska.zeroize();
+        self.pk_hash.zeroize();
+        self.psk.zeroize();
+        self.psk_id.zeroize();
+        self.early_key.zeroize();
+        self.confirm_key.zeroize();
+        self.completed = false;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Drop for Uake<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> ZeroizeOnDrop for Uake<P> {}
+
 impl Uake {
     /// Builds new UAKE struct
     /// ```
@@ -88,8 +185,53 @@ impl Uake {
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<P: KyberParams> Uake<P> {
+    /// Builds a UAKE struct deriving the session key with the given version
+    /// ```
+    /// # use pqc_kyber::*;
+    /// let mut kex: Uake = Uake::with_version(KexVersion::V2);
+    /// ```
+    pub fn with_version(version: KexVersion) -> Self {
+        Uake {
+            shared_secret: SharedSecret::zeroed(),
+            send_a: P::UakeSendInit::zeroed(),
+            send_b: P::UakeSendResponse::zeroed(),
+            temp_key: SharedSecret::zeroed(),
+            eska: SecretKey::zeroed(),
+            pk_hash: [0u8; KYBER_SYMBYTES],
+            version,
+            psk: None,
+            psk_id: [0u8; KYBER_SYMBYTES],
+            early_key: None,
+            confirm_key: SharedSecret::zeroed(),
+            completed: false,
+        }
+    }
+
+    /// Builds a UAKE struct mixing a pre-shared key into the session key, in
+    /// addition to the key exchange. Both parties need the same key and
+    /// identifier, the identifier may be empty.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # use core::convert::TryFrom;
+    /// # fn main() -> Result<(),KyberError> {
+    /// let psk = PresharedKey::try_from(&[7u8; 32][..])?;
+    /// let mut kex: Uake = Uake::with_psk(KexVersion::V2, psk, b"psk 1");
+    /// # Ok(()) }
+    /// ```
+    pub fn with_psk(version: KexVersion, psk: PresharedKey, psk_id: &[u8]) -> Self {
+        let mut kex = Self::with_version(version);
+        hash_h(&mut kex.psk_id, psk_id, psk_id.len());
+        kex.psk = Some(psk);
+        kex
+    }
 
     /// Initiates a Unilaterally Authenticated Key Exchange.
+    ///
+    /// Public keys encoding coefficients that are not reduced modulo q are
+    /// rejected with [`KyberError::InvalidPublicKey`].
     /// ```
     /// # use pqc_kyber::*;
     /// # fn main() -> Result<(),KyberError> {
@@ -101,23 +243,38 @@ impl Uake {
     /// ```
     pub fn client_init<R>(
         &mut self,
-        pubkey: &PublicKey,
+        pubkey: &PublicKey<P>,
         rng: &mut R,
-    ) -> Result<UakeSendInit, KyberError>
+    ) -> Result<P::UakeSendInit, KyberError>
     where
         R: CryptoRng + RngCore,
     {
-        uake_init_a(
-            &mut self.send_a,
-            &mut self.temp_key,
-            &mut self.eska,
-            pubkey,
+        self.completed = false;
+        self.early_key = None;
+        uake_init_a::<P, R>(
+            self.send_a.as_mut(),
+            self.temp_key.as_mut(),
+            self.eska.as_mut(),
+            pubkey.as_ref(),
             rng,
         )?;
+        if self.version == KexVersion::V2 {
+            hash_h(&mut self.pk_hash, pubkey.as_ref(), P::PUBLICKEYBYTES);
+        }
+        early_key_for(
+            self.early_key
+                .get_or_insert_with(SharedSecret::zeroed)
+                .as_mut(),
+            self.temp_key.as_ref(),
+            KdfParams::new(self.version, &self.psk, &self.psk_id),
+        );
         Ok(self.send_a)
     }
 
     /// Handles the output of a `client_init()` request
+    ///
+    /// Public keys encoding coefficients that are not reduced modulo q are
+    /// rejected with [`KyberError::InvalidPublicKey`].
     /// ```
     /// # use pqc_kyber::*;
     /// # fn main() -> Result<(),KyberError> {
@@ -130,20 +287,33 @@ impl Uake {
     /// # Ok(()) }
     pub fn server_receive<R>(
         &mut self,
-        send_a: UakeSendInit,
-        secretkey: &SecretKey,
+        send_a: P::UakeSendInit,
+        secretkey: &SecretKey<P>,
         rng: &mut R,
-    ) -> Result<UakeSendResponse, KyberError>
+    ) -> Result<P::UakeSendResponse, KyberError>
     where
         R: CryptoRng + RngCore,
     {
-        uake_shared_b(
-            &mut self.send_b,
-            &mut self.shared_secret,
-            &send_a,
-            secretkey,
+        self.completed = false;
+        self.early_key = None;
+        let early_key = self.early_key.get_or_insert_with(SharedSecret::zeroed);
+        let res = uake_shared_b::<P, R>(
+            self.send_b.as_mut(),
+            KexKeys {
+                k: self.shared_secret.as_mut(),
+                ck: self.confirm_key.as_mut(),
+            },
+            send_a.as_ref(),
+            secretkey.as_ref(),
+            KdfParams::new(self.version, &self.psk, &self.psk_id),
+            Some(early_key.as_mut()),
             rng,
-        )?;
+        );
+        if res.is_err() {
+            self.early_key = None;
+        }
+        res?;
+        self.completed = true;
         Ok(self.send_b)
     }
 
@@ -161,10 +331,132 @@ impl Uake {
     /// let client_confirm = alice.client_confirm(server_send)?;
     /// assert_eq!(alice.shared_secret, bob.shared_secret);
     /// # Ok(()) }
-    pub fn client_confirm(&mut self, send_b: UakeSendResponse) -> Result<(), KyberError> {
-        uake_shared_a(&mut self.shared_secret, &send_b, &self.temp_key, &self.eska)?;
+    pub fn client_confirm(&mut self, send_b: P::UakeSendResponse) -> Result<(), KyberError> {
+        let transcript = [
+            UAKE_V2_LABEL,
+            &self.pk_hash,
+            self.send_a.as_ref(),
+            send_b.as_ref(),
+        ];
+        uake_shared_a::<P>(
+            KexKeys {
+                k: self.shared_secret.as_mut(),
+                ck: self.confirm_key.as_mut(),
+            },
+            send_b.as_ref(),
+            self.temp_key.as_ref(),
+            self.eska.as_ref(),
+            KdfParams::new(self.version, &self.psk, &self.psk_id),
+            &transcript,
+        )?;
+        self.completed = true;
         Ok(())
     }
+
+    /// Key for early data, sent by the client along with the output of
+    /// `client_init()` before the exchange completes. The client has it
+    /// after `client_init()`, the server after `server_receive()`, before
+    /// that it is `None`.
+    ///
+    /// The early key is weaker than the shared secret. It only depends on
+    /// the temporary key encapsulated to the server's static public key,
+    /// and on the pre-shared key when one is set:
+    ///
+    /// * It has no forward secrecy. Whoever obtains the server's secret key
+    ///   later can recover it and decrypt recorded early data, unless a
+    ///   pre-shared key unknown to them was mixed in.
+    /// * It is not protected against replay. The output of `client_init()`
+    ///   can be sent to the server again, which derives the same early key
+    ///   and accepts the early data a second time.
+    /// * The V2 transcript is not mixed into it.
+    ///
+    /// Only use it for requests that are safe to repeat and to disclose
+    /// later, and move to the shared secret once the exchange completes.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # fn main() -> Result<(),KyberError> {
+    /// # let mut rng = rand::thread_rng();
+    /// let mut alice = Uake::new();
+    /// let mut bob = Uake::new();
+    /// let bob_keys = keypair(&mut rng)?;
+    /// assert!(alice.early_key().is_none());
+    /// let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+    /// let server_send = bob.server_receive(client_init, &bob_keys.secret, &mut rng)?;
+    /// assert!(alice.early_key().is_some());
+    /// assert_eq!(alice.early_key(), bob.early_key());
+    /// # Ok(()) }
+    /// ```
+    pub fn early_key(&self) -> Option<&SharedSecret> {
+        self.early_key.as_ref()
+    }
+
+    /// Handles the output of a `client_init()` request as `server_receive()`
+    /// does, also returning a tag that confirms the shared secret to the
+    /// client. The client's tag is then checked with `server_confirm()`.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # fn main() -> Result<(),KyberError> {
+    /// # let mut rng = rand::thread_rng();
+    /// let mut alice = Uake::new();
+    /// let mut bob = Uake::new();
+    /// let bob_keys = keypair(&mut rng)?;
+    /// let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+    /// let (server_send, server_tag) =
+    ///     bob.server_receive_with_tag(client_init, &bob_keys.secret, &mut rng)?;
+    /// let client_tag = alice.client_confirm_with_tag(server_send, &server_tag)?;
+    /// bob.server_confirm(&client_tag)?;
+    /// assert_eq!(alice.shared_secret, bob.shared_secret);
+    /// # Ok(()) }
+    /// ```
+    pub fn server_receive_with_tag<R>(
+        &mut self,
+        send_a: P::UakeSendInit,
+        secretkey: &SecretKey<P>,
+        rng: &mut R,
+    ) -> Result<(P::UakeSendResponse, ConfirmTag), KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let send_b = self.server_receive(send_a, secretkey, rng)?;
+        Ok((send_b, tag_for(&self.confirm_key, SERVER_CONFIRM_LABEL)))
+    }
+
+    /// Decapsulates the shared secret as `client_confirm()` does and checks
+    /// the server's tag, returning the client's tag for `server_confirm()`.
+    ///
+    /// On a mismatch the shared secret is cleared and
+    /// [`KyberError::KeyConfirmation`] is returned.
+    pub fn client_confirm_with_tag(
+        &mut self,
+        send_b: P::UakeSendResponse,
+        tag: &ConfirmTag,
+    ) -> Result<ConfirmTag, KyberError> {
+        self.client_confirm(send_b)?;
+        check_tag(
+            &mut self.shared_secret,
+            &mut self.confirm_key,
+            tag,
+            SERVER_CONFIRM_LABEL,
+        )?;
+        Ok(tag_for(&self.confirm_key, CLIENT_CONFIRM_LABEL))
+    }
+
+    /// Checks the client's tag returned by `client_confirm_with_tag()`.
+    ///
+    /// On a mismatch the shared secret is cleared and
+    /// [`KyberError::KeyConfirmation`] is returned, as it is when no
+    /// exchange was completed with `server_receive_with_tag()` first.
+    pub fn server_confirm(&mut self, tag: &ConfirmTag) -> Result<(), KyberError> {
+        if !self.completed {
+            return Err(KyberError::KeyConfirmation);
+        }
+        check_tag(
+            &mut self.shared_secret,
+            &mut self.confirm_key,
+            tag,
+            CLIENT_CONFIRM_LABEL,
+        )
+    }
 }
 
 /// Used for mutually authenticated key exchange between two parties.
@@ -188,32 +480,78 @@ impl Uake {
 /// assert_eq!(alice.shared_secret, bob.shared_secret);
 /// # Ok(()) }
 /// ```
+///
+/// As with [`Uake`], [`KexVersion::V2`] binds the session key to the
+/// transcript and both parties' public keys:
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(),KyberError> {
+/// # let mut rng = rand::thread_rng();
+/// let mut alice: Ake = Ake::with_version(KexVersion::V2);
+/// let mut bob: Ake = Ake::with_version(KexVersion::V2);
+/// # let alice_keys = keypair(&mut rng)?;
+/// # let bob_keys = keypair(&mut rng)?;
+/// # let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+/// # let server_send = bob.server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)?;
+/// # alice.client_confirm(server_send, &alice_keys.secret)?;
+/// # assert_eq!(alice.shared_secret, bob.shared_secret);
+/// # Ok(()) }
+/// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "zeroize", derive(Zeroize, ZeroizeOnDrop))]
-pub struct Ake {
+pub struct Ake<P: KyberParams = DefaultParams> {
     /// The resulting shared secret from a key exchange
     pub shared_secret: SharedSecret,
     /// Sent when initiating a key exchange
-    send_a: AkeSendInit,
+    send_a: P::AkeSendInit,
     /// Response to a key exchange initiation
-    send_b: AkeSendResponse,
+    send_b: P::AkeSendResponse,
     // Ephemeral keys
-    temp_key: TempKey,
-    eska: Eska,
+    temp_key: SharedSecret,
+    eska: SecretKey<P>,
+    // Hash of the server's static public key, kept by the client for V2
+    pk_hash: [u8; KYBER_SYMBYTES],
+    version: KexVersion,
+    psk: Option<PresharedKey>,
+    psk_id: [u8; KYBER_SYMBYTES],
+    // Keys the confirmation tags, derived next to the shared secret
+    confirm_key: SharedSecret,
+    // Set once this party derived the shared secret
+    completed: bool,
 }
 
-impl Default for Ake {
+impl<P: KyberParams> Default for Ake<P> {
     fn default() -> Self {
-        Ake {
-            shared_secret: [0u8; KYBER_SSBYTES],
-            send_a: [0u8; AKE_INIT_BYTES],
-            send_b: [0u8; AKE_RESPONSE_BYTES],
-            temp_key: [0u8; KYBER_SSBYTES],
-            eska: [0u8; KYBER_SECRETKEYBYTES],
-        }
+        Self::with_version(KexVersion::default())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Zeroize for Ake<P> {
+    fn zeroize(&mut self) {
+        self.shared_secret.zeroize();
+        self.send_a.zeroize();
+        self.send_b.zeroize();
+        self.temp_key.zeroize();
+        self.eska.zeroize();
+        self.pk_hash.zeroize();
+        self.psk.zeroize();
+        self.psk_id.zeroize();
+        self.confirm_key.zeroize();
+        self.completed = false;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> Drop for Ake<P> {
+    fn drop(&mut self) {
+        self.zeroize();
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> ZeroizeOnDrop for Ake<P> {}
+
 impl Ake {
     /// Builds a new AKE struct
     /// ```
@@ -223,8 +561,52 @@ impl Ake {
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<P: KyberParams> Ake<P> {
+    /// Builds an AKE struct deriving the session key with the given version
+    /// ```
+    /// # use pqc_kyber::*;
+    /// let mut kex: Ake = Ake::with_version(KexVersion::V2);
+    /// ```
+    pub fn with_version(version: KexVersion) -> Self {
+        Ake {
+            shared_secret: SharedSecret::zeroed(),
+            send_a: P::AkeSendInit::zeroed(),
+            send_b: P::AkeSendResponse::zeroed(),
+            temp_key: SharedSecret::zeroed(),
+            eska: SecretKey::zeroed(),
+            pk_hash: [0u8; KYBER_SYMBYTES],
+            version,
+            psk: None,
+            psk_id: [0u8; KYBER_SYMBYTES],
+            confirm_key: SharedSecret::zeroed(),
+            completed: false,
+        }
+    }
+
+    /// Builds an AKE struct mixing a pre-shared key into the session key, in
+    /// addition to the key exchange. Both parties need the same key and
+    /// identifier, the identifier may be empty.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # use core::convert::TryFrom;
+    /// # fn main() -> Result<(),KyberError> {
+    /// let psk = PresharedKey::try_from(&[7u8; 32][..])?;
+    /// let mut kex: Ake = Ake::with_psk(KexVersion::V2, psk, b"psk 1");
+    /// # Ok(()) }
+    /// ```
+    pub fn with_psk(version: KexVersion, psk: PresharedKey, psk_id: &[u8]) -> Self {
+        let mut kex = Self::with_version(version);
+        hash_h(&mut kex.psk_id, psk_id, psk_id.len());
+        kex.psk = Some(psk);
+        kex
+    }
 
     /// Initiates a Mutually Authenticated Key Exchange.
+    ///
+    /// Public keys encoding coefficients that are not reduced modulo q are
+    /// rejected with [`KyberError::InvalidPublicKey`].
     /// ```
     /// # use pqc_kyber::*;
     /// # fn main() -> Result<(),KyberError> {
@@ -236,23 +618,30 @@ impl Ake {
     /// ```
     pub fn client_init<R>(
         &mut self,
-        pubkey: &PublicKey,
+        pubkey: &PublicKey<P>,
         rng: &mut R,
-    ) -> Result<AkeSendInit, KyberError>
+    ) -> Result<P::AkeSendInit, KyberError>
     where
         R: CryptoRng + RngCore,
     {
-        ake_init_a(
-            &mut self.send_a,
-            &mut self.temp_key,
-            &mut self.eska,
-            pubkey,
+        self.completed = false;
+        ake_init_a::<P, R>(
+            self.send_a.as_mut(),
+            self.temp_key.as_mut(),
+            self.eska.as_mut(),
+            pubkey.as_ref(),
             rng,
         )?;
+        if self.version == KexVersion::V2 {
+            hash_h(&mut self.pk_hash, pubkey.as_ref(), P::PUBLICKEYBYTES);
+        }
         Ok(self.send_a)
     }
 
     /// Handles and authenticates the output of a `client_init()` request
+    ///
+    /// Public keys encoding coefficients that are not reduced modulo q are
+    /// rejected with [`KyberError::InvalidPublicKey`].
     /// ```
     /// # use pqc_kyber::*;
     /// # fn main() -> Result<(),KyberError> {
@@ -266,22 +655,28 @@ impl Ake {
     /// # Ok(()) }
     pub fn server_receive<R>(
         &mut self,
-        ake_send_a: AkeSendInit,
-        pubkey: &PublicKey,
-        secretkey: &SecretKey,
+        ake_send_a: P::AkeSendInit,
+        pubkey: &PublicKey<P>,
+        secretkey: &SecretKey<P>,
         rng: &mut R,
-    ) -> Result<AkeSendResponse, KyberError>
+    ) -> Result<P::AkeSendResponse, KyberError>
     where
         R: CryptoRng + RngCore,
     {
-        ake_shared_b(
-            &mut self.send_b,
-            &mut self.shared_secret,
-            &ake_send_a,
-            secretkey,
-            pubkey,
+        self.completed = false;
+        ake_shared_b::<P, R>(
+            self.send_b.as_mut(),
+            KexKeys {
+                k: self.shared_secret.as_mut(),
+                ck: self.confirm_key.as_mut(),
+            },
+            ake_send_a.as_ref(),
+            secretkey.as_ref(),
+            pubkey.as_ref(),
+            KdfParams::new(self.version, &self.psk, &self.psk_id),
             rng,
         )?;
+        self.completed = true;
         Ok(self.send_b)
     }
 
@@ -302,22 +697,134 @@ impl Ake {
     /// # Ok(()) }
     pub fn client_confirm(
         &mut self,
-        send_b: AkeSendResponse,
-        secretkey: &SecretKey,
+        send_b: P::AkeSendResponse,
+        secretkey: &SecretKey<P>,
     ) -> Result<(), KyberError> {
-        ake_shared_a(
+        let transcript = [
+            AKE_V2_LABEL,
+            &self.pk_hash,
+            pk_hash::<P>(secretkey.as_ref()),
+            self.send_a.as_ref(),
+            send_b.as_ref(),
+        ];
+        ake_shared_a::<P>(
+            KexKeys {
+                k: self.shared_secret.as_mut(),
+                ck: self.confirm_key.as_mut(),
+            },
+            send_b.as_ref(),
+            self.temp_key.as_ref(),
+            self.eska.as_ref(),
+            secretkey.as_ref(),
+            KdfParams::new(self.version, &self.psk, &self.psk_id),
+            &transcript,
+        )?;
+        self.completed = true;
+        Ok(())
+    }
+
+    /// Handles and authenticates the output of a `client_init()` request as
+    /// `server_receive()` does, also returning a tag that confirms the shared
+    /// secret to the client. The client's tag is then checked with
+    /// `server_confirm()`.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # fn main() -> Result<(),KyberError> {
+    /// # let mut rng = rand::thread_rng();
+    /// let mut alice = Ake::new();
+    /// let mut bob = Ake::new();
+    /// let alice_keys = keypair(&mut rng)?;
+    /// let bob_keys = keypair(&mut rng)?;
+    /// let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+    /// let (server_send, server_tag) = bob.server_receive_with_tag(
+    ///     client_init, &alice_keys.public, &bob_keys.secret, &mut rng
+    /// )?;
+    /// let client_tag = alice.client_confirm_with_tag(server_send, &server_tag, &alice_keys.secret)?;
+    /// bob.server_confirm(&client_tag)?;
+    /// assert_eq!(alice.shared_secret, bob.shared_secret);
+    /// # Ok(()) }
+    /// ```
+    pub fn server_receive_with_tag<R>(
+        &mut self,
+        ake_send_a: P::AkeSendInit,
+        pubkey: &PublicKey<P>,
+        secretkey: &SecretKey<P>,
+        rng: &mut R,
+    ) -> Result<(P::AkeSendResponse, ConfirmTag), KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let send_b = self.server_receive(ake_send_a, pubkey, secretkey, rng)?;
+        Ok((send_b, tag_for(&self.confirm_key, SERVER_CONFIRM_LABEL)))
+    }
+
+    /// Decapsulates and authenticates the shared secret as `client_confirm()`
+    /// does and checks the server's tag, returning the client's tag for
+    /// `server_confirm()`.
+    ///
+    /// On a mismatch the shared secret is cleared and
+    /// [`KyberError::KeyConfirmation`] is returned.
+    pub fn client_confirm_with_tag(
+        &mut self,
+        send_b: P::AkeSendResponse,
+        tag: &ConfirmTag,
+        secretkey: &SecretKey<P>,
+    ) -> Result<ConfirmTag, KyberError> {
+        self.client_confirm(send_b, secretkey)?;
+        check_tag(
             &mut self.shared_secret,
-            &send_b,
-            &self.temp_key,
-            &self.eska,
-            secretkey,
+            &mut self.confirm_key,
+            tag,
+            SERVER_CONFIRM_LABEL,
         )?;
+        Ok(tag_for(&self.confirm_key, CLIENT_CONFIRM_LABEL))
+    }
+
+    /// Checks the client's tag returned by `client_confirm_with_tag()`.
+    ///
+    /// On a mismatch the shared secret is cleared and
+    /// [`KyberError::KeyConfirmation`] is returned, as it is when no
+    /// exchange was completed with `server_receive_with_tag()` first.
+    pub fn server_confirm(&mut self, tag: &ConfirmTag) -> Result<(), KyberError> {
+        if !self.completed {
+            return Err(KyberError::KeyConfirmation);
+        }
+        check_tag(
+            &mut self.shared_secret,
+            &mut self.confirm_key,
+            tag,
+            CLIENT_CONFIRM_LABEL,
+        )
+    }
+}
+
+// Confirmation tag sent by the labelled party, keyed with the confirmation
+// key rather than the shared secret handed to the caller
+pub(crate) fn tag_for(ck: &SharedSecret, label: &[u8]) -> ConfirmTag {
+    let mut tag = [0u8; CONFIRM_TAG_BYTES];
+    kex_prf(&mut tag, ck.as_ref(), label);
+    tag
+}
+
+// Compares a received tag in constant time, the shared secret and the
+// confirmation key are cleared when it does not match
+pub(crate) fn check_tag(
+    ss: &mut SharedSecret,
+    ck: &mut SharedSecret,
+    tag: &ConfirmTag,
+    label: &[u8],
+) -> Result<(), KyberError> {
+    if ct_eq(&tag_for(ck, label), tag) {
         Ok(())
+    } else {
+        *ss = SharedSecret::zeroed();
+        *ck = SharedSecret::zeroed();
+        Err(KyberError::KeyConfirmation)
     }
 }
 
 // Unilaterally Authenticated Key Exchange initiation
-fn uake_init_a<R>(
+pub(crate) fn uake_init_a<P: KyberParams, R>(
     send: &mut [u8],
     tk: &mut [u8],
     sk: &mut [u8],
@@ -327,44 +834,55 @@ fn uake_init_a<R>(
 where
     R: CryptoRng + RngCore,
 {
-    crypto_kem_keypair(send, sk, rng, None)?;
-    crypto_kem_enc(&mut send[KYBER_PUBLICKEYBYTES..], tk, pkb, rng, None)?;
+    P::check_public_key(pkb)?;
+    P::crypto_kem_keypair(send, sk, rng, None)?;
+    P::crypto_kem_enc(&mut send[P::PUBLICKEYBYTES..], tk, pkb, rng, None)?;
     Ok(())
 }
 
 // Unilaterally authenticated key exchange computation by Bob
-fn uake_shared_b<R>(
+pub(crate) fn uake_shared_b<P: KyberParams, R>(
     send: &mut [u8],
-    k: &mut [u8],
+    keys: KexKeys,
     recv: &[u8],
     skb: &[u8],
+    params: KdfParams,
+    early_key: Option<&mut [u8]>,
     rng: &mut R,
 ) -> Result<(), KyberError>
 where
     R: CryptoRng + RngCore,
 {
-    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
-    crypto_kem_enc(send, &mut buf, recv, rng, None)?;
-    crypto_kem_dec(
-        &mut buf[KYBER_SYMBYTES..],
-        &recv[KYBER_PUBLICKEYBYTES..],
-        skb,
-    );
-    kdf(k, &buf, 2 * KYBER_SYMBYTES);
+    P::check_public_key(&recv[..P::PUBLICKEYBYTES])?;
+    let mut buf = [0u8; 5 * KYBER_SYMBYTES];
+    P::crypto_kem_enc(send, &mut buf, recv, rng, None)?;
+    P::crypto_kem_dec(&mut buf[KYBER_SYMBYTES..], &recv[P::PUBLICKEYBYTES..], skb);
+    if let Some(early_key) = early_key {
+        early_key_for(early_key, &buf[KYBER_SYMBYTES..2 * KYBER_SYMBYTES], params);
+    }
+    let transcript = [UAKE_V2_LABEL, pk_hash::<P>(skb), recv, send];
+    kex_kdf(keys, &mut buf, 2 * KYBER_SYMBYTES, params, &transcript);
     Ok(())
 }
 
 // Unilaterally authenticated key exchange computation by Alice
-fn uake_shared_a(k: &mut [u8], recv: &[u8], tk: &[u8], sk: &[u8]) -> Result<(), KyberError> {
-    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
-    crypto_kem_dec(&mut buf, recv, sk);
-    buf[KYBER_SYMBYTES..].copy_from_slice(&tk[..]);
-    kdf(k, &buf, 2 * KYBER_SYMBYTES);
+pub(crate) fn uake_shared_a<P: KyberParams>(
+    keys: KexKeys,
+    recv: &[u8],
+    tk: &[u8],
+    sk: &[u8],
+    params: KdfParams,
+    transcript: &[&[u8]],
+) -> Result<(), KyberError> {
+    let mut buf = [0u8; 5 * KYBER_SYMBYTES];
+    P::crypto_kem_dec(&mut buf, recv, sk);
+    buf[KYBER_SYMBYTES..2 * KYBER_SYMBYTES].copy_from_slice(tk);
+    kex_kdf(keys, &mut buf, 2 * KYBER_SYMBYTES, params, transcript);
     Ok(())
 }
 
 // Authenticated key exchange initiation by Alice
-fn ake_init_a<R>(
+pub(crate) fn ake_init_a<P: KyberParams, R>(
     send: &mut [u8],
     tk: &mut [u8],
     sk: &mut [u8],
@@ -374,57 +892,130 @@ fn ake_init_a<R>(
 where
     R: CryptoRng + RngCore,
 {
-    crypto_kem_keypair(send, sk, rng, None)?;
-    crypto_kem_enc(&mut send[KYBER_PUBLICKEYBYTES..], tk, pkb, rng, None)?;
+    P::check_public_key(pkb)?;
+    P::crypto_kem_keypair(send, sk, rng, None)?;
+    P::crypto_kem_enc(&mut send[P::PUBLICKEYBYTES..], tk, pkb, rng, None)?;
     Ok(())
 }
 
 // Mutually authenticated key exchange computation by Bob
-fn ake_shared_b<R>(
+pub(crate) fn ake_shared_b<P: KyberParams, R>(
     send: &mut [u8],
-    k: &mut [u8],
+    keys: KexKeys,
     recv: &[u8],
     skb: &[u8],
     pka: &[u8],
+    params: KdfParams,
     rng: &mut R,
 ) -> Result<(), KyberError>
 where
     R: CryptoRng + RngCore,
 {
-    let mut buf = [0u8; 3 * KYBER_SYMBYTES];
-    crypto_kem_enc(send, &mut buf, recv, rng, None)?;
-    crypto_kem_enc(
-        &mut send[KYBER_CIPHERTEXTBYTES..],
+    P::check_public_key(&recv[..P::PUBLICKEYBYTES])?;
+    P::check_public_key(pka)?;
+    let mut buf = [0u8; 6 * KYBER_SYMBYTES];
+    P::crypto_kem_enc(send, &mut buf, recv, rng, None)?;
+    P::crypto_kem_enc(
+        &mut send[P::CIPHERTEXTBYTES..],
         &mut buf[KYBER_SYMBYTES..],
         pka,
         rng,
         None,
     )?;
-    crypto_kem_dec(
+    P::crypto_kem_dec(
         &mut buf[2 * KYBER_SYMBYTES..],
-        &recv[KYBER_PUBLICKEYBYTES..],
+        &recv[P::PUBLICKEYBYTES..],
         skb,
     );
-    kdf(k, &buf, 3 * KYBER_SYMBYTES);
+    let mut pka_hash = [0u8; KYBER_SYMBYTES];
+    if params.version == KexVersion::V2 {
+        hash_h(&mut pka_hash, pka, P::PUBLICKEYBYTES);
+    }
+    let transcript = [AKE_V2_LABEL, pk_hash::<P>(skb), &pka_hash, recv, send];
+    kex_kdf(keys, &mut buf, 3 * KYBER_SYMBYTES, params, &transcript);
     Ok(())
 }
 
 // Mutually authenticated key exchange computation by Alice
-fn ake_shared_a(
-    k: &mut [u8],
+pub(crate) fn ake_shared_a<P: KyberParams>(
+    keys: KexKeys,
     recv: &[u8],
     tk: &[u8],
     sk: &[u8],
     ska: &[u8],
+    params: KdfParams,
+    transcript: &[&[u8]],
 ) -> Result<(), KyberError> {
-    let mut buf = [0u8; 3 * KYBER_SYMBYTES];
-    crypto_kem_dec(&mut buf, recv, sk);
-    crypto_kem_dec(
-        &mut buf[KYBER_SYMBYTES..],
-        &recv[KYBER_CIPHERTEXTBYTES..],
-        ska,
-    );
-    buf[2 * KYBER_SYMBYTES..].copy_from_slice(&tk[..]);
-    kdf(k, &buf, 3 * KYBER_SYMBYTES);
+    let mut buf = [0u8; 6 * KYBER_SYMBYTES];
+    P::crypto_kem_dec(&mut buf, recv, sk);
+    P::crypto_kem_dec(&mut buf[KYBER_SYMBYTES..], &recv[P::CIPHERTEXTBYTES..], ska);
+    buf[2 * KYBER_SYMBYTES..3 * KYBER_SYMBYTES].copy_from_slice(tk);
+    kex_kdf(keys, &mut buf, 3 * KYBER_SYMBYTES, params, transcript);
     Ok(())
 }
+
+// Early key of the unilateral exchange from the temporary key, followed by
+// the pre-shared key and its identifier hash in the PSK mode
+fn early_key_for(ek: &mut [u8], tk: &[u8], params: KdfParams) {
+    let mut buf = [0u8; 3 * KYBER_SYMBYTES];
+    buf[..KYBER_SYMBYTES].copy_from_slice(tk);
+    let mut len = KYBER_SYMBYTES;
+    if let Some((psk, psk_id)) = params.psk {
+        buf[len..len + KYBER_SYMBYTES].copy_from_slice(psk.as_ref());
+        buf[len + KYBER_SYMBYTES..len + 2 * KYBER_SYMBYTES].copy_from_slice(psk_id);
+        len += 2 * KYBER_SYMBYTES;
+    }
+    kex_prf(ek, &buf[..len], EARLY_KEY_LABEL);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
+}
+
+// Hash of the public key stored in a secret key
+pub(crate) fn pk_hash<P: KyberParams>(sk: &[u8]) -> &[u8] {
+    &sk[P::SECRETKEYBYTES - 2 * KYBER_SYMBYTES..P::SECRETKEYBYTES - KYBER_SYMBYTES]
+}
+
+// Outputs of the session key derivation, the session key and the key of
+// the confirmation tags
+pub(crate) struct KexKeys<'a> {
+    pub(crate) k: &'a mut [u8],
+    pub(crate) ck: &'a mut [u8],
+}
+
+// Inputs of the session key derivation besides the KEM shared secrets
+#[derive(Clone, Copy)]
+pub(crate) struct KdfParams<'a> {
+    pub(crate) version: KexVersion,
+    // Pre-shared key and the hash of its identifier
+    pub(crate) psk: Option<(&'a PresharedKey, &'a [u8])>,
+}
+
+impl<'a> KdfParams<'a> {
+    fn new(version: KexVersion, psk: &'a Option<PresharedKey>, psk_id: &'a [u8]) -> Self {
+        KdfParams {
+            version,
+            psk: psk.as_ref().map(|psk| (psk, psk_id)),
+        }
+    }
+}
+
+// Derives the session key from the KEM shared secrets in buf[..len], V2
+// appends the transcript hash to them and the PSK mode the pre-shared key
+// and its identifier hash. The confirmation key is a separate output over
+// the same input, tags computed with it say nothing about the session key.
+fn kex_kdf(keys: KexKeys, buf: &mut [u8], len: usize, params: KdfParams, transcript: &[&[u8]]) {
+    let mut inlen = len;
+    if params.version == KexVersion::V2 {
+        hash_transcript(&mut buf[inlen..], transcript);
+        inlen += KYBER_SYMBYTES;
+    }
+    if let Some((psk, psk_id)) = params.psk {
+        buf[inlen..inlen + KYBER_SYMBYTES].copy_from_slice(psk.as_ref());
+        buf[inlen + KYBER_SYMBYTES..inlen + 2 * KYBER_SYMBYTES].copy_from_slice(psk_id);
+        inlen += 2 * KYBER_SYMBYTES;
+    }
+    kdf(keys.k, buf, inlen);
+    kex_prf(keys.ck, &buf[..inlen], CONFIRM_KEY_LABEL);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
+}
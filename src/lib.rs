@@ -18,14 +18,25 @@
 //! | kyber1024 | Enables kyber1024 mode, with a security level roughly equivalent to AES-256.                   |
 //! | 90s       | 90's mode uses SHA2 and AES-CTR as a replacement for SHAKE. This may provide hardware speedups on certain architectures.                                                           |
 //! | avx2      | On x86_64 platforms enable the optimized version. This flag is will cause a compile error on other architectures. |
+//! | low-stack | Generates matrix A row by row in the reference code, for devices with small stacks. Slower, the avx2 code is unaffected |
 //! | wasm      | For compiling to WASM targets. |
-//! | nasm | Uses Netwide Assembler avx2 code instead of GAS for portability. Requires a nasm compiler: https://www.nasm.us/ |
-//! | zeroize | This will zero out the key exchange structs on drop using the [zeroize](https://docs.rs/zeroize/latest/zeroize/) crate |
+//! | nasm | Deprecated, the avx2 code no longer uses an assembler. Kept as an alias of `avx2` |
+//! | zeroize | This will zero out the key exchange structs on drop and the intermediate secret buffers of every operation before it returns, using the [zeroize](https://docs.rs/zeroize/latest/zeroize/) crate |
+//! | xwing | Enables the X-Wing hybrid KEM, ML-KEM-768 combined with X25519. Not available in 90s mode |
+//! | hpke | Enables HPKE (RFC 9180) with Kyber as the KEM, HKDF-SHA256 and AES-128-GCM or ChaCha20-Poly1305 |
+//! | sealedbox | Enables public key encryption of arbitrary messages, needs an allocator |
+//! | envelope | Enables encryption of a message to many recipients, needs an allocator |
+//! | pkcs8 | Enables PKCS#8 and SubjectPublicKeyInfo DER and PEM encoding of keys with the ML-KEM OIDs, needs an allocator. Not available in 90s mode |
+//! | kem-traits | Implements the RustCrypto [kem](https://docs.rs/kem) `Encapsulate` and `Decapsulate` traits for `PublicKey` and `SecretKey` |
 //! | std | Enable the standard library |
 //!
 //! ## Usage
 //!
-//! For optimisations on x86 platforms enable the `avx2` feature and the following RUSTFLAGS:
+//! For optimisations on x86 platforms enable the `avx2` feature. With `std` the CPU is
+//! checked at runtime and the reference code is used when avx2 isn't supported.
+//!
+//! `no_std` builds can't query the CPU, they only use the optimised code when the target
+//! features are enabled at compile time:
 //!
 //! ```shell
 //! export RUSTFLAGS="-C target-feature=+aes,+avx2,+sse2,+sse4.1,+bmi2,+popcnt"
@@ -53,6 +64,26 @@
 //! # Ok(()) }
 //! ```
 //!
+//! Keys, ciphertexts and shared secrets are the [`PublicKey`], [`SecretKey`],
+//! [`Ciphertext`] and [`SharedSecret`] types. They are built from received bytes
+//! with `TryFrom<&[u8]>` and read back with `as_ref()`. Secrets are redacted
+//! when printed, compared in constant time and zeroized on drop with the
+//! `zeroize` feature.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # use std::convert::TryFrom;
+//! # fn main() -> Result<(),KyberError> {
+//! # let mut rng = rand::thread_rng();
+//! # let keys_bob = keypair(&mut rng)?;
+//! # let (ct, _) = encapsulate(&keys_bob.public, &mut rng)?;
+//! # let received = ct.as_ref().to_vec();
+//! let ciphertext = Ciphertext::try_from(&received[..])?;
+//! let shared_secret = decapsulate(&ciphertext, &keys_bob.secret)?;
+//! assert_eq!(shared_secret.as_ref().len(), KYBER_SSBYTES);
+//! # Ok(()) }
+//! ```
+//!
 //! Higher level functions offering unilateral or mutual authentication
 //!
 //! #### Unilaterally Authenticated Key Exchange
@@ -111,13 +142,164 @@
 //! ```
 //!
 //!
+//! #### Multiple Security Levels
+//! The features above only choose the default level. Every level is also
+//! available as a [KyberParams] type, so a single build can serve
+//! Kyber512, Kyber768 and Kyber1024 peers side by side:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # let mut rng = rand::thread_rng();
+//! let keys = Kyber512::keypair(&mut rng)?;
+//! let (ciphertext, shared_secret_alice) = Kyber512::encapsulate(&keys.public, &mut rng)?;
+//! let shared_secret_bob = Kyber512::decapsulate(&ciphertext, &keys.secret)?;
+//! assert_eq!(shared_secret_alice, shared_secret_bob);
+//!
+//! let mut alice = Uake::<Kyber1024>::default();
+//! let mut bob = Uake::<Kyber1024>::default();
+//! let bob_keys = Kyber1024::keypair(&mut rng)?;
+//! let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+//! let server_send = bob.server_receive(client_init, &bob_keys.secret, &mut rng)?;
+//! alice.client_confirm(server_send)?;
+//! assert_eq!(alice.shared_secret, bob.shared_secret);
+//! # Ok(()) }
+//! ```
+//!
+//! #### ML-KEM
+//! The [mlkem](mlkem/index.html) module implements the key schedule of the final FIPS 203
+//! standard for interoperability with ML-KEM peers, it is not available in 90s mode.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # #[cfg(not(feature = "90s"))] {
+//! # let mut rng = rand::thread_rng();
+//! let keys = mlkem::keypair(&mut rng)?;
+//! let (ciphertext, shared_secret_alice) = mlkem::encapsulate(&keys.public, &mut rng)?;
+//! let shared_secret_bob = mlkem::decapsulate(&ciphertext, &keys.secret)?;
+//! assert_eq!(shared_secret_alice, shared_secret_bob);
+//! # }
+//! # Ok(()) }
+//! ```
+//!
+//! #### X-Wing
+//! With the `xwing` feature the [xwing](xwing/index.html) module provides the X-Wing hybrid KEM,
+//! ML-KEM-768 combined with X25519. It is not available in 90s mode.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # #[cfg(all(feature = "xwing", not(feature = "90s")))] {
+//! # let mut rng = rand::thread_rng();
+//! let keys = xwing::keypair(&mut rng)?;
+//! let (ciphertext, shared_secret_alice) = xwing::encapsulate(&keys.public, &mut rng)?;
+//! let shared_secret_bob = xwing::decapsulate(&ciphertext, &keys.secret)?;
+//! assert_eq!(shared_secret_alice, shared_secret_bob);
+//! # }
+//! # Ok(()) }
+//! ```
+//!
+//! #### HPKE
+//! With the `hpke` feature the [hpke](hpke/index.html) module seals messages to a Kyber public key
+//! following RFC 9180, in Base or PSK mode:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # #[cfg(feature = "hpke")] {
+//! # use pqc_kyber::hpke::*;
+//! # let mut rng = rand::thread_rng();
+//! # let keys = keypair(&mut rng)?;
+//! let hpke = Hpke::base(Aead::Aes128Gcm);
+//! let mut message = *b"Hello Bob";
+//! let (enc, tag) = hpke.seal(&keys.public, b"info", b"aad", &mut message, &mut rng)?;
+//! hpke.open(&enc, &keys.secret, b"info", b"aad", &mut message, &tag)?;
+//! assert_eq!(&message, b"Hello Bob");
+//! # }
+//! # Ok(()) }
+//! ```
+//!
+//! #### Sealed Boxes
+//! With the `sealedbox` feature the [sealedbox](sealedbox/index.html) module encrypts messages of
+//! any length to a public key:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # #[cfg(feature = "sealedbox")] {
+//! # let mut rng = rand::thread_rng();
+//! # let keys = keypair(&mut rng)?;
+//! let sealed = sealedbox::seal(&keys.public, b"Hello Bob", b"", &mut rng)?;
+//! let message = sealedbox::open(&keys.secret, &sealed, b"")?;
+//! assert_eq!(message, b"Hello Bob");
+//! # }
+//! # Ok(()) }
+//! ```
+//!
+//! #### Envelopes
+//! With the `envelope` feature the [envelope](envelope/index.html) module encrypts a message once
+//! for many recipients, each finding their entry by the hash of their public key:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # #[cfg(feature = "envelope")] {
+//! # let mut rng = rand::thread_rng();
+//! # let alice = keypair(&mut rng)?;
+//! # let bob = keypair(&mut rng)?;
+//! let sealed = envelope::seal(&[alice.public, bob.public], b"file key", b"", &mut rng)?;
+//! let message = envelope::open(&bob.secret, &sealed, b"")?;
+//! assert_eq!(message, b"file key");
+//! # }
+//! # Ok(()) }
+//! ```
+//!
+//! #### Key Encoding
+//! With the `pkcs8` feature keys implement the traits of the re-exported
+//! [pkcs8](https://docs.rs/pkcs8) crate, using the `id-alg-ml-kem-512/768/1024` identifiers. Those
+//! identify ML-KEM keys, the encoding is meant for keys of the [mlkem](mlkem/index.html) module and
+//! seeds are FIPS 203 seeds:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(),KyberError> {
+//! # #[cfg(all(feature = "pkcs8", not(feature = "90s")))] {
+//! use pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+//! # let mut rng = rand::thread_rng();
+//! let keys = mlkem::keypair(&mut rng)?;
+//! let pem = keys.public.to_public_key_pem(LineEnding::LF).unwrap();
+//! let der = keys.secret.to_pkcs8_der().unwrap();
+//! let secret = SecretKey::<DefaultParams>::from_pkcs8_der(der.as_bytes()).unwrap();
+//! assert_eq!(public(&secret), keys.public);
+//! # }
+//! # Ok(()) }
+//! ```
+//!
 //! ## Errors
-//! The [KyberError](enum.KyberError.html) enum handles errors. It has two variants:
+//! The [KyberError](enum.KyberError.html) enum handles errors. It has the following variants:
 //!
 //! * **InvalidInput** - One or more byte inputs to a function are incorrectly sized. A likely cause of
-//! this is two parties using different security levels while trying to negotiate a key exchange.
+//!   this is two parties using different security levels while trying to negotiate a key exchange.
+//!
+//! * **Decapsulation** - The ciphertext was unable to be authenticated. The shared secret was not decapsulated.
+//!   Only returned by [decapsulate_explicit](fn.decapsulate_explicit.html), decapsulation otherwise uses implicit rejection.
+//!
+//! * **RandomBytesGeneration** - Error trying to fill random bytes (i.e external (hardware) RNG modules can fail).
+//!
+//! * **InvalidKey** - Given public and secret key does not match. Probably an input error.
+//!
+//! * **InvalidPublicKey** - The public key encodes coefficients that are not reduced modulo q (FIPS 203 modulus check).
 //!
-//! * **Decapsulation** - The ciphertext was unable to be authenticated. The shared secret was not decapsulated  
+//! * **InvalidSecretKey** - The public key hash stored in the secret key does not match (FIPS 203 hash check).
+//!
+//! * **InvalidSealedBox** - A sealed box failed to authenticate, it was modified or sealed to a different key.
+//!
+//! * **InvalidHpkeMessage** - An HPKE message failed to authenticate, it was modified, sealed to a different key or opened with a different context.
+//!
+//! * **RecipientNotFound** - An envelope has no entry for the secret key it was opened with.
+//!
+//! * **KeyConfirmation** - A key exchange confirmation tag did not match, the parties derived different keys.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::many_single_char_names)]
@@ -128,15 +310,10 @@ compile_error!("Only one security level can be specified");
 
 #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
 mod avx2;
-#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
-use avx2::*;
 
-#[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
 mod reference;
-#[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
 use reference::*;
 
-#[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
 #[cfg(feature = "hazmat")]
 pub use reference::indcpa;
 
@@ -144,21 +321,47 @@ pub use reference::indcpa;
 mod wasm;
 
 mod api;
+mod backend;
+#[cfg(all(feature = "pkcs8", not(feature = "90s")))]
+mod encoding;
+#[cfg(feature = "envelope")]
+pub mod envelope;
 mod error;
+pub mod handshake;
+#[cfg(feature = "hpke")]
+pub mod hpke;
 mod kem;
+#[cfg(feature = "kem-traits")]
+mod kem_traits;
 mod kex;
+#[cfg(not(feature = "90s"))]
+pub mod mlkem;
 mod params;
+mod prepared;
 mod rng;
+#[cfg(feature = "sealedbox")]
+pub mod sealedbox;
 mod symmetric;
+mod types;
+#[cfg(all(feature = "xwing", not(feature = "90s")))]
+pub mod xwing;
 
 pub use api::*;
+#[cfg(all(feature = "pkcs8", not(feature = "90s")))]
+pub use encoding::{ID_ALG_ML_KEM_1024, ID_ALG_ML_KEM_512, ID_ALG_ML_KEM_768};
 pub use error::KyberError;
 pub use kex::*;
 pub use params::{
-    KYBER_90S, KYBER_CIPHERTEXTBYTES, KYBER_K, KYBER_PUBLICKEYBYTES, KYBER_SECRETKEYBYTES,
-    KYBER_SSBYTES, KYBER_SYMBYTES,
+    ByteArray, DefaultParams, Kyber1024, Kyber512, Kyber768, KyberParams, KYBER_90S,
+    KYBER_CIPHERTEXTBYTES, KYBER_K, KYBER_PUBLICKEYBYTES, KYBER_SECRETKEYBYTES, KYBER_SSBYTES,
+    KYBER_SYMBYTES,
 };
+#[cfg(all(feature = "pkcs8", not(feature = "90s")))]
+pub use pkcs8;
+pub use prepared::{PreparedPublicKey, PreparedSecretKey};
 pub use rand_core::{CryptoRng, RngCore};
+pub use subtle::Choice;
+pub use types::{Ciphertext, PresharedKey, PublicKey, SecretKey, SharedSecret};
 
 // Feature hack to expose private functions for the Known Answer Tests
 // and fuzzing. Will fail to compile if used outside `cargo test` or
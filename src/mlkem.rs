@@ -0,0 +1,305 @@
+//! FIPS 203 ML-KEM
+//!
+//! The final NIST standard differs from round 3 Kyber only in its key schedule:
+//! key generation domain separates `G` with the security level, the random
+//! message is used as is, the shared secret is taken directly from
+//! `G(m || H(ek))` and implicit rejection returns `J(z || c)`. Keys and
+//! ciphertexts have the same sizes and layout as round 3 Kyber, but the two
+//! are not interoperable.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(), KyberError> {
+//! let mut rng = rand::thread_rng();
+//! let keys = mlkem::keypair(&mut rng)?;
+//! let (ct, ss1) = mlkem::encapsulate(&keys.public, &mut rng)?;
+//! let ss2 = mlkem::decapsulate(&ct, &keys.secret)?;
+//! assert_eq!(ss1, ss2);
+//! # Ok(()) }
+//! ```
+//!
+//! Not available in 90s mode, ML-KEM is only specified with SHA3 and SHAKE.
+use crate::{
+    api::DummyRng,
+    backend::*,
+    error::KyberError,
+    kem::{kem_check_pk, kem_check_sk},
+    kex::{Decapsulated, Encapsulated},
+    params::*,
+    rng::randombytes,
+    symmetric::*,
+    verify::*,
+    Ciphertext, CryptoRng, Keypair, PublicKey, RngCore, SecretKey, SharedSecret,
+};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Generates an ML-KEM keypair with a provided RNG.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = mlkem::keypair(&mut rng)?;
+/// # Ok(())}
+/// ```
+pub fn keypair<R>(rng: &mut R) -> Result<Keypair, KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    let mut public = PublicKey::zeroed();
+    let mut secret = SecretKey::zeroed();
+    mlkem_keypair::<KYBER_K, R>(public.as_mut(), secret.as_mut(), rng, None)?;
+    Ok(Keypair { public, secret })
+}
+
+/// Deterministically derives an ML-KEM keypair from the 64 byte `d || z` seed
+/// of FIPS 203 `ML-KEM.KeyGen_internal`.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let keys = mlkem::derive(&[42u8; 64])?;
+/// assert_eq!(keys, mlkem::derive(&[42u8; 64])?);
+/// # Ok(())}
+/// ```
+pub fn derive(seed: &[u8]) -> Result<Keypair, KyberError> {
+    let mut public = PublicKey::zeroed();
+    let mut secret = SecretKey::zeroed();
+    let mut _rng = DummyRng {};
+    if seed.len() != 2 * KYBER_SYMBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    mlkem_keypair::<KYBER_K, _>(
+        public.as_mut(),
+        secret.as_mut(),
+        &mut _rng,
+        Some((&seed[..KYBER_SYMBYTES], &seed[KYBER_SYMBYTES..])),
+    )?;
+    Ok(Keypair { public, secret })
+}
+
+/// Encapsulates to an ML-KEM public key returning the ciphertext to send
+/// and the shared secret
+///
+/// The public key must pass the FIPS 203 modulus check, otherwise
+/// [`KyberError::InvalidPublicKey`] is returned.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = mlkem::keypair(&mut rng)?;
+/// let (ciphertext, shared_secret) = mlkem::encapsulate(&keys.public, &mut rng)?;
+/// # Ok(())}
+/// ```
+pub fn encapsulate<R>(pk: &PublicKey, rng: &mut R) -> Encapsulated
+where
+    R: CryptoRng + RngCore,
+{
+    kem_check_pk::<KYBER_K>(pk.as_ref())?;
+    let mut ct = Ciphertext::zeroed();
+    let mut ss = SharedSecret::zeroed();
+    mlkem_enc::<KYBER_K, R>(ct.as_mut(), ss.as_mut(), pk.as_ref(), rng)?;
+    Ok((ct, ss))
+}
+
+/// Decapsulates an ML-KEM ciphertext with a secret key.
+///
+/// Invalid ciphertexts are implicitly rejected, the returned shared secret is
+/// then a pseudo-random value unknown to the sender.
+///
+/// The secret key must pass the FIPS 203 hash check, otherwise
+/// [`KyberError::InvalidSecretKey`] is returned.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = mlkem::keypair(&mut rng)?;
+/// let (ct, ss1) = mlkem::encapsulate(&keys.public, &mut rng)?;
+/// let ss2 = mlkem::decapsulate(&ct, &keys.secret)?;
+/// assert_eq!(ss1, ss2);
+/// #  Ok(())}
+/// ```
+pub fn decapsulate(ct: &Ciphertext, sk: &SecretKey) -> Decapsulated {
+    kem_check_sk::<KYBER_K>(sk.as_ref())?;
+    let mut ss = SharedSecret::zeroed();
+    mlkem_dec::<KYBER_K>(ss.as_mut(), ct.as_ref(), sk.as_ref());
+    Ok(ss)
+}
+
+/// Encodes a 64 byte `d || z` seed as a PKCS#8 private key in the seed
+/// form, the most compact encoding of an ML-KEM key.
+///
+/// Secret keys are otherwise exported in the expanded form, see
+/// [`EncodePrivateKey`](pkcs8::EncodePrivateKey). Requires the `pkcs8`
+/// feature.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// use pkcs8::DecodePrivateKey;
+/// # fn main() -> Result<(), KyberError> {
+/// let seed = [42u8; 64];
+/// let der = mlkem::seed_to_pkcs8_der(&seed)?;
+/// let secret = SecretKey::<DefaultParams>::from_pkcs8_der(der.as_bytes()).unwrap();
+/// assert_eq!(secret, mlkem::derive(&seed)?.secret);
+/// # Ok(())}
+/// ```
+#[cfg(feature = "pkcs8")]
+pub fn seed_to_pkcs8_der(seed: &[u8]) -> Result<pkcs8::SecretDocument, KyberError> {
+    crate::encoding::encode_seed::<DefaultParams>(seed)
+}
+
+/// Name:  mlkem_keypair
+///
+/// Description: FIPS 203 ML-KEM.KeyGen for the security level K, the seed
+///  (d, z) is drawn from the RNG unless provided
+///
+/// Arguments:   - [u8] pk: output public key (an already allocated array of Params::<K>::PUBLICKEYBYTES bytes)
+///  - [u8] sk: output private key (an already allocated array of Params::<K>::SECRETKEYBYTES bytes)
+pub(crate) fn mlkem_keypair<const K: usize, R>(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    rng: &mut R,
+    seed: Option<(&[u8], &[u8])>,
+) -> Result<(), KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    let pk_start = Params::<K>::SECRETKEYBYTES - (2 * KYBER_SYMBYTES);
+    let sk_start = Params::<K>::SECRETKEYBYTES - KYBER_SYMBYTES;
+    let start = Params::<K>::INDCPA_SECRETKEYBYTES;
+    let end = Params::<K>::INDCPA_PUBLICKEYBYTES + Params::<K>::INDCPA_SECRETKEYBYTES;
+
+    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+    let mut randbuf = [0u8; KYBER_SYMBYTES + 1];
+
+    if let Some(s) = seed {
+        randbuf[..KYBER_SYMBYTES].copy_from_slice(s.0);
+    } else {
+        randombytes(&mut randbuf, KYBER_SYMBYTES, rng)?;
+    }
+
+    // Domain separation of the security level: (rho, sigma) = G(d || k)
+    randbuf[KYBER_SYMBYTES] = K as u8;
+    hash_g(&mut buf, &randbuf, KYBER_SYMBYTES + 1);
+
+    let (publicseed, noiseseed) = buf.split_at(KYBER_SYMBYTES);
+    indcpa_keypair_derand::<K>(pk, sk, publicseed, noiseseed);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        randbuf.zeroize();
+    }
+
+    sk[start..end].copy_from_slice(&pk[..Params::<K>::INDCPA_PUBLICKEYBYTES]);
+    hash_h(&mut sk[pk_start..], pk, Params::<K>::PUBLICKEYBYTES);
+
+    if let Some(s) = seed {
+        sk[sk_start..].copy_from_slice(s.1)
+    } else {
+        randombytes(&mut sk[sk_start..], KYBER_SYMBYTES, rng)?;
+    }
+    Ok(())
+}
+
+/// Name:  mlkem_enc
+///
+/// Description: FIPS 203 ML-KEM.Encaps for the security level K, generates
+///  cipher text and shared secret for given public key
+///
+/// Arguments:   - [u8] ct:   output cipher text (an already allocated array of Params::<K>::CIPHERTEXTBYTES bytes)
+///  - [u8] ss:   output shared secret (an already allocated array of KYBER_SSBYTES bytes)
+///  - const [u8] pk: input public key (an already allocated array of Params::<K>::PUBLICKEYBYTES bytes)
+pub(crate) fn mlkem_enc<const K: usize, R>(
+    ct: &mut [u8],
+    ss: &mut [u8],
+    pk: &[u8],
+    rng: &mut R,
+) -> Result<(), KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    let mut m = [0u8; KYBER_SYMBYTES];
+    randombytes(&mut m, KYBER_SYMBYTES, rng)?;
+    mlkem_enc_derand::<K>(ct, ss, pk, &m);
+    #[cfg(feature = "zeroize")]
+    m.zeroize();
+    Ok(())
+}
+
+/// Name:  mlkem_enc_derand
+///
+/// Description: FIPS 203 ML-KEM.Encaps_internal for the security level K,
+///  deterministic encapsulation with the message m
+///
+/// Arguments:   - [u8] ct:   output cipher text (an already allocated array of Params::<K>::CIPHERTEXTBYTES bytes)
+///  - [u8] ss:   output shared secret (an already allocated array of KYBER_SSBYTES bytes)
+///  - const [u8] pk: input public key (an already allocated array of Params::<K>::PUBLICKEYBYTES bytes)
+///  - const [u8] m: input message (an already allocated array of KYBER_SYMBYTES bytes)
+pub(crate) fn mlkem_enc_derand<const K: usize>(ct: &mut [u8], ss: &mut [u8], pk: &[u8], m: &[u8]) {
+    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+    let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+
+    buf[..KYBER_SYMBYTES].copy_from_slice(&m[..KYBER_SYMBYTES]);
+
+    // (K, r) = G(m || H(ek))
+    hash_h(&mut buf[KYBER_SYMBYTES..], pk, Params::<K>::PUBLICKEYBYTES);
+    hash_g(&mut kr, &buf, 2 * KYBER_SYMBYTES);
+
+    // coins are in kr[KYBER_SYMBYTES..]
+    indcpa_enc::<K>(ct, &buf, pk, &kr[KYBER_SYMBYTES..]);
+
+    ss[..KYBER_SSBYTES].copy_from_slice(&kr[..KYBER_SYMBYTES]);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        kr.zeroize();
+    }
+}
+
+/// Name:  mlkem_dec
+///
+/// Description: FIPS 203 ML-KEM.Decaps for the security level K, generates
+///  shared secret for given cipher text and private key
+///
+/// Arguments:   - [u8] ss:   output shared secret (an already allocated array of KYBER_SSBYTES bytes)
+///  - const [u8] ct: input cipher text (an already allocated array of Params::<K>::CIPHERTEXTBYTES bytes)
+///  - const [u8] sk: input private key (an already allocated array of Params::<K>::SECRETKEYBYTES bytes)
+///
+/// On failure, ss will contain J(z || c).
+pub(crate) fn mlkem_dec<const K: usize>(ss: &mut [u8], ct: &[u8], sk: &[u8]) {
+    let start = Params::<K>::SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    let end = Params::<K>::SECRETKEYBYTES - KYBER_SYMBYTES;
+    let mut buf = [0u8; 2 * KYBER_SYMBYTES];
+    let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+    let mut cmp = [0u8; KYBER_CIPHERTEXTBYTES_MAX];
+    let cmp = &mut cmp[..Params::<K>::CIPHERTEXTBYTES];
+    let pk = &sk[Params::<K>::INDCPA_SECRETKEYBYTES..][..Params::<K>::INDCPA_PUBLICKEYBYTES];
+
+    indcpa_dec::<K>(&mut buf, ct, sk);
+
+    // (K', r') = G(m' || H(ek))
+    buf[KYBER_SYMBYTES..].copy_from_slice(&sk[start..end]);
+    hash_g(&mut kr, &buf, 2 * KYBER_SYMBYTES);
+
+    // coins are in kr[KYBER_SYMBYTES..]
+    indcpa_enc::<K>(cmp, &buf, pk, &kr[KYBER_SYMBYTES..]);
+    let fail = verify(ct, cmp, Params::<K>::CIPHERTEXTBYTES);
+
+    // Implicit rejection value, kept unless re-encryption succeeded
+    rkprf(ss, &sk[end..], &ct[..Params::<K>::CIPHERTEXTBYTES]);
+    cmov(ss, &kr, KYBER_SYMBYTES, fail ^ 1);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        kr.zeroize();
+        cmp.zeroize();
+    }
+}
@@ -1,3 +1,16 @@
+// The default level sizes are not all needed by the reference backend
+#![allow(dead_code)]
+use crate::{
+    backend::{ExpandedPk, ExpandedSk},
+    kem,
+    symmetric::kdf_context,
+    Ciphertext, CryptoRng, Keypair, KyberError, PublicKey, RngCore, SecretKey, SharedSecret,
+};
+use core::{fmt::Debug, hash::Hash};
+use subtle::Choice;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
 /// The security level of Kyber
 ///
 /// Defaults to 3 (kyber768), will be 2 or 4 respectively when
@@ -25,7 +38,7 @@ pub const KYBER_90S: bool = cfg!(feature = "90s");
 pub const KYBER_N: usize = 256;
 pub const KYBER_Q: usize = 3329;
 
-pub const KYBER_ETA1: usize = if cfg!(feature = "kyber512") { 3 } else { 2 };
+pub const KYBER_ETA1: usize = Params::<KYBER_K>::ETA1;
 pub const KYBER_ETA2: usize = 2;
 
 // Size of the hashes and seeds
@@ -35,26 +48,439 @@ pub const KYBER_SYMBYTES: usize = 32;
 pub const KYBER_SSBYTES: usize = 32;
 
 pub const KYBER_POLYBYTES: usize = 384;
-pub const KYBER_POLYVECBYTES: usize = KYBER_K * KYBER_POLYBYTES;
+pub const KYBER_POLYVECBYTES: usize = Params::<KYBER_K>::POLYVECBYTES;
 
-#[cfg(not(feature = "kyber1024"))]
-pub const KYBER_POLYCOMPRESSEDBYTES: usize = 128;
-#[cfg(not(feature = "kyber1024"))]
-pub const KYBER_POLYVECCOMPRESSEDBYTES: usize = KYBER_K * 320;
-
-#[cfg(feature = "kyber1024")]
-pub const KYBER_POLYCOMPRESSEDBYTES: usize = 160;
-#[cfg(feature = "kyber1024")]
-pub const KYBER_POLYVECCOMPRESSEDBYTES: usize = KYBER_K * 352;
+pub const KYBER_POLYCOMPRESSEDBYTES: usize = Params::<KYBER_K>::POLYCOMPRESSEDBYTES;
+pub const KYBER_POLYVECCOMPRESSEDBYTES: usize = Params::<KYBER_K>::POLYVECCOMPRESSEDBYTES;
 
-pub const KYBER_INDCPA_PUBLICKEYBYTES: usize = KYBER_POLYVECBYTES + KYBER_SYMBYTES;
-pub const KYBER_INDCPA_SECRETKEYBYTES: usize = KYBER_POLYVECBYTES;
-pub const KYBER_INDCPA_BYTES: usize = KYBER_POLYVECCOMPRESSEDBYTES + KYBER_POLYCOMPRESSEDBYTES;
+pub const KYBER_INDCPA_PUBLICKEYBYTES: usize = Params::<KYBER_K>::INDCPA_PUBLICKEYBYTES;
+pub const KYBER_INDCPA_SECRETKEYBYTES: usize = Params::<KYBER_K>::INDCPA_SECRETKEYBYTES;
+pub const KYBER_INDCPA_BYTES: usize = Params::<KYBER_K>::INDCPA_BYTES;
 
 /// Size in bytes of the Kyber public key
-pub const KYBER_PUBLICKEYBYTES: usize = KYBER_INDCPA_PUBLICKEYBYTES;
+pub const KYBER_PUBLICKEYBYTES: usize = Params::<KYBER_K>::PUBLICKEYBYTES;
 /// Size in bytes of the Kyber secret key
-pub const KYBER_SECRETKEYBYTES: usize =
-    KYBER_INDCPA_SECRETKEYBYTES + KYBER_INDCPA_PUBLICKEYBYTES + 2 * KYBER_SYMBYTES;
+pub const KYBER_SECRETKEYBYTES: usize = Params::<KYBER_K>::SECRETKEYBYTES;
 /// Size in bytes of the Kyber ciphertext
-pub const KYBER_CIPHERTEXTBYTES: usize = KYBER_INDCPA_BYTES;
+pub const KYBER_CIPHERTEXTBYTES: usize = Params::<KYBER_K>::CIPHERTEXTBYTES;
+
+// Largest supported module rank, sizes buffers shared by all security levels
+pub const KYBER_K_MAX: usize = 4;
+pub const KYBER_ETA1_MAX: usize = 3;
+pub const KYBER_CIPHERTEXTBYTES_MAX: usize = Params::<KYBER_K_MAX>::CIPHERTEXTBYTES;
+
+/// Sizes derived from the module rank `K`, used by the code shared between
+/// all security levels.
+pub struct Params<const K: usize>;
+
+impl<const K: usize> Params<K> {
+    pub const ETA1: usize = if K == 2 { 3 } else { 2 };
+    pub const POLYVECBYTES: usize = K * KYBER_POLYBYTES;
+    pub const POLYCOMPRESSEDBYTES: usize = if K == 4 { 160 } else { 128 };
+    pub const POLYVECCOMPRESSEDBYTES: usize = K * if K == 4 { 352 } else { 320 };
+    pub const INDCPA_PUBLICKEYBYTES: usize = Self::POLYVECBYTES + KYBER_SYMBYTES;
+    pub const INDCPA_SECRETKEYBYTES: usize = Self::POLYVECBYTES;
+    pub const INDCPA_BYTES: usize = Self::POLYVECCOMPRESSEDBYTES + Self::POLYCOMPRESSEDBYTES;
+    pub const PUBLICKEYBYTES: usize = Self::INDCPA_PUBLICKEYBYTES;
+    pub const SECRETKEYBYTES: usize =
+        Self::INDCPA_SECRETKEYBYTES + Self::INDCPA_PUBLICKEYBYTES + 2 * KYBER_SYMBYTES;
+    pub const CIPHERTEXTBYTES: usize = Self::INDCPA_BYTES;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Fixed size byte arrays used for keys, ciphertexts and key exchange messages.
+///
+/// Implemented for `[u8; N]`, this trait is sealed.
+#[cfg(not(feature = "zeroize"))]
+pub trait ByteArray:
+    AsRef<[u8]> + AsMut<[u8]> + Copy + Clone + Debug + Eq + PartialEq + sealed::Sealed
+{
+    /// An all zero array
+    fn zeroed() -> Self;
+}
+
+/// Fixed size byte arrays used for keys, ciphertexts and key exchange messages.
+///
+/// Implemented for `[u8; N]`, this trait is sealed.
+#[cfg(feature = "zeroize")]
+pub trait ByteArray:
+    AsRef<[u8]> + AsMut<[u8]> + Copy + Clone + Debug + Eq + PartialEq + Zeroize + sealed::Sealed
+{
+    /// An all zero array
+    fn zeroed() -> Self;
+}
+
+impl<const N: usize> sealed::Sealed for [u8; N] {}
+
+impl<const N: usize> ByteArray for [u8; N] {
+    fn zeroed() -> Self {
+        [0u8; N]
+    }
+}
+
+/// A Kyber parameter set, allowing several security levels to be used side
+/// by side in a single build.
+///
+/// Implemented by [`Kyber512`], [`Kyber768`] and [`Kyber1024`]. The level
+/// selected with feature flags is [`DefaultParams`], which is what the free
+/// functions such as [`keypair`](crate::keypair) use.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = Kyber1024::keypair(&mut rng)?;
+/// let (ct, ss1) = Kyber1024::encapsulate(&keys.public, &mut rng)?;
+/// let ss2 = Kyber1024::decapsulate(&ct, &keys.secret)?;
+/// assert_eq!(ss1, ss2);
+///
+/// let keys = Kyber512::keypair(&mut rng)?;
+/// assert_eq!(keys.public.as_ref().len(), Kyber512::PUBLICKEYBYTES);
+/// # Ok(()) }
+/// ```
+///
+/// With the `avx2` feature only [`DefaultParams`] uses the optimised backend,
+/// other levels use the reference code.
+pub trait KyberParams:
+    Copy + Clone + Debug + Default + Eq + PartialEq + Hash + sealed::Sealed
+{
+    /// The module rank, 2, 3 or 4
+    const K: usize;
+    /// Size in bytes of the public key
+    const PUBLICKEYBYTES: usize;
+    /// Size in bytes of the secret key
+    const SECRETKEYBYTES: usize;
+    /// Size in bytes of the ciphertext
+    const CIPHERTEXTBYTES: usize;
+
+    /// Public key byte array
+    type PublicKey: ByteArray;
+    /// Secret key byte array
+    type SecretKey: ByteArray;
+    /// Ciphertext byte array
+    type Ciphertext: ByteArray;
+    /// Bytes to send when initiating a unilateral key exchange
+    type UakeSendInit: ByteArray;
+    /// Bytes to send when responding to a unilateral key exchange
+    type UakeSendResponse: ByteArray;
+    /// Bytes to send when initiating a mutual key exchange
+    type AkeSendInit: ByteArray;
+    /// Bytes to send when responding to a mutual key exchange
+    type AkeSendResponse: ByteArray;
+
+    #[doc(hidden)]
+    type ExpandedPublicKey: Clone + Default;
+
+    #[doc(hidden)]
+    type ExpandedSecretKey: Clone + Default;
+
+    #[doc(hidden)]
+    fn crypto_kem_keypair<R: RngCore + CryptoRng>(
+        pk: &mut [u8],
+        sk: &mut [u8],
+        rng: &mut R,
+        seed: Option<(&[u8], &[u8])>,
+    ) -> Result<(), KyberError>;
+
+    #[doc(hidden)]
+    fn crypto_kem_enc<R: RngCore + CryptoRng>(
+        ct: &mut [u8],
+        ss: &mut [u8],
+        pk: &[u8],
+        rng: &mut R,
+        seed: Option<&[u8]>,
+    ) -> Result<(), KyberError>;
+
+    #[doc(hidden)]
+    fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> u8;
+
+    #[doc(hidden)]
+    fn crypto_kem_enc_prekey<R: RngCore + CryptoRng>(
+        ct: &mut [u8],
+        kr: &mut [u8],
+        pk: &[u8],
+        rng: &mut R,
+    ) -> Result<(), KyberError>;
+
+    #[doc(hidden)]
+    fn crypto_kem_dec_prekey(kr: &mut [u8], ct: &[u8], sk: &[u8]) -> u8;
+
+    #[doc(hidden)]
+    fn expand_public_key(exp: &mut Self::ExpandedPublicKey, hpk: &mut [u8], pk: &[u8]);
+
+    #[doc(hidden)]
+    fn crypto_kem_enc_expanded<R: RngCore + CryptoRng>(
+        ct: &mut [u8],
+        ss: &mut [u8],
+        exp: &Self::ExpandedPublicKey,
+        hpk: &[u8],
+        rng: &mut R,
+    ) -> Result<(), KyberError>;
+
+    #[doc(hidden)]
+    fn expand_secret_key(
+        pk_exp: &mut Self::ExpandedPublicKey,
+        sk_exp: &mut Self::ExpandedSecretKey,
+        sk: &[u8],
+    );
+
+    #[doc(hidden)]
+    fn crypto_kem_dec_expanded(
+        ss: &mut [u8],
+        ct: &[u8],
+        pk_exp: &Self::ExpandedPublicKey,
+        sk_exp: &Self::ExpandedSecretKey,
+        sk: &[u8],
+    );
+
+    #[doc(hidden)]
+    fn check_public_key(pk: &[u8]) -> Result<(), KyberError>;
+
+    #[doc(hidden)]
+    fn check_secret_key(sk: &[u8]) -> Result<(), KyberError>;
+
+    /// Keypair generation with a provided RNG.
+    fn keypair<R: RngCore + CryptoRng>(rng: &mut R) -> Result<Keypair<Self>, KyberError> {
+        let mut public = PublicKey::zeroed();
+        let mut secret = SecretKey::zeroed();
+        Self::crypto_kem_keypair(public.as_mut(), secret.as_mut(), rng, None)?;
+        Ok(Keypair { public, secret })
+    }
+
+    /// Encapsulates a public key returning the ciphertext to send
+    /// and the shared secret
+    ///
+    /// Public keys encoding coefficients that are not reduced modulo q are
+    /// rejected with [`KyberError::InvalidPublicKey`].
+    fn encapsulate<R: RngCore + CryptoRng>(
+        pk: &PublicKey<Self>,
+        rng: &mut R,
+    ) -> Result<(Ciphertext<Self>, SharedSecret), KyberError> {
+        Self::check_public_key(pk.as_ref())?;
+        let mut ct = Ciphertext::zeroed();
+        let mut ss = SharedSecret::zeroed();
+        Self::crypto_kem_enc(ct.as_mut(), ss.as_mut(), pk.as_ref(), rng, None)?;
+        Ok((ct, ss))
+    }
+
+    /// Decapsulates ciphertext with a secret key
+    ///
+    /// Secret keys whose stored public key hash does not match are rejected
+    /// with [`KyberError::InvalidSecretKey`].
+    fn decapsulate(
+        ct: &Ciphertext<Self>,
+        sk: &SecretKey<Self>,
+    ) -> Result<SharedSecret, KyberError> {
+        Self::check_secret_key(sk.as_ref())?;
+        let mut ss = SharedSecret::zeroed();
+        Self::crypto_kem_dec(ss.as_mut(), ct.as_ref(), sk.as_ref());
+        Ok(ss)
+    }
+
+    /// Encapsulates a public key, deriving `out.len()` bytes of key material
+    /// bound to `context` instead of the 32 byte shared secret
+    ///
+    /// See [`encapsulate_with_context`](crate::encapsulate_with_context).
+    fn encapsulate_with_context<R: RngCore + CryptoRng>(
+        pk: &PublicKey<Self>,
+        context: &[u8],
+        out: &mut [u8],
+        rng: &mut R,
+    ) -> Result<Ciphertext<Self>, KyberError> {
+        Self::check_public_key(pk.as_ref())?;
+        let mut ct = Ciphertext::zeroed();
+        let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+        Self::crypto_kem_enc_prekey(ct.as_mut(), &mut kr, pk.as_ref(), rng)?;
+        let res = kdf_context(out, &kr, context);
+        #[cfg(feature = "zeroize")]
+        kr.zeroize();
+        res.map(|_| ct)
+    }
+
+    /// Decapsulates ciphertext with a secret key, deriving `out.len()` bytes
+    /// of key material bound to `context`
+    ///
+    /// See [`decapsulate_with_context`](crate::decapsulate_with_context).
+    fn decapsulate_with_context(
+        ct: &Ciphertext<Self>,
+        sk: &SecretKey<Self>,
+        context: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), KyberError> {
+        Self::check_secret_key(sk.as_ref())?;
+        let mut kr = [0u8; 2 * KYBER_SYMBYTES];
+        Self::crypto_kem_dec_prekey(&mut kr, ct.as_ref(), sk.as_ref());
+        let res = kdf_context(out, &kr, context);
+        #[cfg(feature = "zeroize")]
+        kr.zeroize();
+        res
+    }
+
+    /// Decapsulates ciphertext with a secret key, also reporting whether
+    /// the ciphertext passed the re-encryption check
+    ///
+    /// The [`Choice`] is 1 for a valid ciphertext. An invalid ciphertext
+    /// still returns the implicit rejection secret, as [`decapsulate`]
+    /// does, and the whole call runs in constant time. Branching on the
+    /// `Choice` tells an attacker whether a ciphertext was valid.
+    ///
+    /// [`decapsulate`]: KyberParams::decapsulate
+    fn decapsulate_checked(
+        ct: &Ciphertext<Self>,
+        sk: &SecretKey<Self>,
+    ) -> Result<(SharedSecret, Choice), KyberError> {
+        Self::check_secret_key(sk.as_ref())?;
+        let mut ss = SharedSecret::zeroed();
+        let fail = Self::crypto_kem_dec(ss.as_mut(), ct.as_ref(), sk.as_ref());
+        Ok((ss, Choice::from(1 ^ fail)))
+    }
+
+    /// Decapsulates ciphertext with a secret key, explicitly rejecting
+    /// invalid ciphertexts with [`KyberError::Decapsulation`]
+    ///
+    /// Opt-in for protocols specified with explicit rejection, the error
+    /// reveals which ciphertexts were invalid.
+    fn decapsulate_explicit(
+        ct: &Ciphertext<Self>,
+        sk: &SecretKey<Self>,
+    ) -> Result<SharedSecret, KyberError> {
+        let (ss, valid) = Self::decapsulate_checked(ct, sk)?;
+        if bool::from(valid) {
+            Ok(ss)
+        } else {
+            Err(KyberError::Decapsulation)
+        }
+    }
+}
+
+macro_rules! kyber_params {
+    ($(#[$doc:meta])* $name:ident, $k:literal) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+        pub struct $name;
+
+        impl sealed::Sealed for $name {}
+
+        impl KyberParams for $name {
+            const K: usize = $k;
+            const PUBLICKEYBYTES: usize = Params::<$k>::PUBLICKEYBYTES;
+            const SECRETKEYBYTES: usize = Params::<$k>::SECRETKEYBYTES;
+            const CIPHERTEXTBYTES: usize = Params::<$k>::CIPHERTEXTBYTES;
+
+            type PublicKey = [u8; Params::<$k>::PUBLICKEYBYTES];
+            type SecretKey = [u8; Params::<$k>::SECRETKEYBYTES];
+            type Ciphertext = [u8; Params::<$k>::CIPHERTEXTBYTES];
+            type UakeSendInit = [u8; Params::<$k>::PUBLICKEYBYTES + Params::<$k>::CIPHERTEXTBYTES];
+            type UakeSendResponse = [u8; Params::<$k>::CIPHERTEXTBYTES];
+            type AkeSendInit = [u8; Params::<$k>::PUBLICKEYBYTES + Params::<$k>::CIPHERTEXTBYTES];
+            type AkeSendResponse = [u8; 2 * Params::<$k>::CIPHERTEXTBYTES];
+            type ExpandedPublicKey = ExpandedPk<$k>;
+            type ExpandedSecretKey = ExpandedSk<$k>;
+
+            fn crypto_kem_keypair<R: RngCore + CryptoRng>(
+                pk: &mut [u8],
+                sk: &mut [u8],
+                rng: &mut R,
+                seed: Option<(&[u8], &[u8])>,
+            ) -> Result<(), KyberError> {
+                kem::kem_keypair::<$k, R>(pk, sk, rng, seed)
+            }
+
+            fn crypto_kem_enc<R: RngCore + CryptoRng>(
+                ct: &mut [u8],
+                ss: &mut [u8],
+                pk: &[u8],
+                rng: &mut R,
+                seed: Option<&[u8]>,
+            ) -> Result<(), KyberError> {
+                kem::kem_enc::<$k, R>(ct, ss, pk, rng, seed)
+            }
+
+            fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> u8 {
+                kem::kem_dec::<$k>(ss, ct, sk)
+            }
+
+            fn crypto_kem_enc_prekey<R: RngCore + CryptoRng>(
+                ct: &mut [u8],
+                kr: &mut [u8],
+                pk: &[u8],
+                rng: &mut R,
+            ) -> Result<(), KyberError> {
+                kem::kem_enc_prekey::<$k, R>(ct, kr, pk, rng, None)
+            }
+
+            fn crypto_kem_dec_prekey(kr: &mut [u8], ct: &[u8], sk: &[u8]) -> u8 {
+                kem::kem_dec_prekey::<$k>(kr, ct, sk)
+            }
+
+            fn expand_public_key(exp: &mut ExpandedPk<$k>, hpk: &mut [u8], pk: &[u8]) {
+                kem::kem_expand_pk::<$k>(exp, hpk, pk)
+            }
+
+            fn crypto_kem_enc_expanded<R: RngCore + CryptoRng>(
+                ct: &mut [u8],
+                ss: &mut [u8],
+                exp: &ExpandedPk<$k>,
+                hpk: &[u8],
+                rng: &mut R,
+            ) -> Result<(), KyberError> {
+                kem::kem_enc_expanded::<$k, R>(ct, ss, exp, hpk, rng)
+            }
+
+            fn expand_secret_key(
+                pk_exp: &mut ExpandedPk<$k>,
+                sk_exp: &mut ExpandedSk<$k>,
+                sk: &[u8],
+            ) {
+                kem::kem_expand_sk::<$k>(pk_exp, sk_exp, sk)
+            }
+
+            fn crypto_kem_dec_expanded(
+                ss: &mut [u8],
+                ct: &[u8],
+                pk_exp: &ExpandedPk<$k>,
+                sk_exp: &ExpandedSk<$k>,
+                sk: &[u8],
+            ) {
+                kem::kem_dec_expanded::<$k>(ss, ct, pk_exp, sk_exp, sk)
+            }
+
+            fn check_public_key(pk: &[u8]) -> Result<(), KyberError> {
+                kem::kem_check_pk::<$k>(pk)
+            }
+
+            fn check_secret_key(sk: &[u8]) -> Result<(), KyberError> {
+                kem::kem_check_sk::<$k>(sk)
+            }
+        }
+    };
+}
+
+kyber_params!(
+    /// Kyber512 parameter set, roughly equivalent to AES-128
+    Kyber512,
+    2
+);
+kyber_params!(
+    /// Kyber768 parameter set, roughly equivalent to AES-192
+    Kyber768,
+    3
+);
+kyber_params!(
+    /// Kyber1024 parameter set, roughly equivalent to AES-256
+    Kyber1024,
+    4
+);
+
+/// The parameter set chosen with the security level features
+#[cfg(feature = "kyber512")]
+pub type DefaultParams = Kyber512;
+/// The parameter set chosen with the security level features
+#[cfg(not(any(feature = "kyber512", feature = "kyber1024")))]
+pub type DefaultParams = Kyber768;
+/// The parameter set chosen with the security level features
+#[cfg(feature = "kyber1024")]
+pub type DefaultParams = Kyber1024;
@@ -0,0 +1,179 @@
+//! Keys expanded once for repeated use.
+//!
+//! Encapsulation unpacks the public key, generates the matrix A with
+//! rejection sampling on SHAKE128 and hashes the public key on every call.
+//! Decapsulation also unpacks the secret key and redoes all of that for the
+//! re-encryption check. [`PreparedPublicKey`] and [`PreparedSecretKey`] do
+//! that work once.
+use crate::{
+    params::*, Ciphertext, CryptoRng, KyberError, PublicKey, RngCore, SecretKey, SharedSecret,
+};
+use core::fmt;
+#[cfg(feature = "zeroize")]
+use zeroize::ZeroizeOnDrop;
+
+/// A public key with its matrix A, vector of polynomials and hash
+/// precomputed, for fast repeated encapsulation.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let server = PreparedPublicKey::new(&keys.public)?;
+/// for _ in 0..3 {
+///     let (ct, ss1) = server.encapsulate(&mut rng)?;
+///     let ss2 = decapsulate(&ct, &keys.secret)?;
+///     assert_eq!(ss1, ss2);
+/// }
+/// # Ok(()) }
+/// ```
+pub struct PreparedPublicKey<P: KyberParams = DefaultParams> {
+    public: PublicKey<P>,
+    expanded: P::ExpandedPublicKey,
+    hpk: [u8; KYBER_SYMBYTES],
+}
+
+impl<P: KyberParams> PreparedPublicKey<P> {
+    /// Expands a public key.
+    ///
+    /// The FIPS 203 modulus check is done here once, public keys encoding
+    /// coefficients that are not reduced modulo q fail with
+    /// [`KyberError::InvalidPublicKey`].
+    pub fn new(pk: &PublicKey<P>) -> Result<Self, KyberError> {
+        P::check_public_key(pk.as_ref())?;
+        let mut prepared = PreparedPublicKey {
+            public: *pk,
+            expanded: P::ExpandedPublicKey::default(),
+            hpk: [0u8; KYBER_SYMBYTES],
+        };
+        P::expand_public_key(&mut prepared.expanded, &mut prepared.hpk, pk.as_ref());
+        Ok(prepared)
+    }
+
+    /// The public key this was expanded from
+    pub fn public_key(&self) -> &PublicKey<P> {
+        &self.public
+    }
+
+    /// Encapsulates to the public key returning the ciphertext to send
+    /// and the shared secret, the same as [`KyberParams::encapsulate`]
+    pub fn encapsulate<R>(&self, rng: &mut R) -> Result<(Ciphertext<P>, SharedSecret), KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut ct = Ciphertext::zeroed();
+        let mut ss = SharedSecret::zeroed();
+        P::crypto_kem_enc_expanded(ct.as_mut(), ss.as_mut(), &self.expanded, &self.hpk, rng)?;
+        Ok((ct, ss))
+    }
+}
+
+impl<P: KyberParams> Clone for PreparedPublicKey<P> {
+    fn clone(&self) -> Self {
+        PreparedPublicKey {
+            public: self.public,
+            expanded: self.expanded.clone(),
+            hpk: self.hpk,
+        }
+    }
+}
+
+impl<P: KyberParams> fmt::Debug for PreparedPublicKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PreparedPublicKey")
+            .field(&self.public)
+            .finish()
+    }
+}
+
+/// A secret key with its NTT domain secret vector, the matrix A and vector
+/// of polynomials of its public key and the public key hash precomputed,
+/// for fast repeated decapsulation.
+///
+/// Zeroized on drop with the `zeroize` feature.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let server = PreparedSecretKey::new(&keys.secret)?;
+/// for _ in 0..3 {
+///     let (ct, ss1) = encapsulate(&keys.public, &mut rng)?;
+///     let ss2 = server.decapsulate(&ct);
+///     assert_eq!(ss1, ss2);
+/// }
+/// # Ok(()) }
+/// ```
+pub struct PreparedSecretKey<P: KyberParams = DefaultParams> {
+    secret: SecretKey<P>,
+    expanded_pk: P::ExpandedPublicKey,
+    expanded_sk: P::ExpandedSecretKey,
+}
+
+impl<P: KyberParams> PreparedSecretKey<P> {
+    /// Expands a secret key.
+    ///
+    /// The FIPS 203 hash check is done here once, secret keys whose stored
+    /// public key hash does not match fail with
+    /// [`KyberError::InvalidSecretKey`].
+    // Inlined so the expanded key is built in the caller's frame, returning
+    // it from a call would leave a copy of the secret vector on the stack
+    #[inline(always)]
+    pub fn new(sk: &SecretKey<P>) -> Result<Self, KyberError> {
+        P::check_secret_key(sk.as_ref())?;
+        let mut prepared = PreparedSecretKey {
+            secret: sk.clone(),
+            expanded_pk: P::ExpandedPublicKey::default(),
+            expanded_sk: P::ExpandedSecretKey::default(),
+        };
+        P::expand_secret_key(
+            &mut prepared.expanded_pk,
+            &mut prepared.expanded_sk,
+            sk.as_ref(),
+        );
+        Ok(prepared)
+    }
+
+    /// The secret key this was expanded from
+    pub fn secret_key(&self) -> &SecretKey<P> {
+        &self.secret
+    }
+
+    /// Decapsulates a ciphertext, the same as [`KyberParams::decapsulate`]
+    ///
+    /// Invalid ciphertexts are implicitly rejected, the returned shared secret
+    /// is then a pseudo-random value unknown to the sender.
+    pub fn decapsulate(&self, ct: &Ciphertext<P>) -> SharedSecret {
+        let mut ss = SharedSecret::zeroed();
+        P::crypto_kem_dec_expanded(
+            ss.as_mut(),
+            ct.as_ref(),
+            &self.expanded_pk,
+            &self.expanded_sk,
+            self.secret.as_ref(),
+        );
+        ss
+    }
+}
+
+impl<P: KyberParams> Clone for PreparedSecretKey<P> {
+    fn clone(&self) -> Self {
+        PreparedSecretKey {
+            secret: self.secret.clone(),
+            expanded_pk: self.expanded_pk.clone(),
+            expanded_sk: self.expanded_sk.clone(),
+        }
+    }
+}
+
+impl<P: KyberParams> fmt::Debug for PreparedSecretKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PreparedSecretKey(<redacted>)")
+    }
+}
+
+// The secret key and the expanded secret vector both zeroize themselves
+#[cfg(feature = "zeroize")]
+impl<P: KyberParams> ZeroizeOnDrop for PreparedSecretKey<P> {}
@@ -27,6 +27,9 @@
  */
 #![cfg(feature = "90s")]
 
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
 pub struct Aes256CtrCtx {
     pub sk_exp: [u64; 120],
     pub ivw: [u32; 16],
@@ -421,7 +424,10 @@ fn sub_word(x: u32) -> u32 {
     br_aes_ct64_ortho(&mut q);
     br_aes_ct64_bitslice_sbox(&mut q);
     br_aes_ct64_ortho(&mut q);
-    q[0] as u32
+    let r = q[0] as u32;
+    #[cfg(feature = "zeroize")]
+    q.zeroize();
+    r
 }
 
 const RCON: [u32; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1B, 0x36];
@@ -474,7 +480,11 @@ fn br_aes_ct64_keysched(comp_skey: &mut [u64], key: &[u8]) {
             | (q[6] & 0x4444444444444444)
             | (q[7] & 0x8888888888888888);
         j += 2;
+        #[cfg(feature = "zeroize")]
+        q.zeroize();
     }
+    #[cfg(feature = "zeroize")]
+    skey.zeroize();
 }
 
 fn br_aes_ct64_skey_expand(skey: &mut [u64], comp_skey: &[u64]) {
@@ -592,6 +602,11 @@ fn aes_ctr4x(out: &mut [u8], ivw: &mut [u32], sk_exp: &[u64]) {
         br_aes_ct64_interleave_out(&mut w[(i << 2)..], q[i], q[i + 4]);
     }
     br_range_enc32le(out, &w, 16);
+    #[cfg(feature = "zeroize")]
+    {
+        w.zeroize();
+        q.zeroize();
+    }
 
     /* Increase counter for next 4 blocks */
     ivw[3] = inc4_be(ivw[3]);
@@ -604,6 +619,8 @@ fn br_aes_ct64_ctr_init(sk_exp: &mut [u64], key: &[u8]) {
     let mut skey = [0u64; 30];
     br_aes_ct64_keysched(&mut skey, key);
     br_aes_ct64_skey_expand(sk_exp, &skey);
+    #[cfg(feature = "zeroize")]
+    skey.zeroize();
 }
 
 #[cfg(not(feature = "90s-fixslice"))]
@@ -629,7 +646,9 @@ fn br_aes_ct64_ctr_run(sk_exp: &mut [u64], iv: &[u8], cc: u32, data: &mut [u8],
     if len > 0 {
         let mut tmp = [0u8; 64];
         aes_ctr4x(&mut tmp, &mut ivw, sk_exp);
-        data[idx..].copy_from_slice(&tmp[..len])
+        data[idx..].copy_from_slice(&tmp[..len]);
+        #[cfg(feature = "zeroize")]
+        tmp.zeroize();
     }
 }
 
@@ -649,6 +668,8 @@ pub fn aes256ctr_prf(output: &mut [u8], outlen: usize, key: &[u8], nonce: u8) {
     pad_nonce[0] = nonce;
     br_aes_ct64_ctr_init(&mut sk_exp, key);
     br_aes_ct64_ctr_run(&mut sk_exp, &pad_nonce, 0, output, outlen);
+    #[cfg(feature = "zeroize")]
+    sk_exp.zeroize();
 }
 
 /// Name:  aes256ctr_init
@@ -1,5 +1,5 @@
-use crate::params::KYBER_N;
-use crate::poly::Poly;
+use super::poly::Poly;
+use crate::params::{Params, KYBER_N};
 
 /// Name:  load32_littleendian
 ///
@@ -78,8 +78,8 @@ pub fn cbd3(r: &mut Poly, buf: &[u8]) {
     }
 }
 
-pub fn poly_cbd_eta1(r: &mut Poly, buf: &[u8]) {
-    if cfg!(feature = "kyber512") {
+pub fn poly_cbd_eta1<const K: usize>(r: &mut Poly, buf: &[u8]) {
+    if Params::<K>::ETA1 == 3 {
         cbd3(r, buf)
     } else {
         cbd2(r, buf)
@@ -1,9 +1,11 @@
 #![allow(clippy::needless_range_loop, dead_code)]
 
 use crate::symmetric::KeccakState;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 pub const SHAKE128_RATE: usize = 168;
-const SHAKE256_RATE: usize = 136;
+pub const SHAKE256_RATE: usize = 136;
 const SHA3_256_RATE: usize = 136;
 const SHA3_512_RATE: usize = 72;
 const NROUNDS: usize = 24;
@@ -373,6 +375,8 @@ pub fn shake256(out: &mut [u8], mut outlen: usize, input: &[u8], inlen: usize) {
     outlen -= nblocks * SHAKE256_RATE;
     idx += nblocks * SHAKE256_RATE;
     shake256_squeeze(&mut out[idx..], outlen, &mut state);
+    #[cfg(feature = "zeroize")]
+    state.zeroize();
 }
 
 /// Name:  sha3_256
@@ -389,6 +393,8 @@ pub fn sha3_256(h: &mut [u8], input: &[u8], inlen: usize) {
     for i in 0..4 {
         store64(&mut h[8 * i..], s[i]);
     }
+    #[cfg(feature = "zeroize")]
+    s.zeroize();
 }
 
 /// Name:  sha3_512
@@ -405,6 +411,8 @@ pub fn sha3_512(h: &mut [u8], input: &[u8], inlen: usize) {
     for i in 0..8 {
         store64(&mut h[8 * i..], s[i]);
     }
+    #[cfg(feature = "zeroize")]
+    s.zeroize();
 }
 
 /// Name:  keccak_finalize
@@ -416,7 +424,7 @@ pub fn sha3_512(h: &mut [u8], input: &[u8], inlen: usize) {
 ///  - usize r: rate in bytes (e.g., 168 for SHAKE128)
 ///  - u8 p: domain separation byte
 fn keccak_finalize(s: &mut [u64], pos: usize, r: usize, p: u8) {
-    s[pos / 8] ^= (p as u64) << 8 * (pos % 8);
+    s[pos / 8] ^= (p as u64) << (8 * (pos % 8));
     s[r / 8 - 1] ^= 1u64 << 63;
 }
 
@@ -445,9 +453,9 @@ pub fn keccak_absorb_once(s: &mut [u64], r: usize, input: &[u8], mut inlen: usiz
     }
 
     for i in 0..inlen {
-        s[i / 8] ^= (input[idx + i] as u64) << 8 * (i % 8);
+        s[i / 8] ^= (input[idx + i] as u64) << (8 * (i % 8));
     }
-    s[inlen / 8] ^= (p as u64) << 8 * (inlen % 8);
+    s[inlen / 8] ^= (p as u64) << (8 * (inlen % 8));
     s[(r - 1) / 8] ^= 1u64 << 63;
 }
 
@@ -460,8 +468,9 @@ pub fn keccak_absorb_once(s: &mut [u64], r: usize, input: &[u8], mut inlen: usiz
 /// Arguments:   - [u8] out:   output blocks
 ///  - u64 nblocks:  number of blocks to be squeezed (written to out)
 ///  - u64 *s:   in/output Keccak state
-///  usize pos: number of bytes in current block already squeezed
+///  - usize pos: number of bytes in current block already squeezed
 ///  - usize r:  rate in bytes (e.g., 168 for SHAKE128)
+///
 /// Returns new position pos in current block
 pub fn keccak_squeeze(
     out: &mut [u8],
@@ -477,12 +486,10 @@ pub fn keccak_squeeze(
             pos = 0
         }
         let mut i = pos;
-        let mut w = i / 8;
         while i < r && i < pos + outlen {
-            store64(&mut out[idx..], s[w]);
-            i += 8;
-            w += 1;
-            idx += 8;
+            out[idx] = (s[i / 8] >> (8 * (i % 8))) as u8;
+            i += 1;
+            idx += 1;
         }
         outlen -= i - pos;
         pos = i;
@@ -490,6 +497,35 @@ pub fn keccak_squeeze(
     pos
 }
 
+/// Name:  keccak_absorb
+///
+/// Description: Absorb step of Keccak; incremental.
+///
+/// Arguments:   - u64 *s:   pointer to Keccak state
+///  - usize pos: position in current block to be absorbed
+///  - usize r:  rate in bytes (e.g., 168 for SHAKE128)
+///  - const [u8] input:  input to be absorbed into s
+///
+/// Returns new position pos in current block
+fn keccak_absorb(s: &mut [u64], mut pos: usize, r: usize, input: &[u8]) -> usize {
+    let mut idx = 0;
+    let mut inlen = input.len();
+    while pos + inlen >= r {
+        for i in pos..r {
+            s[i / 8] ^= (input[idx] as u64) << (8 * (i % 8));
+            idx += 1;
+        }
+        inlen -= r - pos;
+        keccakf1600_statepermute(s);
+        pos = 0;
+    }
+    for i in pos..pos + inlen {
+        s[i / 8] ^= (input[idx] as u64) << (8 * (i % 8));
+        idx += 1;
+    }
+    pos + inlen
+}
+
 /// Name:  shake128_init
 ///
 /// Description: Initilizes Keccak state for use as SHAKE128 XOF
@@ -533,20 +569,30 @@ pub fn shake128_absorb_once(state: &mut KeccakState, input: &[u8], inlen: usize)
     state.pos = SHAKE128_RATE;
 }
 
-fn shake256_init(state: &mut KeccakState) {
+pub fn shake256_init(state: &mut KeccakState) {
     state.reset();
 }
 
-fn shake256_finalize(state: &mut KeccakState) {
+/// Name:  shake256_absorb
+///
+/// Description: Absorb step of the SHAKE256 XOF; incremental.
+///
+/// Arguments:   - keccak_state state: pointer to (initialized) output Keccak state
+///  - const [u8] input: input to be absorbed into s
+pub fn shake256_absorb(state: &mut KeccakState, input: &[u8]) {
+    state.pos = keccak_absorb(&mut state.s, state.pos, SHAKE256_RATE, input);
+}
+
+pub fn shake256_finalize(state: &mut KeccakState) {
     keccak_finalize(&mut state.s, state.pos, SHAKE256_RATE, 0x1F);
     state.pos = SHAKE256_RATE;
 }
 
-fn shake256_squeeze(out: &mut [u8], outlen: usize, state: &mut KeccakState) {
+pub fn shake256_squeeze(out: &mut [u8], outlen: usize, state: &mut KeccakState) {
     state.pos = keccak_squeeze(out, outlen, &mut state.s, state.pos, SHAKE256_RATE);
 }
 
-fn shake256_absorb_once(state: &mut KeccakState, input: &[u8], inlen: usize) {
+pub fn shake256_absorb_once(state: &mut KeccakState, input: &[u8], inlen: usize) {
     keccak_absorb_once(&mut state.s, SHAKE256_RATE, input, inlen, 0x1F);
     state.pos = SHAKE256_RATE;
 }
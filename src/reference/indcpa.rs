@@ -1,5 +1,8 @@
+use super::{poly::*, polyvec::*};
 use crate::rng::randombytes;
-use crate::{params::*, poly::*, polyvec::*, symmetric::*, CryptoRng, KyberError, RngCore};
+use crate::{params::*, symmetric::*, CryptoRng, KyberError, RngCore};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 /// Name:  pack_pk
 ///
@@ -10,10 +13,10 @@ use crate::{params::*, poly::*, polyvec::*, symmetric::*, CryptoRng, KyberError,
 /// Arguments:   [u8] r:  the output serialized public key
 ///  const poly *pk:  the input public-key polynomial
 ///  const [u8] seed: the input public seed
-fn pack_pk(r: &mut [u8], pk: &mut Polyvec, seed: &[u8]) {
-    const END: usize = KYBER_SYMBYTES + KYBER_POLYVECBYTES;
+fn pack_pk<const K: usize>(r: &mut [u8], pk: &mut Polyvec<K>, seed: &[u8]) {
+    let start = Params::<K>::POLYVECBYTES;
     polyvec_tobytes(r, pk);
-    r[KYBER_POLYVECBYTES..END].copy_from_slice(&seed[..KYBER_SYMBYTES]);
+    r[start..start + KYBER_SYMBYTES].copy_from_slice(&seed[..KYBER_SYMBYTES]);
 }
 
 /// Name:  unpack_pk
@@ -24,10 +27,10 @@ fn pack_pk(r: &mut [u8], pk: &mut Polyvec, seed: &[u8]) {
 /// Arguments:   - Polyvec pk:  output public-key vector of polynomials
 ///  - [u8] seed:   output seed to generate matrix A
 ///  - const [u8] packedpk: input serialized public key
-fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
-    const END: usize = KYBER_SYMBYTES + KYBER_POLYVECBYTES;
+fn unpack_pk<const K: usize>(pk: &mut Polyvec<K>, seed: &mut [u8], packedpk: &[u8]) {
+    let start = Params::<K>::POLYVECBYTES;
     polyvec_frombytes(pk, packedpk);
-    seed[..KYBER_SYMBYTES].copy_from_slice(&packedpk[KYBER_POLYVECBYTES..END]);
+    seed[..KYBER_SYMBYTES].copy_from_slice(&packedpk[start..start + KYBER_SYMBYTES]);
 }
 
 /// Name:  pack_sk
@@ -36,7 +39,7 @@ fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
 ///
 /// Arguments: - [u8] r:  output serialized secret key
 ///  - const Polyvec sk: input vector of polynomials (secret key)
-fn pack_sk(r: &mut [u8], sk: &mut Polyvec) {
+fn pack_sk<const K: usize>(r: &mut [u8], sk: &mut Polyvec<K>) {
     polyvec_tobytes(r, sk);
 }
 
@@ -46,7 +49,7 @@ fn pack_sk(r: &mut [u8], sk: &mut Polyvec) {
 ///
 /// Arguments:   - Polyvec sk: output vector of polynomials (secret key)
 ///  - const [u8] packedsk: input serialized secret key
-fn unpack_sk(sk: &mut Polyvec, packedsk: &[u8]) {
+fn unpack_sk<const K: usize>(sk: &mut Polyvec<K>, packedsk: &[u8]) {
     polyvec_frombytes(sk, packedsk);
 }
 
@@ -59,9 +62,9 @@ fn unpack_sk(sk: &mut Polyvec, packedsk: &[u8]) {
 /// Arguments:   [u8] r:  the output serialized ciphertext
 ///  const poly *pk:  the input vector of polynomials b
 ///  const [u8] seed: the input polynomial v
-fn pack_ciphertext(r: &mut [u8], b: &mut Polyvec, v: Poly) {
-    polyvec_compress(r, *b);
-    poly_compress(&mut r[KYBER_POLYVECCOMPRESSEDBYTES..], v);
+fn pack_ciphertext<const K: usize>(r: &mut [u8], b: &mut Polyvec<K>, v: &Poly) {
+    polyvec_compress(r, b);
+    poly_compress::<K>(&mut r[Params::<K>::POLYVECCOMPRESSEDBYTES..], v);
 }
 
 /// Name:  unpack_ciphertext
@@ -72,9 +75,9 @@ fn pack_ciphertext(r: &mut [u8], b: &mut Polyvec, v: Poly) {
 /// Arguments:   - Polyvec b:   output vector of polynomials b
 ///  - poly *v:  output polynomial v
 ///  - const [u8] c:   input serialized ciphertext
-fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
+fn unpack_ciphertext<const K: usize>(b: &mut Polyvec<K>, v: &mut Poly, c: &[u8]) {
     polyvec_decompress(b, c);
-    poly_decompress(v, &c[KYBER_POLYVECCOMPRESSEDBYTES..]);
+    poly_decompress::<K>(v, &c[Params::<K>::POLYVECCOMPRESSEDBYTES..]);
 }
 
 /// Name:  rej_uniform
@@ -88,6 +91,7 @@ fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
 ///  - usize buflen:  length of input buffer in bytes
 ///
 /// Returns number of sampled 16-bit integers (at most len)
+#[allow(clippy::identity_op)]
 fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
     let (mut ctr, mut pos) = (0usize, 0usize);
     let (mut val0, mut val1);
@@ -109,11 +113,12 @@ fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
     ctr
 }
 
-fn gen_a(a: &mut [Polyvec], b: &[u8]) {
+#[cfg(not(feature = "low-stack"))]
+fn gen_a<const K: usize>(a: &mut [Polyvec<K>], b: &[u8]) {
     gen_matrix(a, b, false);
 }
 
-fn gen_at(a: &mut [Polyvec], b: &[u8]) {
+fn gen_at<const K: usize>(a: &mut [Polyvec<K>], b: &[u8]) {
     gen_matrix(a, b, true);
 }
 
@@ -127,7 +132,26 @@ fn gen_at(a: &mut [Polyvec], b: &[u8]) {
 /// Arguments:   - Polyvec a:   ouptput matrix A
 ///  - const [u8] seed: input seed
 ///  - bool transposed: boolean deciding whether A or A^T is generated
-fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
+#[allow(clippy::needless_range_loop)]
+fn gen_matrix<const K: usize>(a: &mut [Polyvec<K>], seed: &[u8], transposed: bool) {
+    for i in 0..K {
+        for j in 0..K {
+            gen_matrix_entry(&mut a[i].vec[j], seed, i, j, transposed);
+        }
+    }
+}
+
+/// Name:  gen_matrix_entry
+///
+/// Description: Deterministically generate the entry in row i and column j
+///  of matrix A (or the transpose of A) from a seed
+///
+/// Arguments:   - Poly r:   output polynomial
+///  - const [u8] seed: input seed
+///  - usize i: row of the entry
+///  - usize j: column of the entry
+///  - bool transposed: boolean deciding whether A or A^T is generated
+fn gen_matrix_entry(r: &mut Poly, seed: &[u8], i: usize, j: usize, transposed: bool) {
     let mut ctr;
     // 530 is expected number of required bytes
     const GEN_MATRIX_NBLOCKS: usize =
@@ -137,38 +161,68 @@ fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     let mut off: usize;
     let mut state = XofState::new();
 
-    for i in 0..KYBER_K {
-        for j in 0..KYBER_K {
-            if transposed {
-                xof_absorb(&mut state, seed, i as u8, j as u8);
-            } else {
-                xof_absorb(&mut state, seed, j as u8, i as u8);
-            }
-            xof_squeezeblocks(&mut buf, GEN_MATRIX_NBLOCKS, &mut state);
-            buflen = GEN_MATRIX_NBLOCKS * XOF_BLOCKBYTES;
-            ctr = rej_uniform(&mut a[i].vec[j].coeffs, KYBER_N, &buf, buflen);
-
-            while ctr < KYBER_N {
-                off = buflen % 3;
-                for k in 0..off {
-                    buf[k] = buf[buflen - off + k];
-                }
-                xof_squeezeblocks(&mut buf[off..], 1, &mut state);
-                buflen = off + XOF_BLOCKBYTES;
-                ctr += rej_uniform(&mut a[i].vec[j].coeffs[ctr..], KYBER_N - ctr, &buf, buflen);
-            }
+    if transposed {
+        xof_absorb(&mut state, seed, i as u8, j as u8);
+    } else {
+        xof_absorb(&mut state, seed, j as u8, i as u8);
+    }
+    xof_squeezeblocks(&mut buf, GEN_MATRIX_NBLOCKS, &mut state);
+    buflen = GEN_MATRIX_NBLOCKS * XOF_BLOCKBYTES;
+    ctr = rej_uniform(&mut r.coeffs, KYBER_N, &buf, buflen);
+
+    while ctr < KYBER_N {
+        off = buflen % 3;
+        for k in 0..off {
+            buf[k] = buf[buflen - off + k];
         }
+        xof_squeezeblocks(&mut buf[off..], 1, &mut state);
+        buflen = off + XOF_BLOCKBYTES;
+        ctr += rej_uniform(&mut r.coeffs[ctr..], KYBER_N - ctr, &buf, buflen);
     }
 }
 
+/// Name:  matrix_basemul_acc_montgomery
+///
+/// Description: Pointwise multiply row i of matrix A (or the transpose of A)
+///  with b and accumulate into r. The low-stack replacement of
+///  polyvec_basemul_acc_montgomery over a generated matrix, the entries
+///  of the row are generated one at a time and never stored together
+///
+/// Arguments: - poly *r:  output polynomial
+///  - const [u8] seed: input seed of matrix A
+///  - usize i: row of the matrix
+///  - bool transposed: boolean deciding whether A or A^T is used
+///  - const Polyvec b: input vector of polynomials
+#[cfg(feature = "low-stack")]
+fn matrix_basemul_acc_montgomery<const K: usize>(
+    r: &mut Poly,
+    seed: &[u8],
+    i: usize,
+    transposed: bool,
+    b: &Polyvec<K>,
+) {
+    let mut a = Poly::new();
+    let mut t = Poly::new();
+    gen_matrix_entry(&mut a, seed, i, 0, transposed);
+    poly_basemul(r, &a, &b.vec[0]);
+    for j in 1..K {
+        gen_matrix_entry(&mut a, seed, i, j, transposed);
+        poly_basemul(&mut t, &a, &b.vec[j]);
+        poly_add(r, &t);
+    }
+    poly_reduce(r);
+    #[cfg(feature = "zeroize")]
+    t.zeroize();
+}
+
 // Name:  indcpa_keypair
 //
 // Description: Generates public and private key for the CPA-secure
 //  public-key encryption scheme underlying Kyber
 //
-// Arguments: - [u8] pk: output public key (length KYBER_INDCPA_PUBLICKEYBYTES)
-//  - [u8] sk: output private key (length KYBER_INDCPA_SECRETKEYBYTES)
-pub fn indcpa_keypair<R>(
+// Arguments: - [u8] pk: output public key (length Params::<K>::INDCPA_PUBLICKEYBYTES)
+//  - [u8] sk: output private key (length Params::<K>::INDCPA_SECRETKEYBYTES)
+pub fn indcpa_keypair<const K: usize, R>(
     pk: &mut [u8],
     sk: &mut [u8],
     _seed: Option<(&[u8], &[u8])>,
@@ -177,14 +231,11 @@ pub fn indcpa_keypair<R>(
 where
     R: CryptoRng + RngCore,
 {
-    let mut a = [Polyvec::new(); KYBER_K];
-    let (mut e, mut pkpv, mut skpv) = (Polyvec::new(), Polyvec::new(), Polyvec::new());
-    let mut nonce = 0u8;
     let mut buf = [0u8; 2 * KYBER_SYMBYTES];
     let mut randbuf = [0u8; 2 * KYBER_SYMBYTES];
 
     if let Some(s) = _seed {
-        randbuf[..KYBER_SYMBYTES].copy_from_slice(&s.0);
+        randbuf[..KYBER_SYMBYTES].copy_from_slice(s.0);
     } else {
         randombytes(&mut randbuf, KYBER_SYMBYTES, _rng)?;
     }
@@ -192,14 +243,94 @@ where
     hash_g(&mut buf, &randbuf, KYBER_SYMBYTES);
 
     let (publicseed, noiseseed) = buf.split_at(KYBER_SYMBYTES);
+    indcpa_keypair_derand::<K>(pk, sk, publicseed, noiseseed);
+    #[cfg(feature = "zeroize")]
+    {
+        buf.zeroize();
+        randbuf.zeroize();
+    }
+    Ok(())
+}
+
+/// Name:  indcpa_keypair_derand
+///
+/// Description: Deterministically generates public and private key for the
+///  CPA-secure public-key encryption scheme from already expanded seeds.
+///  Low-stack variant, matrix A is generated row by row inside the
+///  matrix-vector product and e one polynomial at a time
+///
+/// Arguments: - [u8] pk: output public key (length Params::<K>::INDCPA_PUBLICKEYBYTES)
+///  - [u8] sk: output private key (length Params::<K>::INDCPA_SECRETKEYBYTES)
+///  - const [u8] publicseed: seed used to generate matrix A (length KYBER_SYMBYTES)
+///  - const [u8] noiseseed: seed used to sample s and e (length KYBER_SYMBYTES)
+#[cfg(feature = "low-stack")]
+pub fn indcpa_keypair_derand<const K: usize>(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    publicseed: &[u8],
+    noiseseed: &[u8],
+) {
+    let mut pkpv = Polyvec::<K>::new();
+    let mut skpv = Polyvec::<K>::new();
+    let mut e = Poly::new();
+
+    for i in 0..K {
+        poly_getnoise_eta1::<K>(&mut skpv.vec[i], noiseseed, i as u8);
+    }
+    polyvec_ntt(&mut skpv);
+
+    // matrix-vector multiplication, e_i uses the nonce K + i
+    for i in 0..K {
+        matrix_basemul_acc_montgomery(&mut pkpv.vec[i], publicseed, i, false, &skpv);
+        poly_tomont(&mut pkpv.vec[i]);
+        poly_getnoise_eta1::<K>(&mut e, noiseseed, (K + i) as u8);
+        poly_ntt(&mut e);
+        poly_add(&mut pkpv.vec[i], &e);
+    }
+    polyvec_reduce(&mut pkpv);
+
+    pack_sk(sk, &mut skpv);
+    pack_pk(pk, &mut pkpv, publicseed);
+    #[cfg(feature = "zeroize")]
+    {
+        skpv.zeroize();
+        e.zeroize();
+    }
+}
+
+/// Name:  indcpa_keypair_derand
+///
+/// Description: Deterministically generates public and private key for the
+///  CPA-secure public-key encryption scheme from already expanded seeds
+///
+/// Arguments: - [u8] pk: output public key (length Params::<K>::INDCPA_PUBLICKEYBYTES)
+///  - [u8] sk: output private key (length Params::<K>::INDCPA_SECRETKEYBYTES)
+///  - const [u8] publicseed: seed used to generate matrix A (length KYBER_SYMBYTES)
+///  - const [u8] noiseseed: seed used to sample s and e (length KYBER_SYMBYTES)
+#[cfg(not(feature = "low-stack"))]
+#[allow(clippy::needless_range_loop)]
+pub fn indcpa_keypair_derand<const K: usize>(
+    pk: &mut [u8],
+    sk: &mut [u8],
+    publicseed: &[u8],
+    noiseseed: &[u8],
+) {
+    let mut a = [Polyvec::<K>::new(); K];
+    let (mut e, mut pkpv, mut skpv) = (
+        Polyvec::<K>::new(),
+        Polyvec::<K>::new(),
+        Polyvec::<K>::new(),
+    );
+    let mut nonce = 0u8;
+
     gen_a(&mut a, publicseed);
 
-    for i in 0..KYBER_K {
-        poly_getnoise_eta1(&mut skpv.vec[i], noiseseed, nonce);
+    for i in 0..K {
+        poly_getnoise_eta1::<K>(&mut skpv.vec[i], noiseseed, nonce);
         nonce += 1;
     }
-    for i in 0..KYBER_K {
-        poly_getnoise_eta1(&mut e.vec[i], noiseseed, nonce);
+    for i in 0..K {
+        poly_getnoise_eta1::<K>(&mut e.vec[i], noiseseed, nonce);
         nonce += 1;
     }
 
@@ -207,7 +338,7 @@ where
     polyvec_ntt(&mut e);
 
     // matrix-vector multiplication
-    for i in 0..KYBER_K {
+    for i in 0..K {
         polyvec_basemul_acc_montgomery(&mut pkpv.vec[i], &a[i], &skpv);
         poly_tomont(&mut pkpv.vec[i]);
     }
@@ -216,7 +347,11 @@ where
 
     pack_sk(sk, &mut skpv);
     pack_pk(pk, &mut pkpv, publicseed);
-    Ok(())
+    #[cfg(feature = "zeroize")]
+    {
+        skpv.zeroize();
+        e.zeroize();
+    }
 }
 
 /// Name:  indcpa_enc
@@ -224,32 +359,123 @@ where
 /// Description: Encryption function of the CPA-secure
 ///  public-key encryption scheme underlying Kyber.
 ///
-/// Arguments: - [u8] c:  output ciphertext (length KYBER_INDCPA_BYTES)
+/// Arguments: - [u8] c:  output ciphertext (length Params::<K>::INDCPA_BYTES)
+///  - const [u8] m:  input message (length KYBER_SYMBYTES)
+///  - const [u8] pk:   input public key (length Params::<K>::INDCPA_PUBLICKEYBYTES)
+///  - const [u8] coin: input random coins used as seed (length KYBER_SYMBYTES)
+///    to deterministically generate all randomness
+#[cfg(not(feature = "low-stack"))]
+pub fn indcpa_enc<const K: usize>(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
+    let mut at = [Polyvec::<K>::new(); K];
+    let mut pkpv = Polyvec::<K>::new();
+
+    indcpa_expand_pk(&mut at, &mut pkpv, pk);
+    indcpa_enc_expanded(c, m, &at, &pkpv, coins);
+}
+
+/// Name:  indcpa_enc
+///
+/// Description: Encryption function of the CPA-secure
+///  public-key encryption scheme underlying Kyber. Low-stack variant,
+///  the transposed matrix A is generated row by row inside the
+///  matrix-vector product and the noise one polynomial at a time
+///
+/// Arguments: - [u8] c:  output ciphertext (length Params::<K>::INDCPA_BYTES)
+///  - const [u8] m:  input message (length KYBER_SYMBYTES)
+///  - const [u8] pk:   input public key (length Params::<K>::INDCPA_PUBLICKEYBYTES)
+///  - const [u8] coin: input random coins used as seed (length KYBER_SYMBYTES)
+///    to deterministically generate all randomness
+#[cfg(feature = "low-stack")]
+pub fn indcpa_enc<const K: usize>(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
+    let mut seed = [0u8; KYBER_SYMBYTES];
+    let mut pkpv = Polyvec::<K>::new();
+    let mut sp = Polyvec::<K>::new();
+    let mut b = Polyvec::<K>::new();
+    let mut v = Poly::new();
+    // Holds each noise polynomial in turn, then the message
+    let mut t = Poly::new();
+
+    unpack_pk(&mut pkpv, &mut seed, pk);
+    for i in 0..K {
+        poly_getnoise_eta1::<K>(&mut sp.vec[i], coins, i as u8);
+    }
+    polyvec_ntt(&mut sp);
+
+    // matrix-vector multiplication, e1_i uses the nonce K + i
+    for i in 0..K {
+        matrix_basemul_acc_montgomery(&mut b.vec[i], &seed, i, true, &sp);
+        poly_invntt_tomont(&mut b.vec[i]);
+        poly_getnoise_eta2(&mut t, coins, (K + i) as u8);
+        poly_add(&mut b.vec[i], &t);
+    }
+
+    polyvec_basemul_acc_montgomery(&mut v, &pkpv, &sp);
+    poly_invntt_tomont(&mut v);
+    poly_getnoise_eta2(&mut t, coins, (2 * K) as u8);
+    poly_add(&mut v, &t);
+    poly_frommsg(&mut t, m);
+    poly_add(&mut v, &t);
+    polyvec_reduce(&mut b);
+    poly_reduce(&mut v);
+
+    pack_ciphertext(c, &mut b, &v);
+    #[cfg(feature = "zeroize")]
+    {
+        sp.zeroize();
+        b.zeroize();
+        v.zeroize();
+        t.zeroize();
+    }
+}
+
+/// Name:  indcpa_expand_pk
+///
+/// Description: Unpacks the public key and generates the transposed
+///  matrix A, the public key dependent part of indcpa_enc
+///
+/// Arguments: - Polyvec at: output transposed matrix A
+///  - Polyvec pkpv: output public-key vector of polynomials
+///  - const [u8] pk: input public key (length Params::<K>::INDCPA_PUBLICKEYBYTES)
+pub fn indcpa_expand_pk<const K: usize>(at: &mut [Polyvec<K>], pkpv: &mut Polyvec<K>, pk: &[u8]) {
+    let mut seed = [0u8; KYBER_SYMBYTES];
+
+    unpack_pk(pkpv, &mut seed, pk);
+    gen_at(at, &seed);
+}
+
+/// Name:  indcpa_enc_expanded
+///
+/// Description: indcpa_enc with a public key expanded by indcpa_expand_pk
+///
+/// Arguments: - [u8] c:  output ciphertext (length Params::<K>::INDCPA_BYTES)
 ///  - const [u8] m:  input message (length KYBER_SYMBYTES)
-///  - const [u8] pk:   input public key (length KYBER_INDCPA_PUBLICKEYBYTES)
+///  - const Polyvec at: input transposed matrix A
+///  - const Polyvec pkpv: input public-key vector of polynomials
 ///  - const [u8] coin: input random coins used as seed (length KYBER_SYMBYTES)
-///      to deterministically generate all randomness
-pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
-    let mut at = [Polyvec::new(); KYBER_K];
-    let (mut sp, mut pkpv, mut ep, mut b) = (
-        Polyvec::new(),
-        Polyvec::new(),
-        Polyvec::new(),
-        Polyvec::new(),
+///    to deterministically generate all randomness
+#[allow(clippy::needless_range_loop)]
+pub fn indcpa_enc_expanded<const K: usize>(
+    c: &mut [u8],
+    m: &[u8],
+    at: &[Polyvec<K>],
+    pkpv: &Polyvec<K>,
+    coins: &[u8],
+) {
+    let (mut sp, mut ep, mut b) = (
+        Polyvec::<K>::new(),
+        Polyvec::<K>::new(),
+        Polyvec::<K>::new(),
     );
     let (mut v, mut k, mut epp) = (Poly::new(), Poly::new(), Poly::new());
-    let mut seed = [0u8; KYBER_SYMBYTES];
     let mut nonce = 0u8;
 
-    unpack_pk(&mut pkpv, &mut seed, pk);
     poly_frommsg(&mut k, m);
-    gen_at(&mut at, &seed);
 
-    for i in 0..KYBER_K {
-        poly_getnoise_eta1(&mut sp.vec[i], coins, nonce);
+    for i in 0..K {
+        poly_getnoise_eta1::<K>(&mut sp.vec[i], coins, nonce);
         nonce += 1;
     }
-    for i in 0..KYBER_K {
+    for i in 0..K {
         poly_getnoise_eta2(&mut ep.vec[i], coins, nonce);
         nonce += 1;
     }
@@ -258,11 +484,11 @@ pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
     polyvec_ntt(&mut sp);
 
     // matrix-vector multiplication
-    for i in 0..KYBER_K {
+    for i in 0..K {
         polyvec_basemul_acc_montgomery(&mut b.vec[i], &at[i], &sp);
     }
 
-    polyvec_basemul_acc_montgomery(&mut v, &pkpv, &sp);
+    polyvec_basemul_acc_montgomery(&mut v, pkpv, &sp);
     polyvec_invntt_tomont(&mut b);
     poly_invntt_tomont(&mut v);
 
@@ -272,7 +498,16 @@ pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
     polyvec_reduce(&mut b);
     poly_reduce(&mut v);
 
-    pack_ciphertext(c, &mut b, v);
+    pack_ciphertext(c, &mut b, &v);
+    #[cfg(feature = "zeroize")]
+    {
+        sp.zeroize();
+        ep.zeroize();
+        b.zeroize();
+        v.zeroize();
+        k.zeroize();
+        epp.zeroize();
+    }
 }
 
 /// Name:  indcpa_dec
@@ -281,21 +516,49 @@ pub fn indcpa_enc(c: &mut [u8], m: &[u8], pk: &[u8], coins: &[u8]) {
 ///  public-key encryption scheme underlying Kyber.
 ///
 /// Arguments:   - [u8] m:  output decrypted message (of length KYBER_SYMBYTES)
-///  - const [u8] c:  input ciphertext (of length KYBER_INDCPA_BYTES)
-///  - const [u8] sk: input secret key (of length KYBER_INDCPA_SECRETKEYBYTES)
-pub fn indcpa_dec(m: &mut [u8], c: &[u8], sk: &[u8]) {
-    let (mut b, mut skpv) = (Polyvec::new(), Polyvec::new());
+///  - const [u8] c:  input ciphertext (of length Params::<K>::INDCPA_BYTES)
+///  - const [u8] sk: input secret key (of length Params::<K>::INDCPA_SECRETKEYBYTES)
+pub fn indcpa_dec<const K: usize>(m: &mut [u8], c: &[u8], sk: &[u8]) {
+    let mut skpv = Polyvec::<K>::new();
+
+    indcpa_expand_sk(&mut skpv, sk);
+    indcpa_dec_expanded(m, c, &skpv);
+    #[cfg(feature = "zeroize")]
+    skpv.zeroize();
+}
+
+/// Name:  indcpa_expand_sk
+///
+/// Description: Unpacks the NTT domain secret vector used by
+///  indcpa_dec_expanded
+///
+/// Arguments:   - Polyvec skpv: output vector of polynomials (secret key)
+///  - const [u8] sk: input secret key (of length Params::<K>::INDCPA_SECRETKEYBYTES)
+pub fn indcpa_expand_sk<const K: usize>(skpv: &mut Polyvec<K>, sk: &[u8]) {
+    unpack_sk(skpv, sk);
+}
+
+/// Name:  indcpa_dec_expanded
+///
+/// Description: indcpa_dec with a secret key unpacked by indcpa_expand_sk
+///
+/// Arguments:   - [u8] m:  output decrypted message (of length KYBER_SYMBYTES)
+///  - const [u8] c:  input ciphertext (of length Params::<K>::INDCPA_BYTES)
+///  - const Polyvec skpv: input vector of polynomials (secret key)
+pub fn indcpa_dec_expanded<const K: usize>(m: &mut [u8], c: &[u8], skpv: &Polyvec<K>) {
+    let mut b = Polyvec::<K>::new();
     let (mut v, mut mp) = (Poly::new(), Poly::new());
 
     unpack_ciphertext(&mut b, &mut v, c);
-    unpack_sk(&mut skpv, sk);
 
     polyvec_ntt(&mut b);
-    polyvec_basemul_acc_montgomery(&mut mp, &skpv, &b);
+    polyvec_basemul_acc_montgomery(&mut mp, skpv, &b);
     poly_invntt_tomont(&mut mp);
 
     poly_sub(&mut mp, &v);
     poly_reduce(&mut mp);
 
-    poly_tomsg(m, mp);
+    poly_tomsg(m, &mp);
+    #[cfg(feature = "zeroize")]
+    mp.zeroize();
 }
@@ -1,4 +1,4 @@
-use crate::reduce::*;
+use super::reduce::*;
 
 // Code to generate zetas used in the number-theoretic transform:
 //
@@ -95,6 +95,7 @@ pub fn ntt(r: &mut [i16]) {
 ///  input is in bitreversed order, output is in standard order
 ///
 /// Arguments:   - i16 r[256]: input/output vector of elements of Zq
+#[allow(clippy::needless_range_loop)]
 pub fn invntt(r: &mut [i16]) {
     let mut j;
     let mut k = 127usize;
@@ -110,7 +111,7 @@ pub fn invntt(r: &mut [i16]) {
             while j < (start + len) {
                 t = r[j];
                 r[j] = barrett_reduce(t + r[j + len]);
-                r[j + len] = r[j + len] - t;
+                r[j + len] -= t;
                 r[j + len] = fqmul(zeta, r[j + len]);
                 j += 1
             }
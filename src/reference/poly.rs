@@ -1,4 +1,7 @@
-use crate::{cbd::*, ntt::*, params::*, reduce::*, symmetric::*};
+use super::{cbd::*, ntt::*, reduce::*};
+use crate::{params::*, symmetric::*};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[derive(Clone)]
 pub struct Poly {
@@ -22,25 +25,40 @@ impl Poly {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for Poly {
+    fn zeroize(&mut self) {
+        self.coeffs.zeroize();
+    }
+}
+
 /// Name:  poly_compress
 ///
 /// Description: Compression and subsequent serialization of a polynomial
 ///
 /// Arguments:   - [u8] r: output byte array (needs space for KYBER_POLYCOMPRESSEDBYTES bytes)
 ///  - const poly *a:  input polynomial
-pub fn poly_compress(r: &mut [u8], a: Poly) {
+#[allow(clippy::needless_range_loop)]
+pub fn poly_compress<const K: usize>(r: &mut [u8], a: &Poly) {
     let mut t = [0u8; 8];
     let mut k = 0usize;
     let mut u: i16;
+    let mut d0: u32;
 
-    match KYBER_POLYCOMPRESSEDBYTES {
+    match Params::<K>::POLYCOMPRESSEDBYTES {
         128 => {
             for i in 0..KYBER_N / 8 {
                 for j in 0..8 {
                     // map to positive standard representatives
                     u = a.coeffs[8 * i + j];
                     u += (u >> 15) & KYBER_Q as i16;
-                    t[j] = (((((u as u16) << 4) + KYBER_Q as u16 / 2) / KYBER_Q as u16) & 15) as u8;
+                    // t[j] = ((((u << 4) + KYBER_Q / 2) / KYBER_Q) & 15
+                    d0 = (u as u32) << 4;
+                    d0 += 1665;
+                    // Only the low 32 bits are needed, the overflow is masked off
+                    d0 = d0.wrapping_mul(80635);
+                    d0 >>= 28;
+                    t[j] = (d0 & 0xf) as u8;
                 }
                 r[k] = t[0] | (t[1] << 4);
                 r[k + 1] = t[2] | (t[3] << 4);
@@ -55,7 +73,12 @@ pub fn poly_compress(r: &mut [u8], a: Poly) {
                     // map to positive standard representatives
                     u = a.coeffs[8 * i + j];
                     u += (u >> 15) & KYBER_Q as i16;
-                    t[j] = (((((u as u32) << 5) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & 31) as u8;
+                    // t[j] = ((((u << 5) + KYBER_Q / 2) / KYBER_Q) & 31
+                    d0 = (u as u32) << 5;
+                    d0 += 1664;
+                    d0 = d0.wrapping_mul(40318);
+                    d0 >>= 27;
+                    t[j] = (d0 & 0x1f) as u8;
                 }
                 r[k] = t[0] | (t[1] << 5);
                 r[k + 1] = (t[1] >> 3) | (t[2] << 2) | (t[3] << 7);
@@ -76,8 +99,13 @@ pub fn poly_compress(r: &mut [u8], a: Poly) {
 ///
 /// Arguments:   - poly *r:  output polynomial
 ///  - const [u8] a: input byte array (of length KYBER_POLYCOMPRESSEDBYTES bytes)
-pub fn poly_decompress(r: &mut Poly, a: &[u8]) {
-    match KYBER_POLYCOMPRESSEDBYTES {
+#[allow(
+    clippy::explicit_counter_loop,
+    clippy::identity_op,
+    clippy::needless_range_loop
+)]
+pub fn poly_decompress<const K: usize>(r: &mut Poly, a: &[u8]) {
+    match Params::<K>::POLYCOMPRESSEDBYTES {
         128 => {
             let mut idx = 0usize;
             for i in 0..KYBER_N / 2 {
@@ -115,7 +143,8 @@ pub fn poly_decompress(r: &mut Poly, a: &[u8]) {
 ///
 /// Arguments:   - [u8] r: output byte array (needs space for KYBER_POLYBYTES bytes)
 ///  - const poly *a:  input polynomial
-pub fn poly_tobytes(r: &mut [u8], a: Poly) {
+#[allow(clippy::identity_op)]
+pub fn poly_tobytes(r: &mut [u8], a: &Poly) {
     let (mut t0, mut t1);
 
     for i in 0..(KYBER_N / 2) {
@@ -137,6 +166,7 @@ pub fn poly_tobytes(r: &mut [u8], a: Poly) {
 ///
 /// Arguments:   - poly *r:  output polynomial
 ///  - const [u8] a: input byte array (of KYBER_POLYBYTES bytes)
+#[allow(clippy::identity_op)]
 pub fn poly_frombytes(r: &mut Poly, a: &[u8]) {
     for i in 0..(KYBER_N / 2) {
         r.coeffs[2 * i + 0] =
@@ -150,16 +180,18 @@ pub fn poly_frombytes(r: &mut Poly, a: &[u8]) {
 ///
 /// Description: Sample a polynomial deterministically from a seed and a nonce,
 ///  with output polynomial close to centered binomial distribution
-///  with parameter KYBER_ETA1
+///  with parameter ETA1 of the security level K
 ///
 /// Arguments:   - poly *r:     output polynomial
 ///  - const [u8] seed: input seed (pointing to array of length KYBER_SYMBYTES bytes)
 ///  - [u8]  nonce:   one-byte input nonce
-pub fn poly_getnoise_eta1(r: &mut Poly, seed: &[u8], nonce: u8) {
-    const LENGTH: usize = KYBER_ETA1 * KYBER_N / 4;
-    let mut buf = [0u8; LENGTH];
-    prf(&mut buf, LENGTH, seed, nonce);
-    poly_cbd_eta1(r, &buf);
+pub fn poly_getnoise_eta1<const K: usize>(r: &mut Poly, seed: &[u8], nonce: u8) {
+    let length = Params::<K>::ETA1 * KYBER_N / 4;
+    let mut buf = [0u8; KYBER_ETA1_MAX * KYBER_N / 4];
+    prf(&mut buf[..length], length, seed, nonce);
+    poly_cbd_eta1::<K>(r, &buf);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
 }
 
 /// Name:  poly_getnoise_eta2
@@ -176,6 +208,8 @@ pub fn poly_getnoise_eta2(r: &mut Poly, seed: &[u8], nonce: u8) {
     let mut buf = [0u8; LENGTH];
     prf(&mut buf, LENGTH, seed, nonce);
     poly_cbd_eta2(r, &buf);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
 }
 
 /// Name:  poly_ntt
@@ -283,6 +317,7 @@ pub fn poly_sub(r: &mut Poly, a: &Poly) {
 ///
 /// Arguments:   - poly *r:    output polynomial
 ///  - const [u8] msg: input message (of length KYBER_SYMBYTES)
+#[allow(clippy::manual_div_ceil, clippy::needless_range_loop)]
 pub fn poly_frommsg(r: &mut Poly, msg: &[u8]) {
     let mut mask;
     for i in 0..KYBER_N / 8 {
@@ -299,16 +334,68 @@ pub fn poly_frommsg(r: &mut Poly, msg: &[u8]) {
 ///
 /// Arguments:   - [u8] msg: output message
 ///  - const poly *a:  input polynomial
-pub fn poly_tomsg(msg: &mut [u8], a: Poly) {
-    let mut t;
+#[allow(clippy::needless_range_loop)]
+pub fn poly_tomsg(msg: &mut [u8], a: &Poly) {
+    let mut t: i16;
+    let mut d0: u32;
 
     for i in 0..KYBER_N / 8 {
         msg[i] = 0;
         for j in 0..8 {
             t = a.coeffs[8 * i + j];
             t += (t >> 15) & KYBER_Q as i16;
-            t = (((t << 1) + KYBER_Q as i16 / 2) / KYBER_Q as i16) & 1;
-            msg[i] |= (t << j) as u8;
+            // t = (((t << 1) + KYBER_Q / 2) / KYBER_Q) & 1
+            d0 = (t as u32) << 1;
+            d0 += 1665;
+            d0 *= 80635;
+            d0 >>= 28;
+            msg[i] |= ((d0 & 1) << j) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Compression with the division by q used before the KyberSlash fix
+    pub(crate) fn compress_div(u: i16, d: u32) -> u32 {
+        let u = u + ((u >> 15) & KYBER_Q as i16);
+        ((((u as u32) << d) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & ((1 << d) - 1)
+    }
+
+    // Reads the i-th d-bit little endian value of a packed array
+    pub(crate) fn unpack(r: &[u8], i: usize, d: usize) -> u32 {
+        let mut bits = 0u32;
+        for b in 0..d {
+            let pos = i * d + b;
+            bits |= (((r[pos / 8] >> (pos % 8)) & 1) as u32) << b;
+        }
+        bits
+    }
+
+    #[test]
+    fn compress_matches_division() {
+        // Every coefficient in [-(q-1), q-1], KYBER_N at a time
+        for start in (-(KYBER_Q as i16) + 1..KYBER_Q as i16).step_by(KYBER_N) {
+            let len = (KYBER_Q as i16 - start).min(KYBER_N as i16) as usize;
+            let mut a = Poly::new();
+            for i in 0..len {
+                a.coeffs[i] = start + i as i16;
+            }
+
+            let mut msg = [0u8; KYBER_SYMBYTES];
+            poly_tomsg(&mut msg, &a);
+            let mut r4 = [0u8; 128];
+            poly_compress::<3>(&mut r4, &a);
+            let mut r5 = [0u8; 160];
+            poly_compress::<4>(&mut r5, &a);
+
+            for (i, &c) in a.coeffs[..len].iter().enumerate() {
+                assert_eq!(unpack(&msg, i, 1), compress_div(c, 1), "coefficient {}", c);
+                assert_eq!(unpack(&r4, i, 4), compress_div(c, 4), "coefficient {}", c);
+                assert_eq!(unpack(&r5, i, 5), compress_div(c, 5), "coefficient {}", c);
+            }
         }
     }
 }
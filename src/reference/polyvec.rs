@@ -1,39 +1,54 @@
 #![allow(clippy::precedence)]
-use crate::{params::*, poly::*};
+use super::poly::*;
+use crate::params::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[derive(Clone)]
-pub struct Polyvec {
-    pub vec: [Poly; KYBER_K],
+pub struct Polyvec<const K: usize> {
+    pub vec: [Poly; K],
 }
 
-impl Copy for Polyvec {}
+impl<const K: usize> Copy for Polyvec<K> {}
 
-impl Polyvec {
+impl<const K: usize> Polyvec<K> {
     pub fn new() -> Self {
         Polyvec {
-            vec: [Poly::new(); KYBER_K],
+            vec: [Poly::new(); K],
         }
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<const K: usize> Zeroize for Polyvec<K> {
+    fn zeroize(&mut self) {
+        self.vec.zeroize();
+    }
+}
+
 /// Name:  polyvec_compress
 ///
 /// Description: Compress and serialize vector of polynomials
 ///
 /// Arguments:   - [u8] r: output byte array (needs space for KYBER_POLYVECCOMPRESSEDBYTES)
 ///  - const Polyvec a: input vector of polynomials
-pub fn polyvec_compress(r: &mut [u8], a: Polyvec) {
-    #[cfg(feature = "kyber1024")]
-    {
+#[allow(clippy::identity_op, clippy::needless_range_loop)]
+pub fn polyvec_compress<const K: usize>(r: &mut [u8], a: &Polyvec<K>) {
+    if K == 4 {
         let mut t = [0u16; 8];
         let mut idx = 0usize;
-        for i in 0..KYBER_K {
+        let mut d0: u64;
+        for i in 0..K {
             for j in 0..KYBER_N / 8 {
                 for k in 0..8 {
                     t[k] = a.vec[i].coeffs[8 * j + k] as u16;
                     t[k] = t[k].wrapping_add((((t[k] as i16) >> 15) & KYBER_Q as i16) as u16);
-                    t[k] = (((((t[k] as u32) << 11) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & 0x7ff)
-                        as u16;
+                    // t[k] = ((((t[k] << 11) + KYBER_Q / 2) / KYBER_Q) & 0x7ff
+                    d0 = (t[k] as u64) << 11;
+                    d0 += 1664;
+                    d0 *= 645084;
+                    d0 >>= 31;
+                    t[k] = (d0 & 0x7ff) as u16;
                 }
                 r[idx + 0] = (t[0] >> 0) as u8;
                 r[idx + 1] = ((t[0] >> 8) | (t[1] << 3)) as u8;
@@ -49,19 +64,21 @@ pub fn polyvec_compress(r: &mut [u8], a: Polyvec) {
                 idx += 11
             }
         }
-    }
-
-    #[cfg(not(feature = "kyber1024"))]
-    {
+    } else {
         let mut t = [0u16; 4];
         let mut idx = 0usize;
-        for i in 0..KYBER_K {
+        let mut d0: u64;
+        for i in 0..K {
             for j in 0..KYBER_N / 4 {
                 for k in 0..4 {
                     t[k] = a.vec[i].coeffs[4 * j + k] as u16;
                     t[k] = t[k].wrapping_add((((t[k] as i16) >> 15) & KYBER_Q as i16) as u16);
-                    t[k] = (((((t[k] as u32) << 10) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & 0x3ff)
-                        as u16;
+                    // t[k] = ((((t[k] << 10) + KYBER_Q / 2) / KYBER_Q) & 0x3ff
+                    d0 = (t[k] as u64) << 10;
+                    d0 += 1665;
+                    d0 *= 1290167;
+                    d0 >>= 32;
+                    t[k] = (d0 & 0x3ff) as u16;
                 }
                 r[idx + 0] = (t[0] >> 0) as u8;
                 r[idx + 1] = ((t[0] >> 8) | (t[1] << 2)) as u8;
@@ -81,12 +98,12 @@ pub fn polyvec_compress(r: &mut [u8], a: Polyvec) {
 ///
 /// Arguments:   - Polyvec r:   output vector of polynomials
 ///  - [u8] a: input byte array (of length KYBER_POLYVECCOMPRESSEDBYTES)
-pub fn polyvec_decompress(r: &mut Polyvec, a: &[u8]) {
-    #[cfg(feature = "kyber1024")]
-    {
+#[allow(clippy::identity_op, clippy::needless_range_loop)]
+pub fn polyvec_decompress<const K: usize>(r: &mut Polyvec<K>, a: &[u8]) {
+    if K == 4 {
         let mut t = [0u16; 8];
         let mut idx = 0usize;
-        for i in 0..KYBER_K {
+        for i in 0..K {
             for j in 0..KYBER_N / 8 {
                 t[0] = (a[idx + 0] >> 0) as u16 | (a[idx + 1] as u16) << 8;
                 t[1] = (a[idx + 1] >> 3) as u16 | (a[idx + 2] as u16) << 5;
@@ -106,13 +123,10 @@ pub fn polyvec_decompress(r: &mut Polyvec, a: &[u8]) {
                 }
             }
         }
-    }
-
-    #[cfg(not(feature = "kyber1024"))]
-    {
+    } else {
         let mut idx = 0usize;
         let mut t = [0u16; 4];
-        for i in 0..KYBER_K {
+        for i in 0..K {
             for j in 0..KYBER_N / 4 {
                 t[0] = (a[idx + 0] >> 0) as u16 | (a[idx + 1] as u16) << 8;
                 t[1] = (a[idx + 1] >> 2) as u16 | (a[idx + 2] as u16) << 6;
@@ -135,9 +149,9 @@ pub fn polyvec_decompress(r: &mut Polyvec, a: &[u8]) {
 ///
 /// Arguments:   - [u8] r: output byte array (needs space for KYBER_POLYVECBYTES)
 ///  - const Polyvec a: input vector of polynomials
-pub fn polyvec_tobytes(r: &mut [u8], a: &Polyvec) {
-    for i in 0..KYBER_K {
-        poly_tobytes(&mut r[i * KYBER_POLYBYTES..], a.vec[i]);
+pub fn polyvec_tobytes<const K: usize>(r: &mut [u8], a: &Polyvec<K>) {
+    for i in 0..K {
+        poly_tobytes(&mut r[i * KYBER_POLYBYTES..], &a.vec[i]);
     }
 }
 
@@ -148,8 +162,8 @@ pub fn polyvec_tobytes(r: &mut [u8], a: &Polyvec) {
 ///
 /// Arguments:   - [u8] r: output byte array
 ///  - const Polyvec a: input vector of polynomials (of length KYBER_POLYVECBYTES)
-pub fn polyvec_frombytes(r: &mut Polyvec, a: &[u8]) {
-    for i in 0..KYBER_K {
+pub fn polyvec_frombytes<const K: usize>(r: &mut Polyvec<K>, a: &[u8]) {
+    for i in 0..K {
         poly_frombytes(&mut r.vec[i], &a[i * KYBER_POLYBYTES..]);
     }
 }
@@ -159,8 +173,8 @@ pub fn polyvec_frombytes(r: &mut Polyvec, a: &[u8]) {
 /// Description: Apply forward NTT to all elements of a vector of polynomials
 ///
 /// Arguments:   - Polyvec r: in/output vector of polynomials
-pub fn polyvec_ntt(r: &mut Polyvec) {
-    for i in 0..KYBER_K {
+pub fn polyvec_ntt<const K: usize>(r: &mut Polyvec<K>) {
+    for i in 0..K {
         poly_ntt(&mut r.vec[i]);
     }
 }
@@ -170,8 +184,8 @@ pub fn polyvec_ntt(r: &mut Polyvec) {
 /// Description: Apply inverse NTT to all elements of a vector of polynomials
 ///
 /// Arguments:   - Polyvec r: in/output vector of polynomials
-pub fn polyvec_invntt_tomont(r: &mut Polyvec) {
-    for i in 0..KYBER_K {
+pub fn polyvec_invntt_tomont<const K: usize>(r: &mut Polyvec<K>) {
+    for i in 0..K {
         poly_invntt_tomont(&mut r.vec[i]);
     }
 }
@@ -183,14 +197,20 @@ pub fn polyvec_invntt_tomont(r: &mut Polyvec) {
 /// Arguments: - poly *r:  output polynomial
 ///  - const Polyvec a: first input vector of polynomials
 ///  - const Polyvec b: second input vector of polynomials
-pub fn polyvec_basemul_acc_montgomery(r: &mut Poly, a: &Polyvec, b: &Polyvec) {
+pub fn polyvec_basemul_acc_montgomery<const K: usize>(
+    r: &mut Poly,
+    a: &Polyvec<K>,
+    b: &Polyvec<K>,
+) {
     let mut t = Poly::new();
     poly_basemul(r, &a.vec[0], &b.vec[0]);
-    for i in 1..KYBER_K {
+    for i in 1..K {
         poly_basemul(&mut t, &a.vec[i], &b.vec[i]);
         poly_add(r, &t);
     }
     poly_reduce(r);
+    #[cfg(feature = "zeroize")]
+    t.zeroize();
 }
 
 /// Name:  polyvec_reduce
@@ -200,8 +220,8 @@ pub fn polyvec_basemul_acc_montgomery(r: &mut Poly, a: &Polyvec, b: &Polyvec) {
 ///  for details of the Barrett reduction see comments in reduce.c
 ///
 /// Arguments:   - poly *r:   input/output polynomial
-pub fn polyvec_reduce(r: &mut Polyvec) {
-    for i in 0..KYBER_K {
+pub fn polyvec_reduce<const K: usize>(r: &mut Polyvec<K>) {
+    for i in 0..K {
         poly_reduce(&mut r.vec[i]);
     }
 }
@@ -212,8 +232,40 @@ pub fn polyvec_reduce(r: &mut Polyvec) {
 ///
 /// Arguments: - Polyvec r:   output vector of polynomials
 ///  - const Polyvec b: second input vector of polynomials
-pub fn polyvec_add(r: &mut Polyvec, b: &Polyvec) {
-    for i in 0..KYBER_K {
+pub fn polyvec_add<const K: usize>(r: &mut Polyvec<K>, b: &Polyvec<K>) {
+    for i in 0..K {
         poly_add(&mut r.vec[i], &b.vec[i]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::poly::tests::{compress_div, unpack};
+
+    #[test]
+    fn compress_matches_division() {
+        // Every coefficient in [-(q-1), q-1], KYBER_N at a time
+        for start in (-(KYBER_Q as i16) + 1..KYBER_Q as i16).step_by(KYBER_N) {
+            let len = (KYBER_Q as i16 - start).min(KYBER_N as i16) as usize;
+            let mut a = Polyvec::<4>::new();
+            for i in 0..len {
+                a.vec[0].coeffs[i] = start + i as i16;
+            }
+
+            let mut r = [0u8; 4 * 352];
+            polyvec_compress(&mut r, &a);
+            for (i, &c) in a.vec[0].coeffs[..len].iter().enumerate() {
+                assert_eq!(unpack(&r, i, 11), compress_div(c, 11), "coefficient {}", c);
+            }
+
+            let mut b = Polyvec::<3>::new();
+            b.vec[0] = a.vec[0];
+            let mut r = [0u8; 3 * 320];
+            polyvec_compress(&mut r, &b);
+            for (i, &c) in a.vec[0].coeffs[..len].iter().enumerate() {
+                assert_eq!(unpack(&r, i, 10), compress_div(c, 10), "coefficient {}", c);
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Sealed boxes, public key encryption of arbitrary messages
+//!
+//! A message is sealed by encapsulating to the recipient's public key,
+//! deriving a ChaCha20-Poly1305 key and nonce from the shared secret with
+//! HKDF-SHA256 and encrypting under them. The sealed box is the Kyber
+//! ciphertext followed by the AEAD ciphertext and tag, it is
+//! [`SEALEDBOX_OVERHEAD`] bytes longer than the message.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(), KyberError> {
+//! let mut rng = rand::thread_rng();
+//! let keys = keypair(&mut rng)?;
+//! let sealed = sealedbox::seal(&keys.public, b"Hello Bob", b"header", &mut rng)?;
+//! let opened = sealedbox::open(&keys.secret, &sealed, b"header")?;
+//! assert_eq!(opened, b"Hello Bob");
+//! # Ok(()) }
+//! ```
+//!
+//! Sealed boxes are anonymous, the recipient learns nothing about who
+//! sealed them. Requires the `sealedbox` feature.
+extern crate alloc;
+
+use crate::{
+    decapsulate, encapsulate, params::*, Ciphertext, CryptoRng, KyberError, PublicKey, RngCore,
+    SecretKey, SharedSecret,
+};
+use alloc::vec::Vec;
+use chacha20poly1305::{aead::AeadInPlace, ChaCha20Poly1305, KeyInit};
+use core::convert::TryFrom;
+use hkdf::Hkdf;
+use sha2::Sha256;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Size of the AEAD authentication tag
+pub const SEALEDBOX_TAGBYTES: usize = 16;
+/// Number of bytes a sealed box adds to the message
+pub const SEALEDBOX_OVERHEAD: usize = KYBER_CIPHERTEXTBYTES + SEALEDBOX_TAGBYTES;
+
+const KEYBYTES: usize = 32;
+const NONCEBYTES: usize = 12;
+// Domain separation of the derived key and nonce
+const INFO: &[u8] = b"pqc_kyber sealed box v1";
+
+/// Seals a message to a public key.
+///
+/// `aad` is authenticated but not encrypted, the same value has to be
+/// given to [`open`]. Public keys encoding coefficients that are not
+/// reduced modulo q are rejected with [`KyberError::InvalidPublicKey`].
+pub fn seal<R>(
+    pk: &PublicKey,
+    plaintext: &[u8],
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    let (ct, ss) = encapsulate(pk, rng)?;
+    let mut sealed = Vec::with_capacity(plaintext.len() + SEALEDBOX_OVERHEAD);
+    sealed.extend_from_slice(ct.as_ref());
+    sealed.extend_from_slice(plaintext);
+
+    let (cipher, nonce) = aead(&ss)?;
+    let tag = cipher
+        .encrypt_in_place_detached(&nonce.into(), aad, &mut sealed[KYBER_CIPHERTEXTBYTES..])
+        .map_err(|_| KyberError::InvalidInput)?;
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+}
+
+/// Opens a sealed box with a secret key, returning the message.
+///
+/// Returns [`KyberError::InvalidSealedBox`] if the box was modified, was
+/// sealed to a different key or `aad` differs, and
+/// [`KyberError::InvalidInput`] if it is too short to be a sealed box.
+pub fn open(sk: &SecretKey, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, KyberError> {
+    if sealed.len() < SEALEDBOX_OVERHEAD {
+        return Err(KyberError::InvalidInput);
+    }
+    let (ct, rest) = sealed.split_at(KYBER_CIPHERTEXTBYTES);
+    let (body, tag) = rest.split_at(rest.len() - SEALEDBOX_TAGBYTES);
+    let ss = decapsulate(&Ciphertext::try_from(ct)?, sk)?;
+
+    let (cipher, nonce) = aead(&ss)?;
+    let mut plaintext = body.to_vec();
+    cipher
+        .decrypt_in_place_detached(&nonce.into(), aad, &mut plaintext, tag.into())
+        .map_err(|_| KyberError::InvalidSealedBox)?;
+    Ok(plaintext)
+}
+
+// AEAD key and nonce from HKDF-SHA256 over the shared secret
+fn aead(ss: &SharedSecret) -> Result<(ChaCha20Poly1305, [u8; NONCEBYTES]), KyberError> {
+    let mut okm = [0u8; KEYBYTES + NONCEBYTES];
+    Hkdf::<Sha256>::new(None, ss.as_ref())
+        .expand(INFO, &mut okm)
+        .map_err(|_| KyberError::InvalidInput)?;
+    let cipher = ChaCha20Poly1305::new_from_slice(&okm[..KEYBYTES]);
+    let mut nonce = [0u8; NONCEBYTES];
+    nonce.copy_from_slice(&okm[KEYBYTES..]);
+    #[cfg(feature = "zeroize")]
+    okm.zeroize();
+    Ok((cipher.map_err(|_| KyberError::InvalidInput)?, nonce))
+}
@@ -2,10 +2,20 @@
 
 #[cfg(feature = "90s")]
 use crate::aes256ctr::*;
+#[cfg(feature = "90s")]
+use crate::params::{KYBER_SSBYTES, KYBER_SYMBYTES};
+use crate::KyberError;
 #[cfg(not(feature = "90s"))]
 use crate::{fips202::*, params::*};
 #[cfg(feature = "90s")]
-use sha2::{Digest, Sha256, Sha512};
+use hkdf::{
+    hmac::{Hmac, Mac},
+    Hkdf,
+};
+#[cfg(feature = "90s")]
+use sha2::{digest::Output, Digest, Sha256, Sha512};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 #[cfg(feature = "90s-fixslice")]
 use aes::cipher::{generic_array::GenericArray, KeyIvInit, StreamCipher};
@@ -46,6 +56,14 @@ impl KeccakState {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for KeccakState {
+    fn zeroize(&mut self) {
+        self.s.zeroize();
+        self.pos.zeroize();
+    }
+}
+
 /// SHA3-256
 #[cfg(not(feature = "90s"))]
 pub fn hash_h(out: &mut [u8], input: &[u8], inlen: usize) {
@@ -57,8 +75,8 @@ pub fn hash_h(out: &mut [u8], input: &[u8], inlen: usize) {
 pub fn hash_h(out: &mut [u8], input: &[u8], inlen: usize) {
     let mut hasher = Sha256::new();
     hasher.update(&input[..inlen]);
-    let digest = hasher.finalize();
-    out[..digest.len()].copy_from_slice(&digest);
+    // Finalized in place, no copy of the digest is left on the stack
+    hasher.finalize_into(Output::<Sha256>::from_mut_slice(&mut out[..KYBER_SYMBYTES]));
 }
 
 #[cfg(not(feature = "90s"))]
@@ -70,13 +88,15 @@ pub fn hash_g(out: &mut [u8], input: &[u8], inlen: usize) {
 pub fn hash_g(out: &mut [u8], input: &[u8], inlen: usize) {
     let mut hasher = Sha512::new();
     hasher.update(&input[..inlen]);
-    let digest = hasher.finalize();
-    out[..digest.len()].copy_from_slice(&digest);
+    // Finalized in place, no copy of the digest is left on the stack
+    hasher.finalize_into(Output::<Sha512>::from_mut_slice(
+        &mut out[..2 * KYBER_SYMBYTES],
+    ));
 }
 
 #[cfg(not(feature = "90s"))]
 pub fn xof_absorb(state: &mut XofState, input: &[u8], x: u8, y: u8) {
-    kyber_shake128_absorb(state, &input, x, y);
+    kyber_shake128_absorb(state, input, x, y);
 }
 
 #[cfg(feature = "90s")]
@@ -99,7 +119,7 @@ pub fn xof_squeezeblocks(out: &mut [u8], outblocks: usize, state: &mut XofState)
 
 #[cfg(not(feature = "90s"))]
 pub fn prf(out: &mut [u8], outbytes: usize, key: &[u8], nonce: u8) {
-    shake256_prf(out, outbytes, &key, nonce);
+    shake256_prf(out, outbytes, key, nonce);
 }
 
 #[cfg(feature = "90s")]
@@ -115,6 +135,16 @@ pub fn prf(out: &mut [u8], _outbytes: usize, key: &[u8], nonce: u8) {
         cipher.apply_keystream(out);
         return;
     }
+    #[cfg(all(
+        target_arch = "x86_64",
+        feature = "avx2",
+        not(feature = "90s-fixslice")
+    ))]
+    if crate::backend::avx2_available() {
+        // Safety: the CPU features were checked by avx2_available()
+        unsafe { crate::avx2::aes256ctr::aes256ctr_prf(out, _outbytes, key, nonce) };
+        return;
+    }
     #[cfg(not(feature = "90s-fixslice"))]
     // Pornin bitslice
     aes256ctr_prf(out, _outbytes, &key, nonce);
@@ -129,8 +159,154 @@ pub fn kdf(out: &mut [u8], input: &[u8], inlen: usize) {
 pub fn kdf(out: &mut [u8], input: &[u8], inlen: usize) {
     let mut hasher = Sha256::new();
     hasher.update(&input[..inlen]);
-    let digest = hasher.finalize();
-    out[..digest.len()].copy_from_slice(&digest);
+    // Finalized in place, no copy of the digest is left on the stack
+    hasher.finalize_into(Output::<Sha256>::from_mut_slice(&mut out[..KYBER_SSBYTES]));
+}
+
+// Domain separation of kdf_context from the Kyber KDF, the context follows
+// it prefixed with its length as a 64 bit big-endian integer
+const KDF_CONTEXT_LABEL: &[u8] = b"pqc_kyber kdf context";
+
+/// Name:  kdf_context
+///
+/// Description: Variable length KDF bound to a context, SHAKE256 over the
+///  concatenation of the pre-key, H(c), a fixed label, the context length
+///  and the context
+///
+/// Arguments:   - [u8] out: output key material (any length)
+///  - const [u8] input: pre-key and H(c) (length 2*KYBER_SYMBYTES)
+///  - const [u8] context: application label
+#[cfg(not(feature = "90s"))]
+pub fn kdf_context(out: &mut [u8], input: &[u8], context: &[u8]) -> Result<(), KyberError> {
+    let mut state = KeccakState::new();
+    shake256_init(&mut state);
+    shake256_absorb(&mut state, &input[..2 * KYBER_SYMBYTES]);
+    shake256_absorb(&mut state, KDF_CONTEXT_LABEL);
+    shake256_absorb(&mut state, &(context.len() as u64).to_be_bytes());
+    shake256_absorb(&mut state, context);
+    shake256_finalize(&mut state);
+    shake256_squeeze(out, out.len(), &mut state);
+    #[cfg(feature = "zeroize")]
+    state.zeroize();
+    Ok(())
+}
+
+/// Name:  kdf_context
+///
+/// Description: Variable length KDF bound to a context, HKDF-SHA512 with the
+///  pre-key and H(c) as input keying material and a fixed label, the context
+///  length and the context as info
+///
+/// Arguments:   - [u8] out: output key material (at most 255*64 bytes)
+///  - const [u8] input: pre-key and H(c) (length 2*KYBER_SYMBYTES)
+///  - const [u8] context: application label
+#[cfg(feature = "90s")]
+pub fn kdf_context(out: &mut [u8], input: &[u8], context: &[u8]) -> Result<(), KyberError> {
+    // HKDF-Extract without a salt, HMAC keyed with zeros. Finalized into a
+    // local array so the pseudorandom key can be wiped.
+    let mut prk = [0u8; 64];
+    let mut mac =
+        <Hmac<Sha512> as Mac>::new_from_slice(&[0u8; 64]).map_err(|_| KyberError::InvalidInput)?;
+    Mac::update(&mut mac, &input[..2 * KYBER_SYMBYTES]);
+    hkdf::hmac::digest::FixedOutput::finalize_into(mac, Output::<Sha512>::from_mut_slice(&mut prk));
+    let len = (context.len() as u64).to_be_bytes();
+    let res = Hkdf::<Sha512>::from_prk(&prk)
+        .map_err(|_| KyberError::InvalidInput)
+        .and_then(|hk| {
+            hk.expand_multi_info(&[KDF_CONTEXT_LABEL, &len, context], out)
+                .map_err(|_| KyberError::InvalidInput)
+        });
+    #[cfg(feature = "zeroize")]
+    prk.zeroize();
+    res
+}
+
+/// Name:  hash_transcript
+///
+/// Description: Hash of a key exchange transcript, SHAKE256 over the
+///  concatenation of its parts
+///
+/// Arguments:   - [u8] out: output hash (length KYBER_SYMBYTES)
+///  - const [&[u8]] parts: transcript parts, absorbed in order
+#[cfg(not(feature = "90s"))]
+pub fn hash_transcript(out: &mut [u8], parts: &[&[u8]]) {
+    let mut state = KeccakState::new();
+    shake256_init(&mut state);
+    for part in parts {
+        shake256_absorb(&mut state, part);
+    }
+    shake256_finalize(&mut state);
+    shake256_squeeze(out, KYBER_SYMBYTES, &mut state);
+}
+
+/// Name:  hash_transcript
+///
+/// Description: Hash of a key exchange transcript, SHA-256 over the
+///  concatenation of its parts
+///
+/// Arguments:   - [u8] out: output hash (length KYBER_SYMBYTES)
+///  - const [&[u8]] parts: transcript parts, absorbed in order
+#[cfg(feature = "90s")]
+pub fn hash_transcript(out: &mut [u8], parts: &[&[u8]]) {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    out[..KYBER_SYMBYTES].copy_from_slice(&hasher.finalize());
+}
+
+/// Name:  kex_prf
+///
+/// Description: Keyed PRF of the key exchanges for confirmation keys, tags
+///  and early keys, SHAKE256 over the concatenation of the key and a label
+///
+/// Arguments:   - [u8] out: output (length KYBER_SYMBYTES)
+///  - const [u8] key: secret key (at least KYBER_SSBYTES)
+///  - const [u8] label: purpose of the output
+#[cfg(not(feature = "90s"))]
+pub fn kex_prf(out: &mut [u8], key: &[u8], label: &[u8]) {
+    let mut state = KeccakState::new();
+    shake256_init(&mut state);
+    shake256_absorb(&mut state, key);
+    shake256_absorb(&mut state, label);
+    shake256_finalize(&mut state);
+    shake256_squeeze(out, KYBER_SYMBYTES, &mut state);
+    #[cfg(feature = "zeroize")]
+    state.zeroize();
+}
+
+/// Name:  kex_prf
+///
+/// Description: Keyed PRF of the key exchanges for confirmation keys, tags
+///  and early keys, HKDF-SHA256 expansion of the key with the label as info
+///
+/// Arguments:   - [u8] out: output (length KYBER_SYMBYTES)
+///  - const [u8] key: secret key (at least KYBER_SSBYTES)
+///  - const [u8] label: purpose of the output
+#[cfg(feature = "90s")]
+pub fn kex_prf(out: &mut [u8], key: &[u8], label: &[u8]) {
+    // A key of 32 bytes or more is a valid SHA-256 pseudorandom key and 32
+    // bytes of output are in range, neither can fail
+    let _ = Hkdf::<Sha256>::from_prk(key).map(|hk| hk.expand(label, &mut out[..KYBER_SYMBYTES]));
+}
+
+/// Name:  rkprf
+///
+/// Description: Implicit rejection PRF J of FIPS 203, SHAKE256 over the
+///  concatenation of the secret rejection value and the ciphertext
+///
+/// Arguments:   - [u8] out: output shared secret (length KYBER_SSBYTES)
+///  - const [u8] key: rejection value z (length KYBER_SYMBYTES)
+///  - const [u8] input: ciphertext (at most KYBER_CIPHERTEXTBYTES_MAX bytes)
+#[cfg(not(feature = "90s"))]
+pub fn rkprf(out: &mut [u8], key: &[u8], input: &[u8]) {
+    let inlen = KYBER_SYMBYTES + input.len();
+    let mut buf = [0u8; KYBER_SYMBYTES + KYBER_CIPHERTEXTBYTES_MAX];
+    buf[..KYBER_SYMBYTES].copy_from_slice(&key[..KYBER_SYMBYTES]);
+    buf[KYBER_SYMBYTES..inlen].copy_from_slice(input);
+    shake256(out, KYBER_SSBYTES, &buf, inlen);
+    #[cfg(feature = "zeroize")]
+    buf[..KYBER_SYMBYTES].zeroize();
 }
 
 /// Name:  kyber_shake128_absorb
@@ -179,4 +355,6 @@ fn shake256_prf(output: &mut [u8], outlen: usize, key: &[u8], nonce: u8) {
     extkey[..KYBER_SYMBYTES].copy_from_slice(key);
     extkey[KYBER_SYMBYTES] = nonce;
     shake256(output, outlen, &extkey, KYBER_SYMBYTES + 1);
+    #[cfg(feature = "zeroize")]
+    extkey.zeroize();
 }
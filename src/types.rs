@@ -0,0 +1,282 @@
+//! Key, ciphertext and shared secret types.
+//!
+//! Comparisons run in constant time and `Debug` never prints secret bytes.
+use crate::{params::*, verify::verify, KyberError};
+use core::{convert::TryFrom, fmt};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+// Hexadecimal Debug output for public values
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Debug for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+// Constant time equality of two byte slices
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && verify(a, b, a.len()) == 0
+}
+
+macro_rules! byte_type {
+    ($(#[$doc:meta])* $name:ident, $bytes:ident, $len:ident) => {
+        $(#[$doc])*
+        pub struct $name<P: KyberParams = DefaultParams>(pub(crate) P::$bytes);
+
+        impl<P: KyberParams> $name<P> {
+            /// Size in bytes
+            pub const LEN: usize = P::$len;
+
+            pub(crate) fn zeroed() -> Self {
+                Self(P::$bytes::zeroed())
+            }
+        }
+
+        impl<P: KyberParams> AsRef<[u8]> for $name<P> {
+            fn as_ref(&self) -> &[u8] {
+                self.0.as_ref()
+            }
+        }
+
+        impl<P: KyberParams> AsMut<[u8]> for $name<P> {
+            fn as_mut(&mut self) -> &mut [u8] {
+                self.0.as_mut()
+            }
+        }
+
+        impl<P: KyberParams> TryFrom<&[u8]> for $name<P> {
+            type Error = KyberError;
+
+            /// Fails with [`KyberError::InvalidInput`] if the slice is not
+            /// exactly the right size.
+            fn try_from(bytes: &[u8]) -> Result<Self, KyberError> {
+                if bytes.len() != P::$len {
+                    return Err(KyberError::InvalidInput);
+                }
+                let mut out = Self::zeroed();
+                out.0.as_mut().copy_from_slice(bytes);
+                Ok(out)
+            }
+        }
+
+        impl<P: KyberParams> PartialEq for $name<P> {
+            fn eq(&self, other: &Self) -> bool {
+                ct_eq(self.as_ref(), other.as_ref())
+            }
+        }
+
+        impl<P: KyberParams> Eq for $name<P> {}
+
+        #[cfg(feature = "zeroize")]
+        impl<P: KyberParams> Zeroize for $name<P> {
+            fn zeroize(&mut self) {
+                self.0.zeroize();
+            }
+        }
+    };
+}
+
+macro_rules! public_type {
+    ($name:ident) => {
+        impl<P: KyberParams> Copy for $name<P> {}
+
+        impl<P: KyberParams> Clone for $name<P> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<P: KyberParams> fmt::Debug for $name<P> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&Hex(self.as_ref()))
+                    .finish()
+            }
+        }
+    };
+}
+
+macro_rules! secret_type {
+    ($name:ident) => {
+        impl<P: KyberParams> Clone for $name<P> {
+            fn clone(&self) -> Self {
+                Self(self.0)
+            }
+        }
+
+        impl<P: KyberParams> fmt::Debug for $name<P> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(concat!(stringify!($name), "(<redacted>)"))
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl<P: KyberParams> Drop for $name<P> {
+            fn drop(&mut self) {
+                self.zeroize();
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl<P: KyberParams> ZeroizeOnDrop for $name<P> {}
+    };
+}
+
+byte_type!(
+    /// Kyber public key
+    PublicKey,
+    PublicKey,
+    PUBLICKEYBYTES
+);
+public_type!(PublicKey);
+
+byte_type!(
+    /// Kyber secret key, redacted when printed and zeroized on drop with
+    /// the `zeroize` feature
+    SecretKey,
+    SecretKey,
+    SECRETKEYBYTES
+);
+secret_type!(SecretKey);
+
+byte_type!(
+    /// Kyber ciphertext
+    Ciphertext,
+    Ciphertext,
+    CIPHERTEXTBYTES
+);
+public_type!(Ciphertext);
+
+macro_rules! array_type {
+    ($(#[$doc:meta])* $name:ident, $len:expr) => {
+        $(#[$doc])*
+        pub struct $name(pub(crate) [u8; $len]);
+
+        impl $name {
+            /// Size in bytes
+            pub const LEN: usize = $len;
+
+            #[allow(dead_code)]
+            pub(crate) fn zeroed() -> Self {
+                $name([0u8; $len])
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl AsMut<[u8]> for $name {
+            fn as_mut(&mut self) -> &mut [u8] {
+                &mut self.0
+            }
+        }
+
+        impl core::convert::TryFrom<&[u8]> for $name {
+            type Error = $crate::KyberError;
+
+            /// Fails with [`KyberError::InvalidInput`](crate::KyberError::InvalidInput)
+            /// if the slice is not exactly the right size.
+            fn try_from(bytes: &[u8]) -> Result<Self, $crate::KyberError> {
+                if bytes.len() != $len {
+                    return Err($crate::KyberError::InvalidInput);
+                }
+                let mut out = Self::zeroed();
+                out.0.copy_from_slice(bytes);
+                Ok(out)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::types::ct_eq(&self.0, &other.0)
+            }
+        }
+
+        impl Eq for $name {}
+
+        #[cfg(feature = "zeroize")]
+        impl zeroize::Zeroize for $name {
+            fn zeroize(&mut self) {
+                zeroize::Zeroize::zeroize(&mut self.0);
+            }
+        }
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! public_array {
+    ($name:ident) => {
+        impl Copy for $name {}
+
+        impl Clone for $name {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&$crate::types::Hex(&self.0))
+                    .finish()
+            }
+        }
+    };
+}
+
+macro_rules! secret_array {
+    ($name:ident) => {
+        impl Clone for $name {
+            fn clone(&self) -> Self {
+                $name(self.0)
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(concat!(stringify!($name), "(<redacted>)"))
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl Drop for $name {
+            fn drop(&mut self) {
+                zeroize::Zeroize::zeroize(self);
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl zeroize::ZeroizeOnDrop for $name {}
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use {array_type, public_array, secret_array};
+
+array_type!(
+    /// Kyber shared secret, redacted when printed and zeroized on drop with
+    /// the `zeroize` feature
+    ///
+    /// The same size for every security level.
+    SharedSecret,
+    KYBER_SSBYTES
+);
+secret_array!(SharedSecret);
+
+array_type!(
+    /// Pre-shared key mixed into the session key of a key exchange, redacted
+    /// when printed and zeroized on drop with the `zeroize` feature
+    ///
+    /// See [`Uake::with_psk`](crate::Uake::with_psk).
+    PresharedKey,
+    KYBER_SYMBYTES
+);
+secret_array!(PresharedKey);
@@ -4,6 +4,7 @@ extern crate alloc;
 use super::*;
 use crate::params::*;
 use alloc::boxed::Box;
+use core::convert::TryFrom;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -11,8 +12,8 @@ pub fn keypair() -> Result<Keys, JsError> {
     let mut rng = rand::rngs::OsRng {};
     match api::keypair(&mut rng) {
         Ok(keys) => Ok(Keys {
-            pubkey: Box::new(keys.public),
-            secret: Box::new(keys.secret),
+            pubkey: Box::from(keys.public.as_ref()),
+            secret: Box::from(keys.secret.as_ref()),
         }),
         Err(KyberError::RandomBytesGeneration) => {
             Err(JsError::new("Error trying to fill random bytes"))
@@ -23,15 +24,16 @@ pub fn keypair() -> Result<Keys, JsError> {
 
 #[wasm_bindgen]
 pub fn encapsulate(pk: Box<[u8]>) -> Result<Kex, JsValue> {
-    if pk.len() != KYBER_PUBLICKEYBYTES {
-        return Err(JsValue::null());
-    }
+    let pk = match PublicKey::try_from(&pk[..]) {
+        Ok(pk) => pk,
+        Err(_) => return Err(JsValue::null()),
+    };
 
     let mut rng = rand::rngs::OsRng {};
     match api::encapsulate(&pk, &mut rng) {
         Ok(kex) => Ok(Kex {
-            ciphertext: Box::new(kex.0),
-            sharedSecret: Box::new(kex.1),
+            ciphertext: Box::from(kex.0.as_ref()),
+            sharedSecret: Box::from(kex.1.as_ref()),
         }),
         Err(_) => Err(JsValue::null()),
     }
@@ -39,12 +41,13 @@ pub fn encapsulate(pk: Box<[u8]>) -> Result<Kex, JsValue> {
 
 #[wasm_bindgen]
 pub fn decapsulate(ct: Box<[u8]>, sk: Box<[u8]>) -> Result<Box<[u8]>, JsValue> {
-    if ct.len() != KYBER_CIPHERTEXTBYTES || sk.len() != KYBER_SECRETKEYBYTES {
-        return Err(JsValue::null());
-    }
+    let (ct, sk) = match (Ciphertext::try_from(&ct[..]), SecretKey::try_from(&sk[..])) {
+        (Ok(ct), Ok(sk)) => (ct, sk),
+        _ => return Err(JsValue::null()),
+    };
 
     match api::decapsulate(&ct, &sk) {
-        Ok(ss) => Ok(Box::new(ss)),
+        Ok(ss) => Ok(Box::from(ss.as_ref())),
         Err(_) => Err(JsValue::null()),
     }
 }
@@ -0,0 +1,289 @@
+//! X-Wing hybrid KEM
+//!
+//! Combines ML-KEM-768 with X25519 as specified in draft-connolly-cfrg-xwing-kem.
+//! The shared secret is SHA3-256 over both component shared secrets, the
+//! X25519 ciphertext and the X25519 public key, it stays secure as long as
+//! either component is unbroken.
+//!
+//! The secret key is the 32 byte seed both component keypairs are derived
+//! from. X-Wing always uses ML-KEM-768, whichever security level is selected
+//! with feature flags.
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # fn main() -> Result<(), KyberError> {
+//! let mut rng = rand::thread_rng();
+//! let keys = xwing::keypair(&mut rng)?;
+//! let (ct, ss1) = xwing::encapsulate(&keys.public, &mut rng)?;
+//! let ss2 = xwing::decapsulate(&ct, &keys.secret)?;
+//! assert_eq!(ss1, ss2);
+//! # Ok(()) }
+//! ```
+//!
+//! Requires the `xwing` feature and is not available in 90s mode.
+use crate::{
+    api::DummyRng,
+    fips202::{sha3_256, shake256},
+    kem::kem_check_pk,
+    kex::Decapsulated,
+    mlkem::{mlkem_dec, mlkem_enc_derand, mlkem_keypair},
+    params::*,
+    rng::randombytes,
+    types::{array_type, public_array, secret_array},
+    CryptoRng, KyberError, RngCore, SharedSecret,
+};
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+// ML-KEM-768
+const K: usize = 3;
+const MLKEM_PUBLICKEYBYTES: usize = Params::<K>::PUBLICKEYBYTES;
+const MLKEM_SECRETKEYBYTES: usize = Params::<K>::SECRETKEYBYTES;
+const MLKEM_CIPHERTEXTBYTES: usize = Params::<K>::CIPHERTEXTBYTES;
+const X25519_BYTES: usize = 32;
+
+/// Size of an X-Wing public key, the ML-KEM-768 key followed by the X25519 key
+pub const XWING_PUBLICKEYBYTES: usize = MLKEM_PUBLICKEYBYTES + X25519_BYTES;
+/// Size of an X-Wing secret key seed
+pub const XWING_SECRETKEYBYTES: usize = 32;
+/// Size of an X-Wing ciphertext, the ML-KEM-768 ciphertext followed by the
+/// ephemeral X25519 key
+pub const XWING_CIPHERTEXTBYTES: usize = MLKEM_CIPHERTEXTBYTES + X25519_BYTES;
+
+// "\./" || "/^\"
+const XWING_LABEL: &[u8] = b"\\.//^\\";
+
+array_type!(
+    /// X-Wing public key
+    PublicKey,
+    XWING_PUBLICKEYBYTES
+);
+public_array!(PublicKey);
+
+array_type!(
+    /// X-Wing secret key seed, redacted when printed and zeroized on drop
+    /// with the `zeroize` feature
+    SecretKey,
+    XWING_SECRETKEYBYTES
+);
+secret_array!(SecretKey);
+
+array_type!(
+    /// X-Wing ciphertext
+    Ciphertext,
+    XWING_CIPHERTEXTBYTES
+);
+public_array!(Ciphertext);
+
+/// An X-Wing public/secret keypair
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Keypair {
+    pub public: PublicKey,
+    pub secret: SecretKey,
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Keypair {
+    fn zeroize(&mut self) {
+        self.public.zeroize();
+        self.secret.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Keypair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for Keypair {}
+
+/// Generates an X-Wing keypair with a provided RNG.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = xwing::keypair(&mut rng)?;
+/// # Ok(())}
+/// ```
+pub fn keypair<R>(rng: &mut R) -> Result<Keypair, KyberError>
+where
+    R: RngCore + CryptoRng,
+{
+    let mut seed = [0u8; XWING_SECRETKEYBYTES];
+    randombytes(&mut seed, XWING_SECRETKEYBYTES, rng)?;
+    derive(&seed)
+}
+
+/// Deterministically derives an X-Wing keypair from a 32 byte seed, the seed
+/// is the secret key.
+///
+/// The ML-KEM-768 keypair is derived with the FIPS 203 key schedule used by
+/// [`mlkem::derive`](crate::mlkem::derive).
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let keys = xwing::derive(&[42u8; 32])?;
+/// assert_eq!(keys.secret.as_ref(), &[42u8; 32]);
+/// # Ok(())}
+/// ```
+pub fn derive(seed: &[u8]) -> Result<Keypair, KyberError> {
+    if seed.len() != XWING_SECRETKEYBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let mut secret = SecretKey::zeroed();
+    secret.0.copy_from_slice(seed);
+    let mut public = PublicKey::zeroed();
+    let mut sk_m = [0u8; MLKEM_SECRETKEYBYTES];
+    let mut sk_x = [0u8; X25519_BYTES];
+    expand_secret_key(&mut public.0, &mut sk_m, &mut sk_x, &secret.0);
+    #[cfg(feature = "zeroize")]
+    {
+        sk_m.zeroize();
+        sk_x.zeroize();
+    }
+    Ok(Keypair { public, secret })
+}
+
+/// Encapsulates to an X-Wing public key returning the ciphertext to send
+/// and the shared secret
+///
+/// The ML-KEM-768 key must pass the FIPS 203 modulus check, otherwise
+/// [`KyberError::InvalidPublicKey`] is returned.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = xwing::keypair(&mut rng)?;
+/// let (ciphertext, shared_secret) = xwing::encapsulate(&keys.public, &mut rng)?;
+/// # Ok(())}
+/// ```
+pub fn encapsulate<R>(pk: &PublicKey, rng: &mut R) -> Result<(Ciphertext, SharedSecret), KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    let (pk_m, pk_x) = pk.0.split_at(MLKEM_PUBLICKEYBYTES);
+    kem_check_pk::<K>(pk_m)?;
+
+    let mut eseed = [0u8; 2 * KYBER_SYMBYTES];
+    randombytes(&mut eseed, 2 * KYBER_SYMBYTES, rng)?;
+    let mut ek_x = [0u8; X25519_BYTES];
+    ek_x.copy_from_slice(&eseed[KYBER_SYMBYTES..]);
+
+    let mut ct = Ciphertext::zeroed();
+    let mut ss_m = [0u8; KYBER_SSBYTES];
+    let (ct_m, ct_x) = ct.0.split_at_mut(MLKEM_CIPHERTEXTBYTES);
+    mlkem_enc_derand::<K>(ct_m, &mut ss_m, pk_m, &eseed[..KYBER_SYMBYTES]);
+
+    ct_x.copy_from_slice(&x25519(ek_x, X25519_BASEPOINT_BYTES));
+    #[allow(unused_mut)]
+    let mut ss_x = x25519(ek_x, to_array(pk_x));
+
+    let ss = combiner(&ss_m, &ss_x, ct_x, pk_x);
+    #[cfg(feature = "zeroize")]
+    {
+        eseed.zeroize();
+        ek_x.zeroize();
+        ss_m.zeroize();
+        ss_x.zeroize();
+    }
+    Ok((ct, ss))
+}
+
+/// Decapsulates an X-Wing ciphertext with a secret key.
+///
+/// Invalid ML-KEM-768 ciphertexts are implicitly rejected, the returned
+/// shared secret is then a pseudo-random value unknown to the sender.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = xwing::keypair(&mut rng)?;
+/// let (ct, ss1) = xwing::encapsulate(&keys.public, &mut rng)?;
+/// let ss2 = xwing::decapsulate(&ct, &keys.secret)?;
+/// assert_eq!(ss1, ss2);
+/// #  Ok(())}
+/// ```
+pub fn decapsulate(ct: &Ciphertext, sk: &SecretKey) -> Decapsulated {
+    let mut pk = [0u8; XWING_PUBLICKEYBYTES];
+    let mut sk_m = [0u8; MLKEM_SECRETKEYBYTES];
+    let mut sk_x = [0u8; X25519_BYTES];
+    expand_secret_key(&mut pk, &mut sk_m, &mut sk_x, &sk.0);
+
+    let (ct_m, ct_x) = ct.0.split_at(MLKEM_CIPHERTEXTBYTES);
+    let mut ss_m = [0u8; KYBER_SSBYTES];
+    mlkem_dec::<K>(&mut ss_m, ct_m, &sk_m);
+    #[allow(unused_mut)]
+    let mut ss_x = x25519(sk_x, to_array(ct_x));
+
+    let ss = combiner(&ss_m, &ss_x, ct_x, &pk[MLKEM_PUBLICKEYBYTES..]);
+    #[cfg(feature = "zeroize")]
+    {
+        sk_m.zeroize();
+        sk_x.zeroize();
+        ss_m.zeroize();
+        ss_x.zeroize();
+    }
+    Ok(ss)
+}
+
+/// Name:  expand_secret_key
+///
+/// Description: Derives both component keypairs from the X-Wing seed,
+///  SHAKE256(sk) gives the ML-KEM-768 seed (d, z) followed by the X25519
+///  secret key
+///
+/// Arguments:   - [u8] pk: output X-Wing public key (an already allocated array of XWING_PUBLICKEYBYTES bytes)
+///  - [u8] sk_m: output ML-KEM-768 secret key
+///  - [u8; 32] sk_x: output X25519 secret key
+///  - const [u8] sk: input X-Wing secret key seed
+fn expand_secret_key(pk: &mut [u8], sk_m: &mut [u8], sk_x: &mut [u8; X25519_BYTES], sk: &[u8]) {
+    let mut expanded = [0u8; 3 * KYBER_SYMBYTES];
+    shake256(&mut expanded, 3 * KYBER_SYMBYTES, sk, XWING_SECRETKEYBYTES);
+
+    let (pk_m, pk_x) = pk.split_at_mut(MLKEM_PUBLICKEYBYTES);
+    let seed = (
+        &expanded[..KYBER_SYMBYTES],
+        &expanded[KYBER_SYMBYTES..2 * KYBER_SYMBYTES],
+    );
+    // Can't fail when seeded
+    let _ = mlkem_keypair::<K, _>(pk_m, sk_m, &mut DummyRng {}, Some(seed));
+
+    sk_x.copy_from_slice(&expanded[2 * KYBER_SYMBYTES..]);
+    pk_x.copy_from_slice(&x25519(*sk_x, X25519_BASEPOINT_BYTES));
+    #[cfg(feature = "zeroize")]
+    expanded.zeroize();
+}
+
+// SHA3-256(ss_M || ss_X || ct_X || pk_X || XWingLabel)
+fn combiner(ss_m: &[u8], ss_x: &[u8], ct_x: &[u8], pk_x: &[u8]) -> SharedSecret {
+    const LEN: usize = 4 * 32 + 6;
+    let mut buf = [0u8; LEN];
+    buf[..32].copy_from_slice(ss_m);
+    buf[32..64].copy_from_slice(ss_x);
+    buf[64..96].copy_from_slice(ct_x);
+    buf[96..128].copy_from_slice(pk_x);
+    buf[128..].copy_from_slice(XWING_LABEL);
+    let mut ss = SharedSecret::zeroed();
+    sha3_256(&mut ss.0, &buf, LEN);
+    #[cfg(feature = "zeroize")]
+    buf.zeroize();
+    ss
+}
+
+fn to_array(x: &[u8]) -> [u8; X25519_BYTES] {
+    let mut out = [0u8; X25519_BYTES];
+    out.copy_from_slice(x);
+    out
+}
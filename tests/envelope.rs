@@ -0,0 +1,102 @@
+#![cfg(feature = "envelope")]
+
+use pqc_kyber::envelope::*;
+use pqc_kyber::*;
+
+const MSG: &[u8] = b"Beauty is truth, truth beauty";
+const AAD: &[u8] = b"Count-0";
+
+#[test]
+fn envelope_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = (0..3)
+        .map(|_| keypair(&mut rng).unwrap())
+        .collect::<Vec<_>>();
+    let recipients = keys.iter().map(|k| k.public).collect::<Vec<_>>();
+    let sealed = seal(&recipients, MSG, AAD, &mut rng).unwrap();
+    assert_eq!(
+        sealed.len(),
+        2 + 3 * ENVELOPE_RECIPIENTBYTES + MSG.len() + sealedbox::SEALEDBOX_TAGBYTES
+    );
+    for k in keys.iter() {
+        assert_eq!(open(&k.secret, &sealed, AAD).unwrap(), MSG);
+    }
+}
+
+#[test]
+fn envelope_key_ids() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    assert_eq!(key_id(&keys.public), secret_key_id(&keys.secret));
+    let sealed = seal(&[keys.public], MSG, AAD, &mut rng).unwrap();
+    assert_eq!(&sealed[2..2 + ENVELOPE_KEYIDBYTES], &key_id(&keys.public));
+}
+
+#[test]
+fn envelope_not_a_recipient() {
+    let mut rng = rand::thread_rng();
+    let alice = keypair(&mut rng).unwrap();
+    let eve = keypair(&mut rng).unwrap();
+    let sealed = seal(&[alice.public], MSG, AAD, &mut rng).unwrap();
+    assert_eq!(
+        open(&eve.secret, &sealed, AAD),
+        Err(KyberError::RecipientNotFound)
+    );
+}
+
+#[test]
+fn envelope_tampering() {
+    let mut rng = rand::thread_rng();
+    let alice = keypair(&mut rng).unwrap();
+    let bob = keypair(&mut rng).unwrap();
+    let sealed = seal(&[alice.public, bob.public], MSG, AAD, &mut rng).unwrap();
+
+    // Alice's wrapped key, Bob's entry, the message and the tag
+    let bob_entry = 2 + ENVELOPE_RECIPIENTBYTES + ENVELOPE_KEYIDBYTES;
+    for i in [
+        2 + ENVELOPE_KEYIDBYTES,
+        bob_entry,
+        sealed.len() - MSG.len(),
+        sealed.len() - 1,
+    ] {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 1;
+        assert_eq!(
+            open(&alice.secret, &tampered, AAD),
+            Err(KyberError::InvalidSealedBox)
+        );
+    }
+    assert_eq!(
+        open(&alice.secret, &sealed, b"other aad"),
+        Err(KyberError::InvalidSealedBox)
+    );
+
+    // Dropping Bob's entry
+    let mut dropped = sealed[..2 + ENVELOPE_RECIPIENTBYTES].to_vec();
+    dropped[1] = 1;
+    dropped.extend_from_slice(&sealed[2 + 2 * ENVELOPE_RECIPIENTBYTES..]);
+    assert_eq!(
+        open(&alice.secret, &dropped, AAD),
+        Err(KyberError::InvalidSealedBox)
+    );
+}
+
+#[test]
+fn envelope_invalid_inputs() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    assert_eq!(seal(&[], MSG, AAD, &mut rng), Err(KyberError::InvalidInput));
+    let sealed = seal(&[keys.public], MSG, AAD, &mut rng).unwrap();
+    for len in [0, 1, 2 + ENVELOPE_RECIPIENTBYTES] {
+        assert_eq!(
+            open(&keys.secret, &sealed[..len], AAD),
+            Err(KyberError::InvalidInput)
+        );
+    }
+    let mut empty = sealed.clone();
+    empty[1] = 0;
+    assert_eq!(
+        open(&keys.secret, &empty, AAD),
+        Err(KyberError::InvalidInput)
+    );
+}
@@ -0,0 +1,112 @@
+use pqc_kyber::handshake::{ake, uake};
+use pqc_kyber::*;
+mod utils;
+use utils::*;
+
+#[test]
+fn uake_valid() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let (alice, client_init) = uake::initiate(KexVersion::V1, &bob_keys.public, &mut rng).unwrap();
+    let (bob, server_send) =
+        uake::respond(KexVersion::V1, client_init, &bob_keys.secret, &mut rng).unwrap();
+    let alice = alice.finish(server_send).unwrap();
+    let bob = bob.finish();
+    assert_eq!(alice, bob);
+}
+
+// Same protocol as the Uake struct
+#[test]
+fn uake_matches_struct() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let (alice, client_init) = uake::initiate(KexVersion::V2, &bob_keys.public, &mut rng).unwrap();
+    let mut bob: Uake = Uake::with_version(KexVersion::V2);
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let alice = alice.finish(server_send).unwrap();
+    assert_eq!(alice.shared_secret(), &bob.shared_secret);
+}
+
+#[test]
+fn uake_confirm_invalid_server_send_ciphertext() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let (alice, client_init) = uake::initiate(KexVersion::V2, &bob_keys.public, &mut rng).unwrap();
+    let (bob, mut server_send) =
+        uake::respond(KexVersion::V2, client_init, &bob_keys.secret, &mut rng).unwrap();
+    server_send[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        alice.confirm(server_send, &bob.tag()).map(|_| ()),
+        Err(KyberError::KeyConfirmation)
+    );
+}
+
+#[test]
+fn ake_confirm_valid() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let (alice, client_init) = ake::initiate(KexVersion::V2, &bob_keys.public, &mut rng).unwrap();
+    let (bob, server_send) = ake::respond(
+        KexVersion::V2,
+        client_init,
+        &alice_keys.public,
+        &bob_keys.secret,
+        &mut rng,
+    )
+    .unwrap();
+    let (alice, client_tag) = alice
+        .confirm(server_send, &bob.tag(), &alice_keys.secret)
+        .unwrap();
+    let bob = bob.confirm(&client_tag).unwrap();
+    assert_eq!(alice.into_shared_secret(), bob.into_shared_secret());
+}
+
+// Bob authenticates the wrong client key, detected by the server's tag
+#[test]
+fn ake_confirm_wrong_client_key() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let other_keys = keypair(&mut rng).unwrap();
+    let (alice, client_init) = ake::initiate(KexVersion::V2, &bob_keys.public, &mut rng).unwrap();
+    let (bob, server_send) = ake::respond(
+        KexVersion::V2,
+        client_init,
+        &other_keys.public,
+        &bob_keys.secret,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(
+        alice
+            .confirm(server_send, &bob.tag(), &alice_keys.secret)
+            .map(|_| ()),
+        Err(KyberError::KeyConfirmation)
+    );
+}
+
+#[test]
+fn server_confirm_invalid_client_tag() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let (_, client_init) = uake::initiate(KexVersion::V2, &bob_keys.public, &mut rng).unwrap();
+    let (bob, _) = uake::respond(KexVersion::V2, client_init, &bob_keys.secret, &mut rng).unwrap();
+    assert_eq!(
+        bob.confirm(&[0u8; CONFIRM_TAG_BYTES]),
+        Err(KyberError::KeyConfirmation)
+    );
+}
+
+#[test]
+fn ake_failed_randombytes_initiate() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut rng = FailingRng::default();
+    assert_eq!(
+        ake::initiate(KexVersion::V1, &bob_keys.public, &mut rng).map(|_| ()),
+        Err(KyberError::RandomBytesGeneration)
+    );
+}
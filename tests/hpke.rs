@@ -0,0 +1,184 @@
+#![cfg(feature = "hpke")]
+
+use pqc_kyber::hpke::*;
+use pqc_kyber::*;
+mod utils;
+
+const INFO: &[u8] = b"Ode on a Grecian Urn";
+const AAD: &[u8] = b"Count-0";
+const PT: &[u8] = b"Beauty is truth, truth beauty";
+const PSK: &str = "0247fd33b913760fa1fa51e1892d9f307fbe65eb171e8132c2af18555a738b82";
+const PSK_ID: &[u8] = b"Ennyn Durin aran Moria";
+
+#[cfg(all(
+    not(feature = "kyber512"),
+    not(feature = "kyber1024"),
+    not(feature = "90s")
+))]
+mod vectors {
+    use super::*;
+    use utils::*;
+
+    // Kyber768 vectors. The DeriveKeyPair seed, key schedule and AEAD outputs
+    // were computed independently from the shared secret with a Python
+    // implementation of RFC 9180 (pyca/cryptography), the KEM outputs come from
+    // this crate and are covered by the KATs.
+    struct Vector {
+        ikm: &'static str,
+        seed: &'static str,
+        m: &'static str,
+        ss: &'static str,
+        psk: bool,
+        // AES-128-GCM and ChaCha20-Poly1305 ciphertext and tag
+        aes: (&'static str, &'static str),
+        chacha: (&'static str, &'static str),
+    }
+
+    const VECTORS: [Vector; 2] = [
+        Vector {
+            ikm: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            seed: "58a6b69331a666a1830ef5441881524e6399fffe864d96f4304ad1c15609fdf8a52ded6257fad2a872f4171edc5ffcc01ef760478a4e65a73988ea10aa4e3ca2",
+            m: "1111111111111111111111111111111111111111111111111111111111111111",
+            ss: "96b7044a26e22008a59e19a94a68d5151674fc0b21f782844b9501261b587439",
+            psk: false,
+            aes: (
+                "908dc107395eff799fb280c21d8524b348b8f0ab694b51ef35b71f656f",
+                "b9305730bf97550228ff9141d8c31ce0",
+            ),
+            chacha: (
+                "ad910a47c535c9eceb8dabace28efc73f3ff4a408312d10fe25a6faf73",
+                "dde1a190c21225f8ae2312e64b8a573d",
+            ),
+        },
+        Vector {
+            ikm: "6465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9fa0a1a2a3",
+            seed: "104b32587a3135aaca769de2df722765ee668a5dce308c30ecca97772c9fdf113a1099ee3575804bfd3dacb7b491309d8f1de69b54b16224b4828497141fc9a3",
+            m: "a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5",
+            ss: "445826beffd7e6ce7cb5a27588b8a5f457dfa83f647c2fc04e5211c49a38eb71",
+            psk: true,
+            aes: (
+                "558796bef05959d28fb2881b51fe20a9a076427b1ec278549eda30c20d",
+                "c1c57214c1263d1990ffc14db8a09b0d",
+            ),
+            chacha: (
+                "b29de2bfa6b249b01d94373fe15ef83fa0743912f7f7a301c0ad975df4",
+                "fc5f2d66b634c1861a901705a45e17c5",
+            ),
+        },
+    ];
+
+    #[test]
+    #[cfg(all(
+        not(feature = "kyber512"),
+        not(feature = "kyber1024"),
+        not(feature = "90s")
+    ))]
+    fn hpke_vectors() {
+        let psk = decode_hex(PSK);
+        for v in VECTORS.iter() {
+            let keys = derive_keypair(&decode_hex(v.ikm)).unwrap();
+            assert_eq!(
+                keys,
+                derive(&decode_hex(v.seed)).unwrap(),
+                "DeriveKeyPair mismatch"
+            );
+
+            for (aead, (ct, tag)) in [(Aead::Aes128Gcm, v.aes), (Aead::ChaCha20Poly1305, v.chacha)]
+            {
+                let hpke = match v.psk {
+                    true => Hpke::psk(aead, &psk, PSK_ID).unwrap(),
+                    false => Hpke::base(aead),
+                };
+                let m = decode_hex(v.m);
+                let mut buf = PT.to_vec();
+                let (enc, t) = hpke
+                    .seal(&keys.public, INFO, AAD, &mut buf, &mut BufferRng(&m))
+                    .unwrap();
+                assert_eq!(
+                    decapsulate(&enc, &keys.secret).unwrap().as_ref(),
+                    &decode_hex(v.ss)[..]
+                );
+                assert_eq!(buf, decode_hex(ct), "Ciphertext mismatch");
+                assert_eq!(t.to_vec(), decode_hex(tag), "Tag mismatch");
+
+                hpke.open(&enc, &keys.secret, INFO, AAD, &mut buf, &t)
+                    .unwrap();
+                assert_eq!(buf, PT);
+            }
+        }
+    }
+}
+
+#[test]
+fn hpke_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let psk = decode_hex(PSK);
+    for aead in [Aead::Aes128Gcm, Aead::ChaCha20Poly1305] {
+        for hpke in [Hpke::base(aead), Hpke::psk(aead, &psk, PSK_ID).unwrap()] {
+            let mut buf = PT.to_vec();
+            let (enc, tag) = hpke
+                .seal(&keys.public, INFO, AAD, &mut buf, &mut rng)
+                .unwrap();
+            assert_ne!(buf, PT);
+            hpke.open(&enc, &keys.secret, INFO, AAD, &mut buf, &tag)
+                .unwrap();
+            assert_eq!(buf, PT);
+        }
+    }
+}
+
+#[test]
+fn hpke_open_failures() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let psk = decode_hex(PSK);
+    let hpke = Hpke::psk(Aead::Aes128Gcm, &psk, PSK_ID).unwrap();
+    let mut buf = PT.to_vec();
+    let (mut enc, mut tag) = hpke
+        .seal(&keys.public, INFO, AAD, &mut buf, &mut rng)
+        .unwrap();
+    let sealed = buf.clone();
+
+    let fails = |hpke: Hpke, enc: &Ciphertext, info: &[u8], aad: &[u8], tag: &Tag| {
+        let mut buf = sealed.clone();
+        let res = hpke.open(enc, &keys.secret, info, aad, &mut buf, tag);
+        assert_eq!(res, Err(KyberError::InvalidHpkeMessage));
+        assert_eq!(buf, sealed);
+    };
+    fails(Hpke::base(Aead::Aes128Gcm), &enc, INFO, AAD, &tag);
+    fails(
+        Hpke::psk(Aead::Aes128Gcm, &[1u8; 32], PSK_ID).unwrap(),
+        &enc,
+        INFO,
+        AAD,
+        &tag,
+    );
+    fails(hpke, &enc, b"other info", AAD, &tag);
+    fails(hpke, &enc, INFO, b"other aad", &tag);
+    tag[0] ^= 1;
+    fails(hpke, &enc, INFO, AAD, &tag);
+    tag[0] ^= 1;
+    enc.as_mut()[0] ^= 1;
+    fails(hpke, &enc, INFO, AAD, &tag);
+}
+
+#[test]
+fn hpke_invalid_inputs() {
+    assert_eq!(derive_keypair(&[0u8; 31]), Err(KyberError::InvalidInput));
+    assert!(matches!(
+        Hpke::psk(Aead::Aes128Gcm, &[0u8; 31], PSK_ID),
+        Err(KyberError::InvalidInput)
+    ));
+    assert!(matches!(
+        Hpke::psk(Aead::Aes128Gcm, &[0u8; 32], b""),
+        Err(KyberError::InvalidInput)
+    ));
+}
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("Hex string decoding"))
+        .collect::<Vec<u8>>()
+}
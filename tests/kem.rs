@@ -1,4 +1,5 @@
 use pqc_kyber::*;
+use std::convert::TryFrom;
 mod utils;
 use utils::*;
 
@@ -14,36 +15,42 @@ fn keypair_encap_decap() {
 fn keypair_import_fake() {
     let mut rng = rand::thread_rng();
     let mut keys = keypair(&mut rng).unwrap();
-    let key = keypairfrom(&mut keys.public, &mut keys.secret, &mut rng).unwrap();
+    keypairfrom(&mut keys.public, &mut keys.secret, &mut rng).unwrap();
 }
 #[test]
 fn keypair_encap_decap_invalid_ciphertext() {
     let mut rng = rand::thread_rng();
     let keys = keypair(&mut rng).unwrap();
     let (mut ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
-    ct[..4].copy_from_slice(&[255u8; 4]);
+    ct.as_mut()[..4].copy_from_slice(&[255u8; 4]);
     assert!(decapsulate(&ct, &keys.secret).unwrap() != ss);
 }
 
 #[test]
 fn keypair_encap_pk_wrong_size() {
-    let mut rng = rand::thread_rng();
     let pk: [u8; KYBER_PUBLICKEYBYTES + 3] = [1u8; KYBER_PUBLICKEYBYTES + 3];
-    assert_eq!(encapsulate(&pk, &mut rng), Err(KyberError::InvalidInput));
+    assert_eq!(
+        <PublicKey>::try_from(&pk[..]),
+        Err(KyberError::InvalidInput)
+    );
 }
 
 #[test]
 fn keypair_decap_ct_wrong_size() {
     let ct: [u8; KYBER_CIPHERTEXTBYTES + 3] = [1u8; KYBER_CIPHERTEXTBYTES + 3];
-    let sk: [u8; KYBER_SECRETKEYBYTES] = [1u8; KYBER_SECRETKEYBYTES];
-    assert_eq!(decapsulate(&ct, &sk), Err(KyberError::InvalidInput));
+    assert_eq!(
+        <Ciphertext>::try_from(&ct[..]),
+        Err(KyberError::InvalidInput)
+    );
 }
 
 #[test]
 fn keypair_decap_sk_wrong_size() {
-    let ct: [u8; KYBER_CIPHERTEXTBYTES] = [1u8; KYBER_CIPHERTEXTBYTES];
     let sk: [u8; KYBER_SECRETKEYBYTES + 3] = [1u8; KYBER_SECRETKEYBYTES + 3];
-    assert_eq!(decapsulate(&ct, &sk), Err(KyberError::InvalidInput));
+    assert_eq!(
+        <SecretKey>::try_from(&sk[..]),
+        Err(KyberError::InvalidInput)
+    );
 }
 
 #[test]
@@ -63,6 +70,32 @@ fn keypair_encap_failed_randombytes() {
     );
 }
 
+#[test]
+fn prepared_pk_modulus_check() {
+    let mut rng = rand::thread_rng();
+    let mut keys = keypair(&mut rng).unwrap();
+    keys.public.as_mut()[0] = 0xff;
+    keys.public.as_mut()[1] |= 0x0f;
+    assert_eq!(
+        PreparedPublicKey::new(&keys.public).err(),
+        Some(KyberError::InvalidPublicKey)
+    );
+}
+
+#[test]
+fn prepared_sk_hash_check() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut sk = keys.secret.clone();
+    sk.as_mut()[KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES] ^= 1;
+    assert_eq!(
+        PreparedSecretKey::new(&sk).err(),
+        Some(KyberError::InvalidSecretKey)
+    );
+    let prepared = PreparedSecretKey::new(&keys.secret).unwrap();
+    assert_eq!(format!("{:?}", prepared), "PreparedSecretKey(<redacted>)");
+}
+
 #[test]
 fn public_from_private() {
     let mut rng = rand::thread_rng();
@@ -70,3 +103,150 @@ fn public_from_private() {
     let pk2 = public(&keys.secret);
     assert_eq!(pk2, keys.public);
 }
+
+// Public key with a coefficient of 4095, not reduced modulo q
+#[test]
+fn encap_pk_modulus_check() {
+    let mut rng = rand::thread_rng();
+    let mut keys = keypair(&mut rng).unwrap();
+    keys.public.as_mut()[0] = 0xff;
+    keys.public.as_mut()[1] |= 0x0f;
+    assert_eq!(
+        encapsulate(&keys.public, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+}
+
+#[test]
+fn decap_sk_hash_check() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut rng).unwrap();
+
+    // Corrupted H(pk)
+    let mut sk = keys.secret.clone();
+    sk.as_mut()[KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES] ^= 1;
+    assert_eq!(decapsulate(&ct, &sk), Err(KyberError::InvalidSecretKey));
+
+    // Corrupted embedded public key
+    let mut sk = keys.secret.clone();
+    sk.as_mut()[KYBER_SECRETKEYBYTES - KYBER_PUBLICKEYBYTES - 2 * KYBER_SYMBYTES] ^= 1;
+    assert_eq!(decapsulate(&ct, &sk), Err(KyberError::InvalidSecretKey));
+}
+
+#[test]
+fn types_from_bytes() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(<PublicKey>::try_from(keys.public.as_ref()), Ok(keys.public));
+    assert_eq!(
+        SecretKey::try_from(keys.secret.as_ref()).as_ref(),
+        Ok(&keys.secret)
+    );
+    assert_eq!(<Ciphertext>::try_from(ct.as_ref()), Ok(ct));
+    assert_eq!(SharedSecret::try_from(ss.as_ref()).as_ref(), Ok(&ss));
+    assert_eq!(
+        SharedSecret::try_from(&[0u8; 31][..]),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn secrets_not_printed() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (_, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(format!("{:?}", keys.secret), "SecretKey(<redacted>)");
+    assert_eq!(format!("{:?}", ss), "SharedSecret(<redacted>)");
+    let hex: String = keys.public.as_ref()[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    assert!(format!("{:?}", keys).contains(&hex));
+}
+
+#[test]
+fn seed_secret_key() {
+    let mut rng = rand::thread_rng();
+    let seeded = Keypair::generate_with_seed(&mut rng).unwrap();
+    let seed = seeded.to_seed();
+    let keys = seeded.clone().into_keypair();
+    assert_eq!(SeedSecretKey::<DefaultParams>::LEN, 64);
+    assert_eq!(derive(seed.as_ref()).unwrap(), keys);
+    assert_eq!(seed.expand(), keys);
+    assert_eq!(seed.public_key(), keys.public);
+    // The same keys as drawing the seed from the RNG
+    assert_eq!(keypair(&mut BufferRng(seed.as_ref())).unwrap(), keys);
+
+    let (ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(seed.decapsulate(&ct), Ok(ss.clone()));
+    assert_eq!(decapsulate(&ct, &seed), Ok(ss));
+    let stored = SeedSecretKey::try_from(seed.as_ref()).unwrap();
+    assert_eq!(Keypair::from_seed(&stored), seeded);
+    assert_eq!(*Keypair::from_seed(&stored), keys);
+    assert_eq!(format!("{:?}", stored), "SeedSecretKey(<redacted>)");
+    assert_eq!(
+        SeedSecretKey::<DefaultParams>::try_from(&[0u8; 63][..]),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn decapsulate_reports_validity() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (mut ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    let (ss2, valid) = decapsulate_checked(&ct, &keys.secret).unwrap();
+    assert!(bool::from(valid));
+    assert_eq!(ss2, ss);
+    assert_eq!(decapsulate_explicit(&ct, &keys.secret), Ok(ss));
+
+    // Invalid ciphertexts get the implicit rejection secret or an error
+    ct.as_mut()[0] ^= 1;
+    let (ss3, valid) = decapsulate_checked(&ct, &keys.secret).unwrap();
+    assert!(!bool::from(valid));
+    assert_eq!(decapsulate(&ct, &keys.secret), Ok(ss3));
+    assert_eq!(
+        decapsulate_explicit(&ct, &keys.secret),
+        Err(KyberError::Decapsulation)
+    );
+}
+
+#[test]
+fn context_bound_secrets() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    for len in [0, 1, 31, 32, 100, 1000] {
+        let mut out1 = vec![0u8; len];
+        let mut out2 = vec![0u8; len];
+        let ct = encapsulate_with_context(&keys.public, b"ctx", &mut out1, &mut rng).unwrap();
+        decapsulate_with_context(&ct, &keys.secret, b"ctx", &mut out2).unwrap();
+        assert_eq!(out1, out2);
+
+        // Different contexts give unrelated keys
+        if len > 0 {
+            decapsulate_with_context(&ct, &keys.secret, b"ctx2", &mut out2).unwrap();
+            assert_ne!(out1, out2);
+        }
+    }
+
+    // Implicit rejection still applies
+    let mut out1 = [0u8; 48];
+    let mut out2 = [0u8; 48];
+    let mut ct = encapsulate_with_context(&keys.public, b"", &mut out1, &mut rng).unwrap();
+    ct.as_mut()[0] ^= 1;
+    decapsulate_with_context(&ct, &keys.secret, b"", &mut out2).unwrap();
+    assert_ne!(out1, out2);
+}
+
+#[test]
+fn context_empty_is_not_shared_secret() {
+    // The context KDF is domain separated from the Kyber KDF
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    let mut out = [0u8; KYBER_SSBYTES];
+    decapsulate_with_context(&ct, &keys.secret, b"", &mut out).unwrap();
+    assert_ne!(&out[..], ss.as_ref());
+}
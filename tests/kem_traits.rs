@@ -0,0 +1,50 @@
+#![cfg(feature = "kem-traits")]
+
+use kem::{Decapsulate, Encapsulate};
+use pqc_kyber::*;
+
+// Only uses the kem traits, as code generic over KEMs would
+fn round_trip<E, D, EK, SS>(ek: &E, dk: &D) -> (SS, SS)
+where
+    E: Encapsulate<EK, SS>,
+    D: Decapsulate<EK, SS>,
+{
+    let mut rng = rand::thread_rng();
+    let (ct, ss1) = ek.encapsulate(&mut rng).unwrap();
+    let ss2 = dk.decapsulate(&ct).unwrap();
+    (ss1, ss2)
+}
+
+#[test]
+fn kem_traits_round_trip() {
+    let keys = keypair(&mut rand::thread_rng()).unwrap();
+    let (ss1, ss2) = round_trip(&keys.public, &keys.secret);
+    assert_eq!(ss1, ss2);
+    // Method calls resolve with both the traits and the crate in scope
+    let (ct, ss) = keys.public.encapsulate(&mut rand::thread_rng()).unwrap();
+    assert_eq!(keys.secret.decapsulate(&ct).unwrap(), ss);
+}
+
+#[test]
+fn kem_traits_other_level() {
+    let keys = Kyber1024::keypair(&mut rand::thread_rng()).unwrap();
+    let (ss1, ss2) = round_trip(&keys.public, &keys.secret);
+    assert_eq!(ss1, ss2);
+}
+
+#[test]
+fn kem_traits_errors() {
+    let mut rng = rand::thread_rng();
+    let mut keys = keypair(&mut rng).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut rng).unwrap();
+    keys.secret.as_mut()[KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES] ^= 1;
+    assert_eq!(
+        Decapsulate::<Ciphertext, SharedSecret>::decapsulate(&keys.secret, &ct),
+        Err(KyberError::InvalidSecretKey)
+    );
+    keys.public.as_mut()[..2].copy_from_slice(&[255u8; 2]);
+    assert_eq!(
+        Encapsulate::<Ciphertext, SharedSecret>::encapsulate(&keys.public, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+}
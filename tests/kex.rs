@@ -1,3 +1,4 @@
+use core::convert::TryFrom;
 use pqc_kyber::*;
 mod utils;
 use utils::*;
@@ -26,9 +27,9 @@ fn uake_invalid_client_init_ciphertext() {
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
     client_init[KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
-    assert!(!bob
+    assert!(bob
         .server_receive(client_init, &bob_keys.secret, &mut rng)
-        .is_err());
+        .is_ok());
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
@@ -40,14 +41,34 @@ fn uake_invalid_client_init_publickey() {
     let mut bob = Uake::new();
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
-    client_init[..4].copy_from_slice(&[255u8; 4]);
+    client_init[..4].copy_from_slice(&[0u8; 4]);
     let server_send = bob
         .server_receive(client_init, &bob_keys.secret, &mut rng)
         .unwrap();
-    assert!(!alice.client_confirm(server_send).is_err());
+    assert!(alice.client_confirm(server_send).is_ok());
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
+// Public keys with a coefficient not reduced mod q are rejected
+#[test]
+fn uake_unreduced_publickey() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let mut bob_keys = keypair(&mut rng).unwrap();
+    let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    client_init[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        bob.server_receive(client_init, &bob_keys.secret, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+    bob_keys.public.as_mut()[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        alice.client_init(&bob_keys.public, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+}
+
 // Corrupted ciphertext sent back to Alice
 #[test]
 fn uake_invalid_server_send_ciphertext() {
@@ -60,7 +81,218 @@ fn uake_invalid_server_send_ciphertext() {
         .server_receive(client_init, &bob_keys.secret, &mut rng)
         .unwrap();
     server_send[..4].copy_from_slice(&[255u8; 4]);
-    assert!(!alice.client_confirm(server_send).is_err());
+    assert!(alice.client_confirm(server_send).is_ok());
+    assert_ne!(alice.shared_secret, bob.shared_secret);
+}
+
+// Transcript bound derivation
+#[test]
+fn uake_v2_valid() {
+    let mut rng = rand::thread_rng();
+    let mut alice: Uake = Uake::with_version(KexVersion::V2);
+    let mut bob: Uake = Uake::with_version(KexVersion::V2);
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice.client_confirm(server_send).unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+// Both parties have to agree on the version
+#[test]
+fn uake_version_mismatch() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob: Uake = Uake::with_version(KexVersion::V2);
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice.client_confirm(server_send).unwrap();
+    assert_ne!(alice.shared_secret, bob.shared_secret);
+}
+
+// Key confirmation round
+#[test]
+fn uake_confirm_valid() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let (server_send, server_tag) = bob
+        .server_receive_with_tag(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let client_tag = alice
+        .client_confirm_with_tag(server_send, &server_tag)
+        .unwrap();
+    bob.server_confirm(&client_tag).unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+// Corrupted ciphertext sent back to Alice, detected by the server's tag
+#[test]
+fn uake_confirm_invalid_server_send_ciphertext() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let (mut server_send, server_tag) = bob
+        .server_receive_with_tag(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    server_send[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        alice.client_confirm_with_tag(server_send, &server_tag),
+        Err(KyberError::KeyConfirmation)
+    );
+    assert_eq!(alice.shared_secret.as_ref(), &[0u8; KYBER_SSBYTES]);
+}
+
+// Corrupted ciphertext sent to Bob, detected by the client's tag after
+// Alice rejects the server's tag
+#[test]
+fn uake_confirm_invalid_client_tag() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let (server_send, server_tag) = bob
+        .server_receive_with_tag(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let mut client_tag = alice
+        .client_confirm_with_tag(server_send, &server_tag)
+        .unwrap();
+    client_tag[0] ^= 1;
+    assert_eq!(
+        bob.server_confirm(&client_tag),
+        Err(KyberError::KeyConfirmation)
+    );
+    assert_eq!(bob.shared_secret.as_ref(), &[0u8; KYBER_SSBYTES]);
+}
+
+// No exchange to confirm, a tag keyed with an all-zero secret used to pass
+#[test]
+fn uake_server_confirm_without_exchange() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let mut carol = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let (server_send, server_tag) = bob
+        .server_receive_with_tag(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let client_tag = alice
+        .client_confirm_with_tag(server_send, &server_tag)
+        .unwrap();
+    assert_eq!(
+        carol.server_confirm(&client_tag),
+        Err(KyberError::KeyConfirmation)
+    );
+    assert_eq!(
+        carol.server_confirm(&[0u8; CONFIRM_TAG_BYTES]),
+        Err(KyberError::KeyConfirmation)
+    );
+}
+
+// Early key, available before the exchange completes
+#[test]
+fn uake_early_key() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    assert_eq!(alice.early_key(), None);
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let early_key = alice.early_key().unwrap().clone();
+    assert_eq!(bob.early_key(), None);
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    assert_eq!(Some(&early_key), bob.early_key());
+    alice.client_confirm(server_send).unwrap();
+    assert_eq!(alice.early_key(), bob.early_key());
+    assert_ne!(alice.early_key(), Some(&alice.shared_secret));
+}
+
+// The same client_init received with different pre-shared keys
+#[test]
+fn uake_early_key_psk() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::with_psk(KexVersion::V2, psk(1), b"id");
+    let mut bob = Uake::with_psk(KexVersion::V2, psk(1), b"id");
+    let mut carol = Uake::with_psk(KexVersion::V2, psk(2), b"id");
+    let mut dave = Uake::with_version(KexVersion::V2);
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    for server in [&mut bob, &mut carol, &mut dave] {
+        server
+            .server_receive(client_init, &bob_keys.secret, &mut rng)
+            .unwrap();
+    }
+    assert_eq!(alice.early_key(), bob.early_key());
+    assert_ne!(bob.early_key(), carol.early_key());
+    assert_ne!(bob.early_key(), dave.early_key());
+    assert_ne!(carol.early_key(), dave.early_key());
+}
+
+// Corrupted ciphertext sent to bob, the early keys differ
+#[test]
+fn uake_early_key_invalid_client_init_ciphertext() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    client_init[KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
+    bob.server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    assert_ne!(alice.early_key(), bob.early_key());
+}
+
+// Pre-shared key mode
+fn uake_psk_run(alice: &mut Uake, bob: &mut Uake) {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice.client_confirm(server_send).unwrap();
+}
+
+fn psk(byte: u8) -> PresharedKey {
+    PresharedKey::try_from(&[byte; 32][..]).unwrap()
+}
+
+#[test]
+fn uake_psk_valid() {
+    let mut alice = Uake::with_psk(KexVersion::V2, psk(1), b"id");
+    let mut bob = Uake::with_psk(KexVersion::V2, psk(1), b"id");
+    uake_psk_run(&mut alice, &mut bob);
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn uake_psk_mismatch() {
+    let mut alice = Uake::with_psk(KexVersion::V1, psk(1), b"");
+    let mut bob = Uake::with_psk(KexVersion::V1, psk(2), b"");
+    uake_psk_run(&mut alice, &mut bob);
+    assert_ne!(alice.shared_secret, bob.shared_secret);
+
+    let mut alice = Uake::with_psk(KexVersion::V1, psk(1), b"id 1");
+    let mut bob = Uake::with_psk(KexVersion::V1, psk(1), b"id 2");
+    uake_psk_run(&mut alice, &mut bob);
+    assert_ne!(alice.shared_secret, bob.shared_secret);
+
+    let mut alice = Uake::with_psk(KexVersion::V1, psk(1), b"");
+    let mut bob = Uake::new();
+    uake_psk_run(&mut alice, &mut bob);
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
@@ -77,7 +309,7 @@ fn ake_valid() {
     let server_send = bob
         .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
         .unwrap();
-    let _client_confirm = alice
+    alice
         .client_confirm(server_send, &alice_keys.secret)
         .unwrap();
     assert_eq!(alice.shared_secret, bob.shared_secret);
@@ -92,9 +324,9 @@ fn ake_invalid_client_init_ciphertext() {
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
     client_init[KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
-    assert!(!bob
+    assert!(bob
         .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
-        .is_err());
+        .is_ok());
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
@@ -106,16 +338,42 @@ fn ake_invalid_client_init_publickey() {
     let alice_keys = keypair(&mut rng).unwrap();
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
-    client_init[..4].copy_from_slice(&[255u8; 4]);
+    client_init[..4].copy_from_slice(&[0u8; 4]);
     let server_send = bob
         .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
         .unwrap();
-    assert!(!alice
+    assert!(alice
         .client_confirm(server_send, &alice_keys.secret)
-        .is_err());
+        .is_ok());
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
+#[test]
+fn ake_unreduced_publickey() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Ake::new();
+    let mut bob = Ake::new();
+    let mut alice_keys = keypair(&mut rng).unwrap();
+    let mut bob_keys = keypair(&mut rng).unwrap();
+    let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    client_init[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        bob.server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    alice_keys.public.as_mut()[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        bob.server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+    bob_keys.public.as_mut()[..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        alice.client_init(&bob_keys.public, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+}
+
 #[test]
 fn ake_invalid_server_send_first_ciphertext() {
     let mut rng = rand::thread_rng();
@@ -128,9 +386,9 @@ fn ake_invalid_server_send_first_ciphertext() {
         .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
         .unwrap();
     server_send[..4].copy_from_slice(&[255u8; 4]);
-    assert!(!alice
+    assert!(alice
         .client_confirm(server_send, &alice_keys.secret)
-        .is_err());
+        .is_ok());
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
@@ -149,6 +407,113 @@ fn ake_invalid_server_send_second_ciphertext() {
     // assert!(alice.client_confirm(server_send, &alice_keys.secret).is_err());
 }
 
+#[test]
+fn ake_v2_valid() {
+    let mut rng = rand::thread_rng();
+    let mut alice: Ake = Ake::with_version(KexVersion::V2);
+    let mut bob: Ake = Ake::with_version(KexVersion::V2);
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice
+        .client_confirm(server_send, &alice_keys.secret)
+        .unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn ake_version_mismatch() {
+    let mut rng = rand::thread_rng();
+    let mut alice: Ake = Ake::with_version(KexVersion::V2);
+    let mut bob = Ake::new();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice
+        .client_confirm(server_send, &alice_keys.secret)
+        .unwrap();
+    assert_ne!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn ake_confirm_valid() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Ake::new();
+    let mut bob = Ake::new();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let (server_send, server_tag) = bob
+        .server_receive_with_tag(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let client_tag = alice
+        .client_confirm_with_tag(server_send, &server_tag, &alice_keys.secret)
+        .unwrap();
+    bob.server_confirm(&client_tag).unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn ake_server_confirm_without_exchange() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Ake::new();
+    let mut bob = Ake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    assert_eq!(
+        bob.server_confirm(&[0u8; CONFIRM_TAG_BYTES]),
+        Err(KyberError::KeyConfirmation)
+    );
+    // A client's own state has nothing to confirm either
+    alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    assert_eq!(
+        alice.server_confirm(&[0u8; CONFIRM_TAG_BYTES]),
+        Err(KyberError::KeyConfirmation)
+    );
+}
+
+#[test]
+fn ake_confirm_invalid_server_send_second_ciphertext() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Ake::new();
+    let mut bob = Ake::new();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let (mut server_send, server_tag) = bob
+        .server_receive_with_tag(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+        .unwrap();
+    server_send[KYBER_CIPHERTEXTBYTES..][..4].copy_from_slice(&[255u8; 4]);
+    assert_eq!(
+        alice.client_confirm_with_tag(server_send, &server_tag, &alice_keys.secret),
+        Err(KyberError::KeyConfirmation)
+    );
+}
+
+#[test]
+fn ake_psk() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    for (bob_psk, matches) in [(1, true), (2, false)] {
+        let mut alice: Ake = Ake::with_psk(KexVersion::V2, psk(1), b"id");
+        let mut bob: Ake = Ake::with_psk(KexVersion::V2, psk(bob_psk), b"id");
+        let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+        let server_send = bob
+            .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+            .unwrap();
+        alice
+            .client_confirm(server_send, &alice_keys.secret)
+            .unwrap();
+        assert_eq!(alice.shared_secret == bob.shared_secret, matches);
+    }
+}
+
 // Rng function fails on keypair
 #[test]
 fn ake_uake_failed_randombytes_keypair() {
@@ -0,0 +1,132 @@
+#![cfg(not(feature = "90s"))]
+
+use pqc_kyber::*;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::path::PathBuf;
+mod utils;
+use utils::*;
+
+// NIST ACVP vectors for FIPS 203, see ACVP/readme.md
+fn acvp_groups(file: &str, function: Option<&str>) -> Vec<Value> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.extend(&["tests", "ACVP", file]);
+    let json: Value =
+        serde_json::from_reader(File::open(path).expect("Error loading ACVP file")).unwrap();
+    let parameter_set = format!("ML-KEM-{}", 256 * KYBER_K);
+    json["testGroups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|g| g["parameterSet"] == parameter_set.as_str())
+        .filter(|g| g["function"].as_str() == function)
+        .cloned()
+        .collect()
+}
+
+fn field(value: &Value, name: &str) -> Vec<u8> {
+    decode_hex(value[name].as_str().unwrap())
+}
+
+// d, z -> ek, dk
+#[test]
+fn mlkem_acvp_keygen() {
+    let groups = acvp_groups("ML-KEM-keyGen-FIPS203.json", None);
+    assert_eq!(groups.len(), 1);
+    for test in groups[0]["tests"].as_array().unwrap() {
+        let mut seed = field(test, "d");
+        seed.extend(field(test, "z"));
+        let keys = mlkem::derive(&seed).unwrap();
+        assert_eq!(
+            keys.public.as_ref(),
+            &field(test, "ek")[..],
+            "tcId {}",
+            test["tcId"]
+        );
+        assert_eq!(
+            keys.secret.as_ref(),
+            &field(test, "dk")[..],
+            "tcId {}",
+            test["tcId"]
+        );
+    }
+}
+
+// ek, m -> c, K
+#[test]
+fn mlkem_acvp_encapsulation() {
+    let groups = acvp_groups("ML-KEM-encapDecap-FIPS203.json", Some("encapsulation"));
+    assert_eq!(groups.len(), 1);
+    for test in groups[0]["tests"].as_array().unwrap() {
+        let public = PublicKey::try_from(&field(test, "ek")[..]).unwrap();
+        let m = field(test, "m");
+        let (ct, ss) = mlkem::encapsulate(&public, &mut BufferRng(&m)).unwrap();
+        assert_eq!(ct.as_ref(), &field(test, "c")[..], "tcId {}", test["tcId"]);
+        assert_eq!(ss.as_ref(), &field(test, "k")[..], "tcId {}", test["tcId"]);
+    }
+}
+
+// dk, c -> K, modified ciphertexts give the implicit rejection key
+#[test]
+fn mlkem_acvp_decapsulation() {
+    let groups = acvp_groups("ML-KEM-encapDecap-FIPS203.json", Some("decapsulation"));
+    assert_eq!(groups.len(), 1);
+    let secret = SecretKey::try_from(&field(&groups[0], "dk")[..]).unwrap();
+    let tests = groups[0]["tests"].as_array().unwrap();
+    assert!(tests.iter().any(|t| t["reason"] == "modify ciphertext"));
+    for test in tests {
+        let ct = Ciphertext::try_from(&field(test, "c")[..]).unwrap();
+        let ss = mlkem::decapsulate(&ct, &secret).unwrap();
+        assert_eq!(ss.as_ref(), &field(test, "k")[..], "tcId {}", test["tcId"]);
+    }
+}
+
+#[test]
+fn mlkem_not_kyber() {
+    let mut rng = rand::thread_rng();
+    let keys = mlkem::keypair(&mut rng).unwrap();
+    let (ct, ss) = mlkem::encapsulate(&keys.public, &mut rng).unwrap();
+    assert_ne!(decapsulate(&ct, &keys.secret).unwrap(), ss);
+}
+
+#[test]
+fn mlkem_wrong_sizes() {
+    let pk = [1u8; KYBER_PUBLICKEYBYTES + 3];
+    assert_eq!(mlkem::derive(&[0u8; 32]), Err(KyberError::InvalidInput));
+    assert_eq!(
+        <PublicKey>::try_from(&pk[..]),
+        Err(KyberError::InvalidInput)
+    );
+    let ct = [1u8; KYBER_CIPHERTEXTBYTES - 1];
+    assert_eq!(
+        <Ciphertext>::try_from(&ct[..]),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn mlkem_input_checks() {
+    let mut rng = rand::thread_rng();
+    let mut keys = mlkem::keypair(&mut rng).unwrap();
+    let (ct, _) = mlkem::encapsulate(&keys.public, &mut rng).unwrap();
+    keys.secret.as_mut()[KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES] ^= 1;
+    assert_eq!(
+        mlkem::decapsulate(&ct, &keys.secret),
+        Err(KyberError::InvalidSecretKey)
+    );
+    // Last coefficient set to q
+    keys.public.as_mut()[KYBER_PUBLICKEYBYTES - KYBER_SYMBYTES - 2] = 0x10;
+    keys.public.as_mut()[KYBER_PUBLICKEYBYTES - KYBER_SYMBYTES - 1] = 0xd0;
+    assert_eq!(
+        mlkem::encapsulate(&keys.public, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+}
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("Hex string decoding"))
+        .collect::<Vec<u8>>()
+}
@@ -0,0 +1,157 @@
+use pqc_kyber::*;
+use std::convert::TryFrom;
+mod utils;
+use utils::*;
+
+fn encap_decap<P: KyberParams>() {
+    let mut rng = rand::thread_rng();
+    let keys = P::keypair(&mut rng).unwrap();
+    let (ct, ss1) = P::encapsulate(&keys.public, &mut rng).unwrap();
+    let ss2 = P::decapsulate(&ct, &keys.secret).unwrap();
+    assert_eq!(ss1, ss2);
+}
+
+fn prepared_encap<P: KyberParams>() {
+    let mut rng = rand::thread_rng();
+    let keys = P::keypair(&mut rng).unwrap();
+    let prepared = PreparedPublicKey::new(&keys.public).unwrap();
+    for i in 0..3u8 {
+        let coins = [i; 32];
+        let (ct1, ss1) = P::encapsulate(&keys.public, &mut BufferRng(&coins)).unwrap();
+        let (ct2, ss2) = prepared.encapsulate(&mut BufferRng(&coins)).unwrap();
+        assert_eq!(ct1, ct2);
+        assert_eq!(ss1, ss2);
+        assert_eq!(P::decapsulate(&ct2, &keys.secret).unwrap(), ss2);
+    }
+}
+
+fn prepared_decap<P: KyberParams>() {
+    let mut rng = rand::thread_rng();
+    let keys = P::keypair(&mut rng).unwrap();
+    let prepared = PreparedSecretKey::new(&keys.secret).unwrap();
+    let (mut ct, ss) = P::encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(prepared.decapsulate(&ct), ss);
+    // Implicit rejection matches too
+    ct.as_mut()[0] ^= 1;
+    let rejected = prepared.decapsulate(&ct);
+    assert_ne!(rejected, ss);
+    assert_eq!(P::decapsulate(&ct, &keys.secret).unwrap(), rejected);
+}
+
+fn uake<P: KyberParams>() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::<P>::default();
+    let mut bob = Uake::<P>::default();
+    let bob_keys = P::keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice.client_confirm(server_send).unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+fn ake<P: KyberParams>() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Ake::<P>::default();
+    let mut bob = Ake::<P>::default();
+    let alice_keys = P::keypair(&mut rng).unwrap();
+    let bob_keys = P::keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice
+        .client_confirm(server_send, &alice_keys.secret)
+        .unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn all_levels_encap_decap() {
+    encap_decap::<Kyber512>();
+    encap_decap::<Kyber768>();
+    encap_decap::<Kyber1024>();
+}
+
+#[test]
+fn all_levels_prepared_encap() {
+    prepared_encap::<Kyber512>();
+    prepared_encap::<Kyber768>();
+    prepared_encap::<Kyber1024>();
+}
+
+#[test]
+fn all_levels_prepared_decap() {
+    prepared_decap::<Kyber512>();
+    prepared_decap::<Kyber768>();
+    prepared_decap::<Kyber1024>();
+}
+
+#[test]
+fn all_levels_uake() {
+    uake::<Kyber512>();
+    uake::<Kyber768>();
+    uake::<Kyber1024>();
+}
+
+#[test]
+fn all_levels_ake() {
+    ake::<Kyber512>();
+    ake::<Kyber768>();
+    ake::<Kyber1024>();
+}
+
+#[test]
+fn level_sizes() {
+    assert_eq!(
+        (
+            Kyber512::PUBLICKEYBYTES,
+            Kyber512::SECRETKEYBYTES,
+            Kyber512::CIPHERTEXTBYTES
+        ),
+        (800, 1632, 768)
+    );
+    assert_eq!(
+        (
+            Kyber768::PUBLICKEYBYTES,
+            Kyber768::SECRETKEYBYTES,
+            Kyber768::CIPHERTEXTBYTES
+        ),
+        (1184, 2400, 1088)
+    );
+    assert_eq!(
+        (
+            Kyber1024::PUBLICKEYBYTES,
+            Kyber1024::SECRETKEYBYTES,
+            Kyber1024::CIPHERTEXTBYTES
+        ),
+        (1568, 3168, 1568)
+    );
+    assert_eq!(DefaultParams::PUBLICKEYBYTES, KYBER_PUBLICKEYBYTES);
+    assert_eq!(DefaultParams::K, KYBER_K);
+}
+
+#[test]
+fn mismatched_levels() {
+    let mut rng = rand::thread_rng();
+    let keys = Kyber512::keypair(&mut rng).unwrap();
+    assert_eq!(
+        PublicKey::<Kyber1024>::try_from(keys.public.as_ref()),
+        Err(KyberError::InvalidInput)
+    );
+    let (ct, _) = Kyber512::encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(
+        Ciphertext::<Kyber768>::try_from(ct.as_ref()),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn default_level_matches_free_functions() {
+    let mut rng = rand::thread_rng();
+    let keys: Keypair = DefaultParams::keypair(&mut rng).unwrap();
+    let (ct, ss1) = encapsulate(&keys.public, &mut rng).unwrap();
+    let ss2 = DefaultParams::decapsulate(&ct, &keys.secret).unwrap();
+    assert_eq!(ss1, ss2);
+}
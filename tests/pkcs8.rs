@@ -0,0 +1,131 @@
+#![cfg(all(feature = "pkcs8", not(feature = "90s")))]
+
+use pqc_kyber::pkcs8::{
+    spki, DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use pqc_kyber::*;
+
+#[cfg(feature = "kyber1024")]
+type OtherParams = Kyber512;
+#[cfg(not(feature = "kyber1024"))]
+type OtherParams = Kyber1024;
+
+// Keys written by OpenSSL 3.5 from the seed 00 01 .. 3f, the expanded forms
+// are compared by their SHA3-256 digest.
+#[cfg(not(any(feature = "kyber512", feature = "kyber1024")))]
+mod vectors {
+    use super::*;
+    use sha3::{Digest, Sha3_256};
+
+    const SEED_ONLY: &str = "3054020100300b060960864801650304040204428040000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f";
+    const EXPANDED: &str = "b499376d2378e6bb0becf82e555ef2fdbb97db75deed1a550f4a84f82915584d";
+    const BOTH: &str = "2d2ebd43e328dd2c0d78ec091a4d544857d90de600b8c7a9d93a2161c58e16b5";
+    const SPKI: &str = "f0405e9ee0251e1179947e7ad24515270233fe69d4223bfd3f9e26a00a1f9404";
+    // PrivateKeyInfo header of the both form, followed by the seed and expanded key
+    const BOTH_PREFIX: &str = "308209be020100300b0609608648016503040402048209aa308209a60440";
+    const EXPANDED_PREFIX: &str = "04820960";
+
+    #[test]
+    fn pkcs8_openssl_vectors() {
+        let der = decode_hex(SEED_ONLY);
+        let seed: Vec<u8> = (0..64).collect();
+        let sk: SecretKey = SecretKey::from_pkcs8_der(&der).unwrap();
+        assert_eq!(sk, mlkem::derive(&seed).unwrap().secret);
+        assert_eq!(
+            mlkem::seed_to_pkcs8_der(&seed).unwrap().as_bytes(),
+            &der[..]
+        );
+        assert_eq!(sha3(sk.to_pkcs8_der().unwrap().as_bytes()), EXPANDED);
+        let spki = public(&sk).to_public_key_der().unwrap();
+        assert_eq!(sha3(spki.as_bytes()), SPKI);
+
+        let mut both = decode_hex(BOTH_PREFIX);
+        both.extend_from_slice(&seed);
+        both.extend_from_slice(&decode_hex(EXPANDED_PREFIX));
+        both.extend_from_slice(sk.as_ref());
+        assert_eq!(sha3(&both), BOTH);
+        assert_eq!(SecretKey::<DefaultParams>::from_pkcs8_der(&both), Ok(sk));
+
+        // The seed and expanded key disagree
+        both[30] ^= 1;
+        assert!(SecretKey::<DefaultParams>::from_pkcs8_der(&both).is_err());
+    }
+
+    fn sha3(input: &[u8]) -> String {
+        Sha3_256::digest(input)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn decode_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("Hex string decoding"))
+            .collect::<Vec<u8>>()
+    }
+}
+
+#[test]
+fn pkcs8_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = mlkem::keypair(&mut rng).unwrap();
+
+    let der = keys.public.to_public_key_der().unwrap();
+    assert_eq!(
+        PublicKey::from_public_key_der(der.as_bytes()),
+        Ok(keys.public)
+    );
+    let pem = keys.public.to_public_key_pem(LineEnding::LF).unwrap();
+    assert_eq!(PublicKey::from_public_key_pem(&pem), Ok(keys.public));
+
+    let der = keys.secret.to_pkcs8_der().unwrap();
+    assert_eq!(
+        SecretKey::from_pkcs8_der(der.as_bytes()).unwrap(),
+        keys.secret
+    );
+    let pem = keys.secret.to_pkcs8_pem(LineEnding::LF).unwrap();
+    assert_eq!(SecretKey::from_pkcs8_pem(&pem).unwrap(), keys.secret);
+}
+
+#[test]
+fn pkcs8_wrong_level() {
+    let mut rng = rand::thread_rng();
+    let keys = mlkem::keypair(&mut rng).unwrap();
+    let der = keys.public.to_public_key_der().unwrap();
+    assert!(matches!(
+        PublicKey::<OtherParams>::from_public_key_der(der.as_bytes()),
+        Err(spki::Error::OidUnknown { .. })
+    ));
+    let der = keys.secret.to_pkcs8_der().unwrap();
+    assert!(SecretKey::<OtherParams>::from_pkcs8_der(der.as_bytes()).is_err());
+    let der = mlkem::seed_to_pkcs8_der(&[7u8; 64]).unwrap();
+    assert!(SecretKey::<OtherParams>::from_pkcs8_der(der.as_bytes()).is_err());
+}
+
+#[test]
+fn pkcs8_malformed() {
+    let mut rng = rand::thread_rng();
+    let mut keys = mlkem::keypair(&mut rng).unwrap();
+    assert_eq!(
+        mlkem::seed_to_pkcs8_der(&[7u8; 63]).map(|_| ()),
+        Err(KyberError::InvalidInput)
+    );
+
+    let der = keys.secret.to_pkcs8_der().unwrap();
+    let der = der.as_bytes();
+    assert!(SecretKey::<DefaultParams>::from_pkcs8_der(&der[..der.len() - 1]).is_err());
+    // Stored public key hash no longer matches
+    let mut tampered = der.to_vec();
+    tampered[der.len() - 40] ^= 1;
+    assert!(SecretKey::<DefaultParams>::from_pkcs8_der(&tampered).is_err());
+
+    // First coefficient set to q fails the modulus check
+    keys.public.as_mut()[0] = 0x01;
+    keys.public.as_mut()[1] = 0x0d;
+    let der = keys.public.to_public_key_der().unwrap();
+    assert_eq!(
+        PublicKey::<DefaultParams>::from_public_key_der(der.as_bytes()),
+        Err(spki::Error::KeyMalformed)
+    );
+}
@@ -5,10 +5,9 @@ set -e
 #
 # Variables: 
 # KAT - Runs the known answer tests
-# AVX2 - Runs avx2 code on x86 platforms with compiled GAS files
-# NASM - Runs avx2 code with both GAS and NASM files seperately
+# AVX2 - Runs avx2 code on x86 platforms
 
-# When setting AVX2 or NASM flags enable avx2 target features 
+# When setting the AVX2 flag optionally enable avx2 target features 
 # and LLVM address sanitser checks (requires nightly):
 # export RUSTFLAGS="${RUSTFLAGS:-} -Z sanitizer=address -C target-cpu=native -C target-feature=+aes,+avx2,+sse2,+sse4.1,+bmi2,+popcnt"
 
@@ -30,16 +29,10 @@ if [ -z "$AVX2" ]
     echo Not using AVX2 optimisations 
     OPT=("")
   else
-    echo Using AVX2 optimisations with GAS assembler
+    echo Using AVX2 optimisations
     OPT=("" "avx2")
 fi
 
-if [[ ! -z "$NASM" ]]
-  then
-    echo Using AVX2 optimisations with NASM assembler
-    OPT+=("nasm")
-fi
-
 # Print Headers
 announce(){
   title="#    $1    #"
@@ -0,0 +1,77 @@
+#![cfg(feature = "sealedbox")]
+
+use pqc_kyber::sealedbox::*;
+use pqc_kyber::*;
+mod utils;
+
+const MSG: &[u8] = b"Beauty is truth, truth beauty";
+const AAD: &[u8] = b"Count-0";
+
+// Kyber768 vector, the AEAD output was computed independently from the
+// shared secret with Python (pyca/cryptography)
+#[test]
+#[cfg(all(
+    not(feature = "kyber512"),
+    not(feature = "kyber1024"),
+    not(feature = "90s")
+))]
+fn sealedbox_vector() {
+    let seed = decode_hex("58a6b69331a666a1830ef5441881524e6399fffe864d96f4304ad1c15609fdf8a52ded6257fad2a872f4171edc5ffcc01ef760478a4e65a73988ea10aa4e3ca2");
+    let body = decode_hex("e633f468b339eedd5051bf3f5a47dc83f6917ee0ebf808716f5f428fe640a574e59c9aa647e08a15cbbce9c54e");
+    let keys = derive(&seed).unwrap();
+    let m = [0x11u8; 32];
+    let sealed = seal(&keys.public, MSG, AAD, &mut utils::BufferRng(&m)).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut utils::BufferRng(&m)).unwrap();
+    assert_eq!(&sealed[..KYBER_CIPHERTEXTBYTES], ct.as_ref());
+    assert_eq!(&sealed[KYBER_CIPHERTEXTBYTES..], &body[..]);
+    assert_eq!(open(&keys.secret, &sealed, AAD).unwrap(), MSG);
+}
+
+#[test]
+fn sealedbox_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    for msg in [&b""[..], MSG, &[0xabu8; 4096][..]] {
+        let sealed = seal(&keys.public, msg, AAD, &mut rng).unwrap();
+        assert_eq!(sealed.len(), msg.len() + SEALEDBOX_OVERHEAD);
+        assert_eq!(open(&keys.secret, &sealed, AAD).unwrap(), msg);
+    }
+}
+
+#[test]
+fn sealedbox_tampering() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let other = keypair(&mut rng).unwrap();
+    let sealed = seal(&keys.public, MSG, AAD, &mut rng).unwrap();
+
+    // Kyber ciphertext, message and tag
+    for i in [0, KYBER_CIPHERTEXTBYTES, sealed.len() - 1] {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 1;
+        assert_eq!(
+            open(&keys.secret, &tampered, AAD),
+            Err(KyberError::InvalidSealedBox)
+        );
+    }
+    assert_eq!(
+        open(&keys.secret, &sealed, b"other aad"),
+        Err(KyberError::InvalidSealedBox)
+    );
+    assert_eq!(
+        open(&other.secret, &sealed, AAD),
+        Err(KyberError::InvalidSealedBox)
+    );
+    assert_eq!(
+        open(&keys.secret, &sealed[..SEALEDBOX_OVERHEAD - 1], AAD),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[allow(dead_code)]
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("Hex string decoding"))
+        .collect::<Vec<u8>>()
+}
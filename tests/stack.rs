@@ -0,0 +1,46 @@
+use pqc_kyber::*;
+mod utils;
+use utils::*;
+
+// Stack budget of each operation with the low-stack feature, in bytes
+#[cfg(feature = "low-stack")]
+const LOW_STACK_BUDGET: usize = 1024 * (6 + 3 * KYBER_K) + AES_STACK;
+
+// The 90s mode AES implementations need a large stack of their own
+#[cfg(all(feature = "low-stack", feature = "90s"))]
+const AES_STACK: usize = 16 * 1024;
+#[cfg(all(feature = "low-stack", not(feature = "90s")))]
+const AES_STACK: usize = 0;
+
+#[test]
+fn stack_usage_of_kem() {
+    let seed = [7u8; 64];
+    let keys = derive(&seed).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut BufferRng(&seed)).unwrap();
+
+    let keypair_usage = stack_usage(move || {
+        keypair(&mut BufferRng(&seed)).unwrap();
+    });
+    let public = keys.public;
+    let encapsulate_usage = stack_usage(move || {
+        encapsulate(&public, &mut BufferRng(&seed)).unwrap();
+    });
+    let secret = keys.secret.clone();
+    let decapsulate_usage = stack_usage(move || {
+        decapsulate(&ct, &secret).unwrap();
+    });
+    for (op, usage) in [
+        ("keypair", keypair_usage),
+        ("encapsulate", encapsulate_usage),
+        ("decapsulate", decapsulate_usage),
+    ] {
+        assert!(
+            usage > 0 && usage < STACK_PAINT,
+            "{} used {} bytes",
+            op,
+            usage
+        );
+        #[cfg(feature = "low-stack")]
+        assert!(usage <= LOW_STACK_BUDGET, "{} used {} bytes", op, usage);
+    }
+}
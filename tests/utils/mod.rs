@@ -1,13 +1,10 @@
+#![allow(dead_code)]
+
 use rand_core::{CryptoRng, Error, RngCore};
 
+#[derive(Default)]
 pub struct FailingRng(u64);
 
-impl Default for FailingRng {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
 impl RngCore for FailingRng {
     fn next_u32(&mut self) -> u32 {
         self.next_u64() as u32
@@ -28,3 +25,87 @@ impl RngCore for FailingRng {
 }
 
 impl CryptoRng for FailingRng {}
+
+// Replays a fixed buffer, for deterministic test vectors
+pub struct BufferRng<'a>(pub &'a [u8]);
+
+impl RngCore for BufferRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        unimplemented!()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        unimplemented!()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let (head, tail) = self.0.split_at(dest.len());
+        dest.copy_from_slice(head);
+        self.0 = tail;
+        Ok(())
+    }
+}
+
+impl CryptoRng for BufferRng<'_> {}
+
+// Size of the stack region painted by stack_usage
+pub const STACK_PAINT: usize = 128 * 1024;
+
+// Peak stack usage of `f` in bytes, measured by painting the stack below
+// the caller, running `f` and finding the deepest byte it overwrote. Runs
+// on a fresh thread so the painted region is not shared with the harness.
+pub fn stack_usage<F: FnOnce() + Send + 'static>(f: F) -> usize {
+    on_painted_stack(f, |base| STACK_PAINT - untouched_stack(base))
+}
+
+// Contents of the painted region after running `f`, everything `f` left
+// behind on the stack
+pub fn stack_residue<F: FnOnce() + Send + 'static>(f: F) -> Vec<u8> {
+    on_painted_stack(f, |base| {
+        (0..STACK_PAINT)
+            .map(|i| unsafe { core::ptr::read_volatile((base + i) as *const u8) })
+            .collect()
+    })
+}
+
+fn on_painted_stack<F, T, I>(f: F, inspect: I) -> T
+where
+    F: FnOnce() + Send + 'static,
+    I: FnOnce(usize) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    std::thread::Builder::new()
+        .stack_size(4 * STACK_PAINT)
+        .spawn(move || {
+            let base = paint_stack();
+            f();
+            inspect(base)
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+// Fills a frame of STACK_PAINT bytes with a pattern, returning its lowest
+// address
+#[inline(never)]
+fn paint_stack() -> usize {
+    let mut region = [0u8; STACK_PAINT];
+    for b in region.iter_mut() {
+        unsafe { core::ptr::write_volatile(b, 0xa5) };
+    }
+    core::hint::black_box(&mut region).as_ptr() as usize
+}
+
+// Counts the bytes still holding the pattern from the lowest address of the
+// painted region, the stack grows down so those were never reached
+#[inline(never)]
+fn untouched_stack(base: usize) -> usize {
+    (0..STACK_PAINT)
+        .take_while(|&i| unsafe { core::ptr::read_volatile((base + i) as *const u8) } == 0xa5)
+        .count()
+}
@@ -0,0 +1,98 @@
+#![cfg(all(feature = "xwing", not(feature = "90s")))]
+
+use pqc_kyber::*;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::path::PathBuf;
+mod utils;
+use utils::*;
+
+// Test vectors from draft-connolly-cfrg-xwing-kem, see XWing/readme.md
+fn vectors() -> Vec<Value> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.extend(&["tests", "XWing", "test-vectors.json"]);
+    let json: Value =
+        serde_json::from_reader(File::open(path).expect("Error loading X-Wing vectors")).unwrap();
+    json.as_array().unwrap().clone()
+}
+
+fn field(value: &Value, name: &str) -> Vec<u8> {
+    decode_hex(value[name].as_str().unwrap())
+}
+
+#[test]
+fn xwing_vectors() {
+    let vectors = vectors();
+    assert_eq!(vectors.len(), 3);
+    for v in vectors.iter() {
+        let seed = field(v, "seed");
+        let keys = xwing::derive(&seed).unwrap();
+        assert_eq!(
+            keys.public.as_ref(),
+            &field(v, "pk")[..],
+            "Public key mismatch"
+        );
+        assert_eq!(
+            keys.secret.as_ref(),
+            &field(v, "sk")[..],
+            "Secret key mismatch"
+        );
+        assert_eq!(keys, xwing::keypair(&mut BufferRng(&seed)).unwrap());
+
+        let eseed = field(v, "eseed");
+        let (ct, ss) = xwing::encapsulate(&keys.public, &mut BufferRng(&eseed)).unwrap();
+        assert_eq!(ct.as_ref(), &field(v, "ct")[..], "Ciphertext mismatch");
+        assert_eq!(ss.as_ref(), &field(v, "ss")[..], "Shared secret mismatch");
+        assert_eq!(xwing::decapsulate(&ct, &keys.secret).unwrap(), ss);
+    }
+}
+
+#[test]
+fn xwing_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = xwing::keypair(&mut rng).unwrap();
+    let (mut ct, ss) = xwing::encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(xwing::decapsulate(&ct, &keys.secret).unwrap(), ss);
+    // Tampering with either component changes the shared secret
+    ct.as_mut()[0] ^= 1;
+    assert_ne!(xwing::decapsulate(&ct, &keys.secret).unwrap(), ss);
+    ct.as_mut()[0] ^= 1;
+    ct.as_mut()[xwing::XWING_CIPHERTEXTBYTES - 1] ^= 1;
+    assert_ne!(xwing::decapsulate(&ct, &keys.secret).unwrap(), ss);
+}
+
+#[test]
+fn xwing_wrong_sizes() {
+    assert_eq!(xwing::derive(&[0u8; 31]), Err(KyberError::InvalidInput));
+    let pk = [1u8; xwing::XWING_PUBLICKEYBYTES - 1];
+    assert_eq!(
+        xwing::PublicKey::try_from(&pk[..]),
+        Err(KyberError::InvalidInput)
+    );
+    let ct = [1u8; xwing::XWING_CIPHERTEXTBYTES + 1];
+    assert_eq!(
+        xwing::Ciphertext::try_from(&ct[..]),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn xwing_invalid_public_key() {
+    let mut rng = rand::thread_rng();
+    let mut keys = xwing::keypair(&mut rng).unwrap();
+    // First ML-KEM coefficient set to q
+    keys.public.as_mut()[0] = 0x01;
+    keys.public.as_mut()[1] = 0x0d;
+    assert_eq!(
+        xwing::encapsulate(&keys.public, &mut rng),
+        Err(KyberError::InvalidPublicKey)
+    );
+}
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("Hex string decoding"))
+        .collect::<Vec<u8>>()
+}
@@ -0,0 +1,182 @@
+// The fixslice key schedule of the aes crate loads the key into locals it
+// does not wipe, so only the bitsliced AES of this crate is checked
+#![cfg(all(feature = "zeroize", not(feature = "90s-fixslice")))]
+
+use pqc_kyber::*;
+#[cfg(feature = "90s")]
+use sha2::{Digest, Sha256 as HashH, Sha512 as HashG};
+#[cfg(not(feature = "90s"))]
+use sha3::{Digest, Sha3_256 as HashH, Sha3_512 as HashG};
+mod utils;
+use utils::*;
+
+// Static so the rng reads it without copying it to the stack
+static SEED: [u8; 64] = [0x5a; 64];
+
+// Looks for secrets in what the operations left on the stack, recomputed
+// here from the fixed rng output
+#[test]
+fn stack_is_wiped() {
+    let keys = derive(&SEED).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut BufferRng(&SEED)).unwrap();
+
+    let noiseseed = HashG::digest(&SEED[..KYBER_SYMBYTES])[KYBER_SYMBYTES..].to_vec();
+    let m = HashH::digest(&SEED[..KYBER_SYMBYTES]).to_vec();
+    let mut buf = m.clone();
+    buf.extend_from_slice(&HashH::digest(keys.public.as_ref()));
+    let kr = HashG::digest(&buf).to_vec();
+    let (prekey, coins) = kr.split_at(KYBER_SYMBYTES);
+
+    let mut leaked = Vec::new();
+    let residue = stack_residue(move || {
+        keypair(&mut BufferRng(&SEED)).unwrap();
+    });
+    find_secret(&mut leaked, &residue, "keypair", "noise seed", &noiseseed);
+    find_secret(
+        &mut leaked,
+        &residue,
+        "keypair",
+        "secret vector",
+        &secret_coefficients(&keys.secret),
+    );
+
+    let public = keys.public;
+    let residue = stack_residue(move || {
+        encapsulate(&public, &mut BufferRng(&SEED)).unwrap();
+    });
+    find_secret(
+        &mut leaked,
+        &residue,
+        "encapsulate",
+        "rng output",
+        &SEED[..KYBER_SYMBYTES],
+    );
+    find_secret(&mut leaked, &residue, "encapsulate", "message", &m);
+    find_secret(&mut leaked, &residue, "encapsulate", "pre-key", prekey);
+    find_secret(&mut leaked, &residue, "encapsulate", "coins", coins);
+
+    let secret = keys.secret.clone();
+    let residue = stack_residue(move || {
+        decapsulate(&ct, &secret).unwrap();
+    });
+    find_secret(&mut leaked, &residue, "decapsulate", "message", &m);
+    find_secret(&mut leaked, &residue, "decapsulate", "pre-key", prekey);
+    find_secret(&mut leaked, &residue, "decapsulate", "coins", coins);
+    find_secret(
+        &mut leaked,
+        &residue,
+        "decapsulate",
+        "secret vector",
+        &secret_coefficients(&keys.secret),
+    );
+
+    let secret = keys.secret.clone();
+    // Bound so it is dropped, and wiped, where it was returned
+    let residue = stack_residue(move || {
+        let _prepared = PreparedSecretKey::new(&secret);
+    });
+    find_secret(
+        &mut leaked,
+        &residue,
+        "prepared new",
+        "secret vector",
+        &secret_coefficients(&keys.secret),
+    );
+
+    let secret = keys.secret.clone();
+    let residue = stack_residue(move || {
+        let prepared = PreparedSecretKey::new(&secret);
+        prepared.as_ref().unwrap().decapsulate(&ct);
+    });
+    find_secret(&mut leaked, &residue, "prepared decapsulate", "message", &m);
+    find_secret(
+        &mut leaked,
+        &residue,
+        "prepared decapsulate",
+        "pre-key",
+        prekey,
+    );
+    find_secret(
+        &mut leaked,
+        &residue,
+        "prepared decapsulate",
+        "secret vector",
+        &secret_coefficients(&keys.secret),
+    );
+
+    assert!(leaked.is_empty(), "left on the stack: {:?}", leaked);
+}
+
+#[cfg(not(feature = "90s"))]
+#[test]
+fn mlkem_stack_is_wiped() {
+    let keys = mlkem::derive(&SEED).unwrap();
+    let (ct, _) = mlkem::encapsulate(&keys.public, &mut BufferRng(&SEED)).unwrap();
+
+    let m = &SEED[..KYBER_SYMBYTES];
+    let mut buf = m.to_vec();
+    buf.extend_from_slice(&HashH::digest(keys.public.as_ref()));
+    let coins = HashG::digest(&buf)[KYBER_SYMBYTES..].to_vec();
+    // FIPS 203 domain separates the key generation seed with k
+    let mut d = SEED[..KYBER_SYMBYTES].to_vec();
+    d.push(KYBER_K as u8);
+    let noiseseed = HashG::digest(&d)[KYBER_SYMBYTES..].to_vec();
+
+    let mut leaked = Vec::new();
+    let residue = stack_residue(move || {
+        mlkem::keypair(&mut BufferRng(&SEED)).unwrap();
+    });
+    find_secret(&mut leaked, &residue, "keypair", "noise seed", &noiseseed);
+
+    let public = keys.public;
+    let residue = stack_residue(move || {
+        mlkem::encapsulate(&public, &mut BufferRng(&SEED)).unwrap();
+    });
+    find_secret(&mut leaked, &residue, "encapsulate", "message", m);
+    find_secret(&mut leaked, &residue, "encapsulate", "coins", &coins);
+
+    let secret = keys.secret.clone();
+    let residue = stack_residue(move || {
+        mlkem::decapsulate(&ct, &secret).unwrap();
+    });
+    find_secret(&mut leaked, &residue, "decapsulate", "message", m);
+    find_secret(&mut leaked, &residue, "decapsulate", "coins", &coins);
+
+    assert!(leaked.is_empty(), "left on the stack: {:?}", leaked);
+}
+
+fn find_secret(leaked: &mut Vec<String>, residue: &[u8], op: &str, name: &str, secret: &[u8]) {
+    if residue.windows(secret.len()).any(|w| w == secret) {
+        leaked.push(format!("{} {}", op, name));
+    }
+}
+
+// First 16 coefficients of the unpacked secret vector as the backend holds
+// them in memory, the avx2 code interleaves them with a stride of 8
+fn secret_coefficients(sk: &SecretKey) -> Vec<u8> {
+    let a = sk.as_ref();
+    let coeffs: Vec<u16> = (0..64)
+        .flat_map(|i| {
+            let t0 = (a[3 * i] as u16 | (a[3 * i + 1] as u16) << 8) & 0xfff;
+            let t1 = (a[3 * i + 1] as u16 >> 4 | (a[3 * i + 2] as u16) << 4) & 0xfff;
+            [t0, t1]
+        })
+        .collect();
+    let stride = if avx2_backend() { 8 } else { 1 };
+    (0..16)
+        .map(|i| coeffs[stride * i])
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+fn avx2_backend() -> bool {
+    is_x86_feature_detected!("avx2")
+        && is_x86_feature_detected!("bmi2")
+        && is_x86_feature_detected!("popcnt")
+}
+
+#[cfg(not(all(target_arch = "x86_64", feature = "avx2")))]
+fn avx2_backend() -> bool {
+    false
+}